@@ -1,21 +1,10 @@
-use super::random::Rand;
+pub use tree_sitter::Edit;
 
-#[derive(Debug)]
-pub struct Edit {
-    pub position: usize,
-    pub deleted_length: usize,
-    pub inserted_text: Vec<u8>,
-}
+use super::random::Rand;
 
 #[must_use]
 pub fn invert_edit(input: &[u8], edit: &Edit) -> Edit {
-    let position = edit.position;
-    let removed_content = &input[position..(position + edit.deleted_length)];
-    Edit {
-        position,
-        deleted_length: edit.inserted_text.len(),
-        inserted_text: removed_content.to_vec(),
-    }
+    edit.invert(input).unwrap()
 }
 
 pub fn get_random_edit(rand: &mut Rand, input: &[u8]) -> Edit {
@@ -453,7 +453,7 @@ pub fn highlight(
         let mut style_stack = vec![theme.default_style().ansi];
         for event in events {
             match event? {
-                HighlightEvent::HighlightStart(highlight) => {
+                HighlightEvent::HighlightStart { highlight, .. } => {
                     style_stack.push(theme.styles[highlight.0].ansi);
                 }
                 HighlightEvent::HighlightEnd => {
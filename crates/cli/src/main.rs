@@ -2,6 +2,7 @@ use std::{
     collections::HashSet,
     env, fs,
     path::{Path, PathBuf},
+    time::Instant,
 };
 
 use anstyle::{AnsiColor, Color, Style};
@@ -27,7 +28,7 @@ use tree_sitter_cli::{
     playground,
     query::{self, QueryFileOptions},
     tags::{self, TagsOptions},
-    test::{self, TestOptions, TestStats, TestSummary},
+    test::{self, TapSummary, TestFormat, TestOptions, TestStats, TestSummary},
     test_highlight, test_tags, util,
     version::{self, BumpLevel},
     wasm,
@@ -289,6 +290,14 @@ struct Parse {
     /// Omit ranges in the output
     #[arg(long)]
     pub no_ranges: bool,
+    /// Parse the files in parallel using the given number of threads. Syntax
+    /// trees are not printed, and each file's summary line is printed as soon as
+    /// the file is parsed
+    #[arg(
+        long,
+        conflicts_with_all = ["output_dot", "output_xml", "output_cst", "debug", "debug_graph", "wasm"]
+    )]
+    pub jobs: Option<usize>,
 }
 
 #[derive(ValueEnum, Clone)]
@@ -359,6 +368,9 @@ struct Test {
     /// Output the test summary in a JSON format
     #[arg(long)]
     pub json_summary: bool,
+    /// Output the test results in a machine-readable format
+    #[arg(long, value_enum, conflicts_with = "json_summary")]
+    pub format: Option<TestFormat>,
 }
 
 #[derive(Args)]
@@ -458,7 +470,7 @@ struct Query {
     /// Measure execution time
     #[arg(long, short)]
     pub time: bool,
-    /// Suppress main output
+    /// Suppress the results and the names of the source files
     #[arg(long, short)]
     pub quiet: bool,
     /// The path to a file with paths to source file(s)
@@ -487,6 +499,12 @@ struct Query {
     /// Order by captures instead of matches
     #[arg(long, short)]
     pub captures: bool,
+    /// Group the captures by match, which is the default
+    #[arg(long, short, conflicts_with = "captures")]
+    pub matches: bool,
+    /// Print the results for each source file as a line of JSON
+    #[arg(long, short = 'j')]
+    pub json: bool,
     /// Whether to run query tests or not
     #[arg(long)]
     pub test: bool,
@@ -1185,28 +1203,71 @@ impl Parse {
                     .unwrap_or(0);
                 options.stats.source_count = paths.len();
 
-                for path in &paths {
-                    let path = Path::new(&path);
-                    let language = loader
-                        .select_language(
-                            Some(path),
-                            current_dir,
-                            self.scope.as_deref(),
-                            lib_info.as_ref(),
-                        )
-                        .with_context(|| {
-                            anyhow!("Failed to load language for path \"{}\"", path.display())
-                        })?;
-
-                    parse::parse_file_at_path(
-                        &mut parser,
-                        &language,
-                        path,
-                        &path.display().to_string(),
+                let jobs = self.jobs.unwrap_or(1);
+                if jobs > 1 {
+                    let languages = paths
+                        .iter()
+                        .map(|path| {
+                            loader
+                                .select_language(
+                                    Some(path),
+                                    current_dir,
+                                    self.scope.as_deref(),
+                                    lib_info.as_ref(),
+                                )
+                                .with_context(|| {
+                                    anyhow!(
+                                        "Failed to load language for path \"{}\"",
+                                        path.display()
+                                    )
+                                })
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+
+                    let start_time = Instant::now();
+                    let summaries = parse::parse_files_in_parallel(
+                        &paths,
+                        &languages,
+                        jobs,
                         max_path_length,
-                        &mut options,
+                        &options,
                     )?;
-                    update_stats(options.stats);
+                    let wall_duration = start_time.elapsed();
+                    for summary in summaries {
+                        options.stats.parse_summaries.push(summary);
+                        update_stats(options.stats);
+                    }
+                    if should_track_stats {
+                        println!(
+                            "\nParsed {} files with {jobs} jobs in {:.2} ms",
+                            paths.len(),
+                            wall_duration.as_micros() as f64 / 1e3
+                        );
+                    }
+                } else {
+                    for path in &paths {
+                        let path = Path::new(&path);
+                        let language = loader
+                            .select_language(
+                                Some(path),
+                                current_dir,
+                                self.scope.as_deref(),
+                                lib_info.as_ref(),
+                            )
+                            .with_context(|| {
+                                anyhow!("Failed to load language for path \"{}\"", path.display())
+                            })?;
+
+                        parse::parse_file_at_path(
+                            &mut parser,
+                            &language,
+                            path,
+                            &path.display().to_string(),
+                            max_path_length,
+                            &mut options,
+                        )?;
+                        update_stats(options.stats);
+                    }
                 }
             }
 
@@ -1296,20 +1357,25 @@ impl Parse {
 
 /// In case an error is encountered, prints out the contents of `test_summary` and
 /// propagates the error
-fn check_test(
-    test_result: Result<()>,
-    test_summary: &TestSummary,
-    json_summary: bool,
-) -> Result<()> {
-    if let Err(e) = test_result {
-        if json_summary {
+fn print_test_summary(test_summary: &TestSummary, format: Option<TestFormat>) {
+    match format {
+        Some(TestFormat::Json) => {
             let json_summary = serde_json::to_string_pretty(test_summary)
                 .expect("Failed to encode summary to JSON");
             println!("{json_summary}");
-        } else {
-            println!("{test_summary}");
         }
+        Some(TestFormat::Tap) => print!("{}", TapSummary(test_summary)),
+        None => println!("{test_summary}"),
+    }
+}
 
+fn check_test(
+    test_result: Result<()>,
+    test_summary: &TestSummary,
+    format: Option<TestFormat>,
+) -> Result<()> {
+    if let Err(e) = test_result {
+        print_test_summary(test_summary, format);
         Err(e)?;
     }
 
@@ -1320,6 +1386,11 @@ impl Test {
     fn run(self, mut loader: loader::Loader, current_dir: &Path) -> Result<()> {
         let config = Config::load(self.config_path)?;
         let stat = self.stat.unwrap_or_default();
+        let format = if self.json_summary {
+            Some(TestFormat::Json)
+        } else {
+            self.format
+        };
 
         loader.debug_build(self.debug_build);
         loader.force_rebuild(self.rebuild || self.grammar_path.is_some());
@@ -1357,8 +1428,12 @@ impl Test {
         parser.set_language(language)?;
 
         let test_dir = current_dir.join("test");
-        let mut test_summary =
-            TestSummary::new(stat, self.update, self.overview_only, self.json_summary);
+        let mut test_summary = TestSummary::new(
+            stat,
+            self.update,
+            self.overview_only,
+            format == Some(TestFormat::Json),
+        );
         test_summary.use_markers = self.show_diff_markers;
 
         // Run the corpus tests. Look for them in `test/corpus`.
@@ -1381,7 +1456,7 @@ impl Test {
             check_test(
                 test::run_tests_at_path(&mut parser, &opts, &mut test_summary),
                 &test_summary,
-                self.json_summary,
+                format,
             )?;
             test_summary.test_num = 1;
         } else {
@@ -1394,7 +1469,7 @@ impl Test {
             check_test(
                 test::check_queries_at_path(language, &query_dir),
                 &test_summary,
-                self.json_summary,
+                format,
             )?;
             test_summary.test_num = 1;
         }
@@ -1413,7 +1488,7 @@ impl Test {
                     &mut test_summary,
                 ),
                 &test_summary,
-                self.json_summary,
+                format,
             )?;
             parser = highlighter.parser;
             test_summary.test_num = 1;
@@ -1432,7 +1507,7 @@ impl Test {
                     &mut test_summary,
                 ),
                 &test_summary,
-                self.json_summary,
+                format,
             )?;
             test_summary.test_num = 1;
         }
@@ -1478,7 +1553,7 @@ impl Test {
                             Some(&mut test_summary),
                         ),
                         &test_summary,
-                        self.json_summary,
+                        format,
                     )?;
                 }
                 if !entries.is_empty() {
@@ -1488,13 +1563,7 @@ impl Test {
         }
         test_summary.test_num = 1;
 
-        if self.json_summary {
-            let json_summary = serde_json::to_string_pretty(&test_summary)
-                .expect("Failed to encode test summary to JSON");
-            println!("{json_summary}");
-        } else {
-            println!("{test_summary}");
-        }
+        print_test_summary(&test_summary, format);
 
         Ok(())
     }
@@ -1573,6 +1642,9 @@ impl Query {
             Point::new(row, 0)
         })?;
 
+        // `--matches` and `--captures` conflict, and grouping by match is the default.
+        let ordered_captures = self.captures && !self.matches;
+
         let cancellation_flag = util::cancel_on_signal();
 
         let input = get_input(
@@ -1592,7 +1664,7 @@ impl Query {
                 )?;
 
                 let opts = QueryFileOptions {
-                    ordered_captures: self.captures,
+                    ordered_captures,
                     byte_range,
                     point_range,
                     containing_byte_range,
@@ -1600,6 +1672,7 @@ impl Query {
                     quiet: self.quiet,
                     print_time: self.time,
                     stdin: false,
+                    json: self.json,
                 };
                 for path in paths {
                     query::query_file_at_path(
@@ -1637,7 +1710,7 @@ impl Query {
                         .ok_or_else(|| anyhow!("No language found"))?
                 };
                 let opts = QueryFileOptions {
-                    ordered_captures: self.captures,
+                    ordered_captures,
                     byte_range,
                     point_range,
                     containing_byte_range,
@@ -1645,19 +1718,22 @@ impl Query {
                     quiet: self.quiet,
                     print_time: self.time,
                     stdin: true,
+                    json: self.json,
                 };
                 query::query_file_at_path(language, &path, &name, query_path, &opts, None)?;
                 fs::remove_file(path)?;
             }
             CliInput::Stdin(contents) => {
                 // Place user input and query output on separate lines
-                println!();
+                if !self.quiet && !self.json {
+                    println!();
+                }
 
                 let path = get_tmp_source_file(&contents)?;
                 let language =
                     loader.select_language(None, current_dir, None, lib_info.as_ref())?;
                 let opts = QueryFileOptions {
-                    ordered_captures: self.captures,
+                    ordered_captures,
                     byte_range,
                     point_range,
                     containing_byte_range,
@@ -1665,6 +1741,7 @@ impl Query {
                     quiet: self.quiet,
                     print_time: self.time,
                     stdin: true,
+                    json: self.json,
                 };
                 query::query_file_at_path(&language, &path, "stdin", query_path, &opts, None)?;
                 fs::remove_file(path)?;
@@ -4,6 +4,7 @@ use std::{
     ops::ControlFlow,
     path::{Path, PathBuf},
     sync::atomic::{AtomicUsize, Ordering},
+    thread,
     time::{Duration, Instant},
 };
 
@@ -14,11 +15,11 @@ use log::info;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tree_sitter::{
-    InputEdit, Language, LogType, ParseOptions, ParseState, Parser, Point, Range, Tree, TreeCursor,
-    ffi,
+    Edit, InputEdit, Language, LogType, ParseOptions, ParseState, Parser, Point, Range, Tree,
+    TreeCursor, ffi,
 };
 
-use crate::{fuzz::edits::Edit, paint::paint, util};
+use crate::{paint::paint, util};
 
 #[derive(Debug, Default, Serialize, JsonSchema)]
 pub struct Stats {
@@ -457,56 +458,7 @@ pub fn parse_file_at_path(
         let mut cursor = tree.walk();
 
         if opts.output == ParseOutput::Normal {
-            let mut needs_newline = false;
-            let mut indent_level = 0;
-            let mut did_visit_children = false;
-            loop {
-                let node = cursor.node();
-                let is_named = node.is_named();
-                if did_visit_children {
-                    if is_named {
-                        stdout.write_all(b")")?;
-                        needs_newline = true;
-                    }
-                    if cursor.goto_next_sibling() {
-                        did_visit_children = false;
-                    } else if cursor.goto_parent() {
-                        did_visit_children = true;
-                        indent_level -= 1;
-                    } else {
-                        break;
-                    }
-                } else {
-                    if is_named {
-                        if needs_newline {
-                            stdout.write_all(b"\n")?;
-                        }
-                        for _ in 0..indent_level {
-                            stdout.write_all(b"  ")?;
-                        }
-                        let start = node.start_position();
-                        let end = node.end_position();
-                        if let Some(field_name) = cursor.field_name() {
-                            write!(&mut stdout, "{field_name}: ")?;
-                        }
-                        write!(&mut stdout, "({}", node.kind())?;
-                        if !opts.no_ranges {
-                            write!(
-                                &mut stdout,
-                                " [{}, {}] - [{}, {}]",
-                                start.row, start.column, end.row, end.column
-                            )?;
-                        }
-                        needs_newline = true;
-                    }
-                    if cursor.goto_first_child() {
-                        did_visit_children = false;
-                        indent_level += 1;
-                    } else {
-                        did_visit_children = true;
-                    }
-                }
-            }
+            render_sexp(&mut cursor, opts.no_ranges, &mut stdout)?;
             cursor.reset(tree.root_node());
             writeln!(&mut stdout)?;
         }
@@ -577,6 +529,9 @@ pub fn parse_file_at_path(
                         if let Some(field_name) = cursor.field_name() {
                             write!(&mut stdout, " field=\"{field_name}\"")?;
                         }
+                        if node.is_missing() {
+                            write!(&mut stdout, " missing=\"true\"")?;
+                        }
                         let start = node.start_position();
                         let end = node.end_position();
                         write!(
@@ -747,6 +702,83 @@ pub fn parse_file_at_path(
     Ok(())
 }
 
+/// Parse many files across a number of threads, each with its own [`Parser`].
+///
+/// Syntax trees are not printed. The summary line for each file is printed as
+/// soon as the file is parsed, so these lines can appear in any order, but each
+/// one starts with the file's path. The returned summaries are in the same order
+/// as `paths`.
+pub fn parse_files_in_parallel(
+    paths: &[PathBuf],
+    languages: &[Language],
+    jobs: usize,
+    max_path_length: usize,
+    opts: &ParseFileOptions,
+) -> Result<Vec<ParseSummary>> {
+    let next_index = &AtomicUsize::new(0);
+    let results = thread::scope(|scope| {
+        #[expect(
+            clippy::needless_collect,
+            reason = "every worker must be spawned before the first one is joined"
+        )]
+        let workers = (0..jobs.clamp(1, paths.len().max(1)))
+            .map(|_| {
+                scope.spawn(move || -> Result<Vec<(usize, ParseSummary)>> {
+                    let mut parser = Parser::new();
+                    let mut stats = ParseStats::default();
+                    let mut worker_opts = ParseFileOptions {
+                        edits: opts.edits,
+                        output: ParseOutput::Quiet,
+                        stats: &mut stats,
+                        print_time: opts.print_time,
+                        timeout: opts.timeout,
+                        debug: ParseDebugType::Quiet,
+                        debug_graph: false,
+                        cancellation_flag: opts.cancellation_flag,
+                        encoding: opts.encoding,
+                        open_log: false,
+                        no_ranges: opts.no_ranges,
+                        parse_theme: opts.parse_theme,
+                    };
+
+                    let mut summaries = Vec::new();
+                    loop {
+                        let index = next_index.fetch_add(1, Ordering::SeqCst);
+                        let Some(path) = paths.get(index) else {
+                            break;
+                        };
+                        parse_file_at_path(
+                            &mut parser,
+                            &languages[index],
+                            path,
+                            &path.display().to_string(),
+                            max_path_length,
+                            &mut worker_opts,
+                        )?;
+                        if let Some(summary) = worker_opts.stats.parse_summaries.pop() {
+                            summaries.push((index, summary));
+                        }
+                    }
+                    Ok(summaries)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .map_err(|_| anyhow!("A parsing thread panicked"))?
+            })
+            .collect::<Result<Vec<_>>>()
+    })?;
+
+    let mut summaries = results.into_iter().flatten().collect::<Vec<_>>();
+    summaries.sort_unstable_by_key(|(index, _)| *index);
+    Ok(summaries.into_iter().map(|(_, summary)| summary).collect())
+}
+
 const fn escape_invisible(c: char) -> Option<&'static str> {
     Some(match c {
         '\n' => "\\n",
@@ -768,6 +800,74 @@ const fn escape_delimiter(c: char) -> Option<&'static str> {
     })
 }
 
+/// Render the tree below the cursor as an indented S-expression with the node ranges, like
+/// [`Node::to_sexp`], but with one named node per line.
+///
+/// Like in [`Node::to_sexp`], nodes that the parser inserted during error recovery are marked
+/// with `MISSING`, and they are rendered even if they are anonymous, so that they can be told
+/// apart from nodes that are present in the source code.
+///
+/// [`Node::to_sexp`]: tree_sitter::Node::to_sexp
+pub fn render_sexp(cursor: &mut TreeCursor, no_ranges: bool, out: &mut impl Write) -> Result<()> {
+    let mut needs_newline = false;
+    let mut indent_level = 0;
+    let mut did_visit_children = false;
+    loop {
+        let node = cursor.node();
+        let is_visible = node.is_named() || node.is_missing();
+        if did_visit_children {
+            if is_visible {
+                out.write_all(b")")?;
+                needs_newline = true;
+            }
+            if cursor.goto_next_sibling() {
+                did_visit_children = false;
+            } else if cursor.goto_parent() {
+                did_visit_children = true;
+                indent_level -= 1;
+            } else {
+                break;
+            }
+        } else {
+            if is_visible {
+                if needs_newline {
+                    out.write_all(b"\n")?;
+                }
+                for _ in 0..indent_level {
+                    out.write_all(b"  ")?;
+                }
+                let start = node.start_position();
+                let end = node.end_position();
+                if let Some(field_name) = cursor.field_name() {
+                    write!(out, "{field_name}: ")?;
+                }
+                if !node.is_missing() {
+                    write!(out, "({}", node.kind())?;
+                } else if node.is_named() {
+                    write!(out, "(MISSING {}", node.kind())?;
+                } else {
+                    write!(out, "(MISSING \"{}\"", node.kind().escape_default())?;
+                }
+                if !no_ranges {
+                    write!(
+                        out,
+                        " [{}, {}] - [{}, {}]",
+                        start.row, start.column, end.row, end.column
+                    )?;
+                }
+                needs_newline = true;
+            }
+            if cursor.goto_first_child() {
+                did_visit_children = false;
+                indent_level += 1;
+            } else {
+                did_visit_children = true;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn render_cst<'a, 'b: 'a>(
     source_code: &[u8],
     tree: &'b Tree,
@@ -1072,23 +1172,7 @@ fn cst_render_node(
 }
 
 pub fn perform_edit(tree: &mut Tree, input: &mut Vec<u8>, edit: &Edit) -> Result<InputEdit> {
-    let start_byte = edit.position;
-    let old_end_byte = edit.position + edit.deleted_length;
-    let new_end_byte = edit.position + edit.inserted_text.len();
-    let start_position = position_for_offset(input, start_byte)?;
-    let old_end_position = position_for_offset(input, old_end_byte)?;
-    input.splice(start_byte..old_end_byte, edit.inserted_text.iter().copied());
-    let new_end_position = position_for_offset(input, new_end_byte)?;
-    let edit = InputEdit {
-        start_byte,
-        old_end_byte,
-        new_end_byte,
-        start_position,
-        old_end_position,
-        new_end_position,
-    };
-    tree.edit(&edit);
-    Ok(edit)
+    Ok(tree_sitter::apply_edit(tree, input, edit)?)
 }
 
 fn parse_edit_flag(source_code: &[u8], flag: &str) -> Result<Edit> {
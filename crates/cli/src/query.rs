@@ -8,8 +8,9 @@ use std::{
 
 use anyhow::{Context, Result};
 use log::warn;
+use serde::Serialize;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Language, Parser, Point, Query, QueryCursor};
+use tree_sitter::{Language, Node, Parser, Point, Query, QueryCursor};
 
 use crate::{
     query_testing::{self, to_utf8_point},
@@ -26,6 +27,67 @@ pub struct QueryFileOptions {
     pub quiet: bool,
     pub print_time: bool,
     pub stdin: bool,
+    pub json: bool,
+}
+
+/// The results of running a query on one source file, printed as a single line of JSON.
+/// Only the array for the selected mode is present, and it is present even if it is empty.
+#[derive(Serialize)]
+struct QueryFileJson<'a> {
+    path: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matches: Option<Vec<QueryMatchJson<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    captures: Option<Vec<QueryCaptureJson<'a>>>,
+}
+
+#[derive(Serialize)]
+struct QueryMatchJson<'a> {
+    pattern: usize,
+    captures: Vec<QueryCaptureJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct QueryCaptureJson<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<usize>,
+    name: &'a str,
+    kind: &'a str,
+    start_byte: usize,
+    end_byte: usize,
+    start_point: PointJson,
+    end_point: PointJson,
+    text: &'a str,
+}
+
+#[derive(Serialize)]
+struct PointJson {
+    row: usize,
+    column: usize,
+}
+
+impl From<Point> for PointJson {
+    fn from(point: Point) -> Self {
+        Self {
+            row: point.row,
+            column: point.column,
+        }
+    }
+}
+
+impl<'a> QueryCaptureJson<'a> {
+    fn new(pattern: Option<usize>, name: &'a str, node: Node, source_code: &'a [u8]) -> Self {
+        Self {
+            pattern,
+            name,
+            kind: node.kind(),
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            start_point: node.start_position().into(),
+            end_point: node.end_position().into(),
+            text: node.utf8_text(source_code).unwrap_or(""),
+        }
+    }
 }
 
 pub fn query_file_at_path(
@@ -62,8 +124,9 @@ pub fn query_file_at_path(
 
     let mut results = Vec::new();
     let should_test = test_summary.is_some();
+    let print_text = !opts.quiet && !opts.json && !should_test;
 
-    if !should_test && !opts.stdin {
+    if print_text && !opts.stdin {
         writeln!(&mut stdout, "{name}")?;
     }
 
@@ -71,23 +134,36 @@ pub fn query_file_at_path(
         fs::read(path).with_context(|| format!("Error reading source file {}", path.display()))?;
     let tree = parser.parse(&source_code, None).unwrap();
 
+    let mut json_matches = Vec::new();
+    let mut json_captures = Vec::new();
+
     let start = Instant::now();
     if opts.ordered_captures {
         let mut captures = query_cursor.captures(&query, tree.root_node(), source_code.as_slice());
         while let Some((mat, capture_index)) = captures.next() {
             let capture = mat.captures[*capture_index];
             let capture_name = &query.capture_names()[capture.index as usize];
-            if !opts.quiet && !should_test {
+            if print_text {
                 writeln!(
                     &mut stdout,
-                    "    pattern: {:>2}, capture: {} - {capture_name}, start: {}, end: {}, text: `{}`",
+                    "    pattern: {:>2}, capture: {} - {capture_name}, kind: {}, start: {}, end: {}, bytes: {:?}, text: `{}`",
                     mat.pattern_index,
                     capture.index,
+                    capture.node.kind(),
                     capture.node.start_position(),
                     capture.node.end_position(),
+                    capture.node.byte_range(),
                     capture.node.utf8_text(&source_code).unwrap_or("")
                 )?;
             }
+            if opts.json {
+                json_captures.push(QueryCaptureJson::new(
+                    Some(mat.pattern_index),
+                    capture_name,
+                    capture.node,
+                    &source_code,
+                ));
+            }
             if should_test {
                 results.push(query_testing::CaptureInfo {
                     name: (*capture_name).to_string(),
@@ -99,28 +175,39 @@ pub fn query_file_at_path(
     } else {
         let mut matches = query_cursor.matches(&query, tree.root_node(), source_code.as_slice());
         while let Some(m) = matches.next() {
-            if !opts.quiet && !should_test {
+            if print_text {
                 writeln!(&mut stdout, "  pattern: {}", m.pattern_index)?;
             }
+            let mut match_captures = Vec::new();
             for capture in m.captures {
                 let start = capture.node.start_position();
                 let end = capture.node.end_position();
+                let kind = capture.node.kind();
+                let bytes = capture.node.byte_range();
                 let capture_name = &query.capture_names()[capture.index as usize];
-                if !opts.quiet && !should_test {
+                if print_text {
                     if end.row == start.row {
                         writeln!(
                             &mut stdout,
-                            "    capture: {} - {capture_name}, start: {start}, end: {end}, text: `{}`",
+                            "    capture: {} - {capture_name}, kind: {kind}, start: {start}, end: {end}, bytes: {bytes:?}, text: `{}`",
                             capture.index,
                             capture.node.utf8_text(&source_code).unwrap_or("")
                         )?;
                     } else {
                         writeln!(
                             &mut stdout,
-                            "    capture: {capture_name}, start: {start}, end: {end}",
+                            "    capture: {capture_name}, kind: {kind}, start: {start}, end: {end}, bytes: {bytes:?}",
                         )?;
                     }
                 }
+                if opts.json {
+                    match_captures.push(QueryCaptureJson::new(
+                        None,
+                        capture_name,
+                        capture.node,
+                        &source_code,
+                    ));
+                }
                 if should_test {
                     results.push(query_testing::CaptureInfo {
                         name: (*capture_name).to_string(),
@@ -129,8 +216,22 @@ pub fn query_file_at_path(
                     });
                 }
             }
+            if opts.json {
+                json_matches.push(QueryMatchJson {
+                    pattern: m.pattern_index,
+                    captures: match_captures,
+                });
+            }
         }
     }
+    if opts.json && !opts.quiet && !should_test {
+        let json = QueryFileJson {
+            path: name,
+            matches: (!opts.ordered_captures).then_some(json_matches),
+            captures: opts.ordered_captures.then_some(json_captures),
+        };
+        writeln!(&mut stdout, "{}", serde_json::to_string(&json)?)?;
+    }
     if query_cursor.did_exceed_match_limit() {
         warn!("Query exceeded maximum number of in-progress captures!");
     }
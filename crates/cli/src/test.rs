@@ -159,6 +159,15 @@ pub enum TestStats {
     TotalOnly,
 }
 
+/// A machine-readable format for the results of `tree-sitter test`
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TestFormat {
+    /// The Test Anything Protocol, version 13
+    Tap,
+    /// The same output as `--json-summary`
+    Json,
+}
+
 pub struct TestOptions<'a> {
     pub path: PathBuf,
     pub debug: bool,
@@ -690,6 +699,84 @@ pub fn run_tests_at_path(
     }
 }
 
+/// The outcome of a single corpus example, as returned by [`run_corpus_tests`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CorpusTestResult {
+    pub name: String,
+    pub passed: bool,
+    /// The expected output from the corpus file, normalized the same way as `actual`.
+    pub expected: String,
+    /// The S-expression (or CST, for `:cst` tests) produced by the parser.
+    pub actual: String,
+    pub input: String,
+}
+
+/// Run every example in the corpus at `path` against `language`, without printing anything.
+///
+/// Unlike [`run_tests_at_path`], this returns one [`CorpusTestResult`] per example so that
+/// callers can render failures however they like. Skipped and platform-specific examples are
+/// omitted, and the `:language` attribute is ignored in favor of `language`.
+pub fn run_corpus_tests(language: &Language, path: &Path) -> Result<Vec<CorpusTestResult>> {
+    let test_entry = parse_tests(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(language)?;
+    let mut results = Vec::new();
+    collect_corpus_results(&mut parser, test_entry, &mut results)?;
+    Ok(results)
+}
+
+fn collect_corpus_results(
+    parser: &mut Parser,
+    test_entry: TestEntry,
+    results: &mut Vec<CorpusTestResult>,
+) -> Result<()> {
+    match test_entry {
+        TestEntry::Group { children, .. } => {
+            for child in children {
+                collect_corpus_results(parser, child, results)?;
+            }
+        }
+        TestEntry::Example {
+            name,
+            input,
+            output,
+            has_fields,
+            attributes,
+            ..
+        } => {
+            if attributes.skip() || !attributes.platform {
+                return Ok(());
+            }
+
+            let tree = parser
+                .parse(&input, None)
+                .ok_or_else(|| anyhow!("Failed to parse test {name:?}"))?;
+            let mut actual = if attributes.cst {
+                render_test_cst(&input, &tree)?
+            } else {
+                tree.root_node().to_sexp()
+            };
+            if !(attributes.cst || has_fields) {
+                actual = strip_sexp_fields(&actual);
+            }
+            let passed = if attributes.error() {
+                tree.root_node().has_error()
+            } else {
+                actual == output
+            };
+
+            results.push(CorpusTestResult {
+                name,
+                passed,
+                expected: output,
+                actual,
+                input: String::from_utf8_lossy(&input).into_owned(),
+            });
+        }
+    }
+    Ok(())
+}
+
 pub fn check_queries_at_path(language: &Language, path: &Path) -> Result<()> {
     for entry in WalkDir::new(path)
         .into_iter()
@@ -709,6 +796,101 @@ pub fn check_queries_at_path(language: &Language, path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Renders a [`TestSummary`] in the Test Anything Protocol (TAP) format.
+///
+/// Each test case becomes one test point, named after its test category and the
+/// groups that contain it. Failing cases are followed by a YAML block with the
+/// expected and actual output, or the failed assertion.
+pub struct TapSummary<'a>(pub &'a TestSummary);
+
+impl std::fmt::Display for TapSummary<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let summary = self.0;
+        let mut unreported_failures = summary.parse_failures.iter().collect::<Vec<_>>();
+        let mut points = Vec::new();
+        for (category, results) in [
+            ("corpus", &summary.parse_results),
+            ("highlight", &summary.highlight_results),
+            ("tags", &summary.tag_results),
+            ("queries", &summary.query_results),
+        ] {
+            let mut path = vec![category];
+            for (depth, entry) in results.iter() {
+                path.truncate(depth + 1);
+                let outcome = match &entry.info {
+                    TestInfo::Group { .. } => {
+                        path.push(&entry.name);
+                        continue;
+                    }
+                    TestInfo::ParseTest { outcome, .. }
+                    | TestInfo::AssertionTest { outcome, .. } => outcome,
+                };
+                let failure = if matches!(outcome, TestOutcome::Failed | TestOutcome::Updated) {
+                    unreported_failures
+                        .iter()
+                        .position(|failure| failure.name == entry.name)
+                        .map(|i| unreported_failures.remove(i))
+                } else {
+                    None
+                };
+                let name = format!("{} / {}", path.join(" / "), entry.name).replace('#', "\\#");
+                points.push((name, outcome, failure));
+            }
+        }
+
+        writeln!(f, "TAP version 13")?;
+        writeln!(f, "1..{}", points.len())?;
+        for (i, (name, outcome, failure)) in points.into_iter().enumerate() {
+            let number = i + 1;
+            match outcome {
+                TestOutcome::Passed
+                | TestOutcome::Updated
+                | TestOutcome::AssertionPassed { .. } => writeln!(f, "ok {number} - {name}")?,
+                TestOutcome::Skipped => writeln!(f, "ok {number} - {name} # SKIP")?,
+                TestOutcome::Platform => {
+                    writeln!(f, "ok {number} - {name} # SKIP not run on this platform")?;
+                }
+                TestOutcome::Failed => {
+                    writeln!(f, "not ok {number} - {name}")?;
+                    writeln!(f, "  ---")?;
+                    if let Some(failure) = failure {
+                        let format_output = |output: &str| {
+                            if failure.is_cst {
+                                output.to_string()
+                            } else {
+                                format_sexp(output, 0)
+                            }
+                        };
+                        if failure.expected == "NO ERROR" {
+                            writeln!(f, "  message: expected an ERROR node")?;
+                        } else {
+                            write_yaml_block(f, "expected", &format_output(&failure.expected))?;
+                        }
+                        write_yaml_block(f, "actual", &format_output(&failure.actual))?;
+                    }
+                    writeln!(f, "  ...")?;
+                }
+                TestOutcome::AssertionFailed { error } => {
+                    writeln!(f, "not ok {number} - {name}")?;
+                    writeln!(f, "  ---")?;
+                    write_yaml_block(f, "message", error)?;
+                    writeln!(f, "  ...")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes `text` as a YAML literal block scalar inside of a TAP diagnostic block.
+fn write_yaml_block(f: &mut std::fmt::Formatter<'_>, key: &str, text: &str) -> std::fmt::Result {
+    writeln!(f, "  {key}: |-")?;
+    for line in text.lines() {
+        writeln!(f, "    {line}")?;
+    }
+    Ok(())
+}
+
 pub struct DiffKey;
 
 impl std::fmt::Display for DiffKey {
@@ -2441,6 +2623,63 @@ Test with cst marker
         (parser, language)
     }
 
+    #[test]
+    fn test_collect_corpus_results() {
+        let (mut parser, _) = c_parser_and_language();
+        let test_entry = parse_test_content(
+            "corpus".to_string(),
+            r"
+===========
+Passing
+===========
+int x;
+---
+(translation_unit (declaration (primitive_type) (identifier)))
+
+===========
+Failing
+===========
+int x;
+---
+(translation_unit (expression_statement (identifier)))
+
+===========
+Expected error
+:error
+===========
+int x
+---
+",
+            None,
+        );
+
+        let mut results = Vec::new();
+        collect_corpus_results(&mut parser, test_entry, &mut results).unwrap();
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|r| (r.name.as_str(), r.passed))
+                .collect::<Vec<_>>(),
+            [
+                ("Passing", true),
+                ("Failing", false),
+                ("Expected error", true)
+            ]
+        );
+
+        let failure = &results[1];
+        assert_eq!(failure.input, "int x;");
+        assert_eq!(
+            failure.expected,
+            "(translation_unit (expression_statement (identifier)))"
+        );
+        assert_eq!(
+            failure.actual,
+            "(translation_unit (declaration (primitive_type) (identifier)))"
+        );
+    }
+
     fn c_test_options(language: &Language) -> TestOptions<'_> {
         let mut languages = BTreeMap::new();
         languages.insert("c", language);
@@ -2704,4 +2943,89 @@ Test with cst marker
             .to_string()
         );
     }
+
+    #[test]
+    fn run_tests_tap_output() {
+        let (mut parser, language) = c_parser_and_language();
+        let opts = c_test_options(&language);
+
+        let example = |name: &str, output: &str, attributes: TestAttributes| TestEntry::Example {
+            name: name.to_string(),
+            input: b"1;\n".to_vec(),
+            output: output.to_string(),
+            header_delim_len: 25,
+            divider_delim_len: 3,
+            has_fields: false,
+            attributes_str: String::new(),
+            attributes,
+            file_name: None,
+        };
+        let test_entry = TestEntry::Group {
+            name: "corpus".to_string(),
+            file_path: None,
+            children: vec![TestEntry::Group {
+                name: "group1".to_string(),
+                children: vec![
+                    example(
+                        "C Test 1",
+                        "(translation_unit (expression_statement (number_literal)))",
+                        TestAttributes::default(),
+                    ),
+                    example(
+                        "C Test #2",
+                        "(translation_unit (expression_statement (string_literal)))",
+                        TestAttributes::default(),
+                    ),
+                    example(
+                        "C Test 3",
+                        "(translation_unit (expression_statement (string_literal)))",
+                        TestAttributes {
+                            expectation: TestExpectation::Skip,
+                            ..Default::default()
+                        },
+                    ),
+                ],
+                file_path: None,
+            }],
+        };
+
+        let mut test_summary = TestSummary::new(TestStats::All, false, false, false);
+        let mut corrected_entries = Vec::new();
+        run_tests(
+            &mut parser,
+            test_entry,
+            &opts,
+            &mut test_summary,
+            &mut corrected_entries,
+            true,
+        )
+        .expect("Failed to run tests");
+
+        let indent = |sexp: &str| {
+            format_sexp(sexp, 0)
+                .lines()
+                .fold(String::new(), |mut indented, line| {
+                    writeln!(indented, "    {line}").unwrap();
+                    indented
+                })
+        };
+        assert_eq!(
+            TapSummary(&test_summary).to_string(),
+            format!(
+                "TAP version 13\n\
+                 1..3\n\
+                 ok 1 - corpus / group1 / C Test 1\n\
+                 not ok 2 - corpus / group1 / C Test \\#2\n  \
+                 ---\n  \
+                 expected: |-\n\
+                 {}  \
+                 actual: |-\n\
+                 {}  \
+                 ...\n\
+                 ok 3 - corpus / group1 / C Test 3 # SKIP\n",
+                indent("(translation_unit (expression_statement (string_literal)))"),
+                indent("(translation_unit (expression_statement (number_literal)))"),
+            )
+        );
+    }
 }
@@ -225,7 +225,7 @@ pub fn get_highlight_positions(
         })?
     {
         match event? {
-            HighlightEvent::HighlightStart(h) => highlight_stack.push(h),
+            HighlightEvent::HighlightStart { highlight, .. } => highlight_stack.push(highlight),
             HighlightEvent::HighlightEnd => {
                 highlight_stack.pop();
             }
@@ -50,6 +50,19 @@ pub fn get_language(name: &str) -> Language {
     TEST_LOADER.load_language_at_path(config).unwrap()
 }
 
+pub fn get_language_library_path(name: &str) -> PathBuf {
+    let src_dir = GRAMMARS_DIR.join(name).join("src");
+    let library_dir = scratch_dir().join("libraries");
+    fs::create_dir_all(&library_dir).unwrap();
+    let output_path = library_dir
+        .join(name)
+        .with_extension(env::consts::DLL_EXTENSION);
+    let mut config = CompileConfig::new(&src_dir, None, Some(output_path.clone()));
+    config.header_paths.push(&HEADER_DIR);
+    TEST_LOADER.load_language_at_path(config).unwrap();
+    output_path
+}
+
 pub fn get_test_fixture_language(name: &str) -> Language {
     get_test_fixture_language_internal(name, false)
 }
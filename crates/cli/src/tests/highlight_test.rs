@@ -9,8 +9,10 @@ use std::{
     },
 };
 
+use tree_sitter::{CaptureQuantifier, Parser, Point, Query, Range};
 use tree_sitter_highlight::{
     Error, Highlight, HighlightConfiguration, HighlightEvent, Highlighter, HtmlRenderer, c,
+    resolve_locals,
 };
 
 use super::helpers::fixtures::{get_highlight_config, get_language, get_language_queries_path};
@@ -255,6 +257,70 @@ fn test_highlighting_multiline_nodes_to_html() {
     );
 }
 
+#[test]
+fn test_highlighting_injected_javascript_in_html_to_html() {
+    let source = [
+        "<script>const x = new Thing();</script>",
+        "<script> f(`a`) </script>",
+    ]
+    .join("\n");
+
+    // The spans of the injected JavaScript end before the host document's
+    // closing tags begin.
+    assert_eq!(
+        &to_html(&source, &HTML_HIGHLIGHT).unwrap(),
+        &[
+            concat!(
+                "<span class=punctuation.bracket>&lt;</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>",
+                "<span class=keyword>const</span> <span class=variable>x</span> <span class=operator>=</span> ",
+                "<span class=keyword>new</span> <span class=constructor>Thing</span>",
+                "<span class=punctuation.bracket>(</span><span class=punctuation.bracket>)</span><span class=punctuation.delimiter>;</span>",
+                "<span class=punctuation.bracket>&lt;/</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>\n",
+            )
+            .to_string(),
+            concat!(
+                "<span class=punctuation.bracket>&lt;</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>",
+                " <span class=function>f</span><span class=punctuation.bracket>(</span><span class=string>`a`</span><span class=punctuation.bracket>)</span> ",
+                "<span class=punctuation.bracket>&lt;/</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>\n",
+            )
+            .to_string(),
+        ]
+    );
+
+    // When the scripts are combined into one injection, a template string can
+    // start in one of them and end in the next. Its highlight covers both of
+    // its parts, but not the tags between them.
+    let queries_path = get_language_queries_path("html");
+    let mut combined_config = HighlightConfiguration::new(
+        get_language("html"),
+        "html",
+        &fs::read_to_string(queries_path.join("highlights.scm")).unwrap(),
+        r#"
+        ((script_element (raw_text) @injection.content)
+         (#set! injection.language "javascript")
+         (#set! injection.combined))
+        "#,
+        "",
+    )
+    .unwrap();
+    combined_config.configure(&HIGHLIGHT_NAMES);
+    let source = "<script>f(`a</script><p>b</p><script>c`)</script>";
+    assert_eq!(
+        &to_html(source, &combined_config).unwrap(),
+        &[concat!(
+            "<span class=punctuation.bracket>&lt;</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>",
+            "<span class=function>f</span><span class=punctuation.bracket>(</span><span class=string>`a</span>",
+            "<span class=punctuation.bracket>&lt;/</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>",
+            "<span class=punctuation.bracket>&lt;</span><span class=tag>p</span><span class=punctuation.bracket>&gt;</span>b",
+            "<span class=punctuation.bracket>&lt;/</span><span class=tag>p</span><span class=punctuation.bracket>&gt;</span>",
+            "<span class=punctuation.bracket>&lt;</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>",
+            "<span class=string>c`</span><span class=punctuation.bracket>)</span>",
+            "<span class=punctuation.bracket>&lt;/</span><span class=tag>script</span><span class=punctuation.bracket>&gt;</span>\n",
+        )
+        .to_string()]
+    );
+}
+
 #[test]
 fn test_highlighting_with_local_variable_tracking() {
     let source = [
@@ -463,6 +529,109 @@ fn test_highlighting_with_content_children_included() {
     );
 }
 
+#[test]
+fn test_highlighting_byte_range() {
+    let source = "const a = `one\ntwo\nthree`;\nfunction b() { return 1; }\n";
+    let range_start = source.find("two").unwrap();
+    let range_end = source.find("return").unwrap();
+
+    let mut highlighter = Highlighter::new();
+    let full_events = highlighter
+        .highlight(&JS_HIGHLIGHT, source.as_bytes(), None, None, |_| None)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let range_events = highlighter
+        .highlight_range(
+            &JS_HIGHLIGHT,
+            source.as_bytes(),
+            range_start..range_end,
+            None,
+            None,
+            |_| None,
+        )
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let full_highlights = highlights_by_byte(&full_events);
+    let range_highlights = highlights_by_byte(&range_events);
+
+    // Only the bytes within the range are emitted, and they are highlighted the
+    // same way as when highlighting the whole document.
+    assert_eq!(
+        range_highlights.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+        (range_start..range_end).collect::<Vec<_>>()
+    );
+    assert_eq!(range_highlights, full_highlights[range_start..range_end]);
+
+    // The range starts inside of a string, so the string highlight is opened
+    // before the first source event.
+    assert_eq!(range_highlights[0].1, &["string"]);
+
+    // The events are well-nested.
+    let mut depth = 0;
+    for event in &range_events {
+        match event {
+            HighlightEvent::HighlightStart { .. } => depth += 1,
+            HighlightEvent::HighlightEnd => depth -= 1,
+            HighlightEvent::Source { .. } => {}
+        }
+        assert!(depth >= 0);
+    }
+    assert_eq!(depth, 0);
+}
+
+fn highlights_by_byte(events: &[HighlightEvent]) -> Vec<(usize, Vec<&'static str>)> {
+    let mut stack = Vec::new();
+    let mut result = Vec::new();
+    for event in events {
+        match event {
+            HighlightEvent::HighlightStart { highlight, .. } => {
+                stack.push(HIGHLIGHT_NAMES[highlight.0].as_str());
+            }
+            HighlightEvent::HighlightEnd => {
+                stack.pop();
+            }
+            HighlightEvent::Source { start, end } => {
+                result.extend((*start..*end).map(|i| (i, stack.clone())));
+            }
+        }
+    }
+    result
+}
+
+#[test]
+fn test_highlighting_reports_pattern_index() {
+    let source = "const a = new B(1);";
+    let mut highlighter = Highlighter::new();
+    let events = highlighter
+        .highlight(&JS_HIGHLIGHT, source.as_bytes(), None, None, |_| None)
+        .unwrap();
+
+    let query = &JS_HIGHLIGHT.query;
+    let mut highlight_count = 0;
+    for event in events {
+        if let HighlightEvent::HighlightStart {
+            highlight,
+            pattern_index,
+        } = event.unwrap()
+        {
+            // The reported pattern contains a capture for the highlight.
+            let highlight_name = HIGHLIGHT_NAMES[highlight.0].as_str();
+            let quantifiers = query.capture_quantifiers(pattern_index);
+            assert!(
+                query.capture_names().iter().enumerate().any(|(i, name)| {
+                    name.starts_with(highlight_name) && quantifiers[i] != CaptureQuantifier::Zero
+                }),
+                "pattern {pattern_index} does not capture {highlight_name}",
+            );
+            highlight_count += 1;
+        }
+    }
+    assert!(highlight_count > 0);
+}
+
 #[test]
 fn test_highlighting_cancellation() {
     // An HTML document with a large injected JavaScript document:
@@ -505,6 +674,22 @@ fn test_highlighting_cancellation() {
         found_cancellation_error,
         "Expected a cancellation error while iterating events"
     );
+
+    // Once cancelled, no further events are produced.
+    assert!(events.next().is_none());
+    drop(events);
+
+    // The highlighter can be reused after a cancellation.
+    let mut events = highlighter
+        .highlight(
+            &HTML_HIGHLIGHT,
+            source.as_bytes(),
+            None,
+            None,
+            test_language_for_injection_string,
+        )
+        .unwrap();
+    assert!(events.all(|event| event.is_ok()));
 }
 
 #[test]
@@ -686,6 +871,185 @@ fn test_highlighting_with_all_captures_applied() {
     );
 }
 
+#[test]
+fn test_highlighting_with_fold_ranges() {
+    let source = "fn one() {\n    1\n}\n\nfn two() -> u32 { 2 }\n";
+    let language = get_language("rust");
+    let highlights_query = indoc::indoc! {"
+        \"fn\" @keyword
+        (function_item name: (identifier) @function)
+        (function_item body: (block) @embedded)
+    "};
+    let folds_query = "(function_item body: (block) @fold)";
+    let mut config = HighlightConfiguration::new_with_folds(
+        language,
+        "rust",
+        highlights_query,
+        "",
+        "",
+        folds_query,
+    )
+    .unwrap();
+    config.configure(&HIGHLIGHT_NAMES);
+
+    let mut highlighter = Highlighter::new();
+    let mut highlighted = Vec::new();
+    let mut stack = Vec::new();
+    for event in highlighter
+        .highlight(&config, source.as_bytes(), None, None, |_| None)
+        .unwrap()
+    {
+        match event.unwrap() {
+            HighlightEvent::HighlightStart { highlight, .. } => stack.push(highlight),
+            HighlightEvent::HighlightEnd => {
+                stack.pop();
+            }
+            HighlightEvent::Source { start, end } => {
+                if let Some(highlight) = stack.last() {
+                    highlighted.push((&source[start..end], HIGHLIGHT_NAMES[highlight.0].as_str()));
+                }
+            }
+        }
+    }
+
+    // The function bodies are both folded and highlighted.
+    assert_eq!(
+        highlighted,
+        &[
+            ("fn", "keyword"),
+            ("one", "function"),
+            ("{\n    1\n}", "embedded"),
+            ("fn", "keyword"),
+            ("two", "function"),
+            ("{ 2 }", "embedded"),
+        ]
+    );
+    assert_eq!(
+        highlighter.fold_ranges(),
+        &[
+            Range {
+                start_byte: 9,
+                end_byte: 18,
+                start_point: Point::new(0, 9),
+                end_point: Point::new(2, 1),
+            },
+            Range {
+                start_byte: 36,
+                end_byte: 41,
+                start_point: Point::new(4, 16),
+                end_point: Point::new(4, 21),
+            },
+        ]
+    );
+
+    // Highlighting again replaces the previous fold ranges.
+    for event in highlighter
+        .highlight(&config, b"fn three() {}", None, None, |_| None)
+        .unwrap()
+    {
+        event.unwrap();
+    }
+    assert_eq!(
+        highlighter
+            .fold_ranges()
+            .iter()
+            .map(|range| (range.start_byte, range.end_byte))
+            .collect::<Vec<_>>(),
+        &[(11, 13)]
+    );
+}
+
+#[test]
+fn test_resolving_locals_with_shadowing() {
+    let language = get_language("javascript");
+    let query = Query::new(
+        &language,
+        r"
+        (function_declaration) @local.scope
+        (formal_parameters (identifier) @local.definition)
+        (variable_declarator
+          name: (identifier) @local.definition
+          value: (_)? @local.definition-value)
+        (identifier) @local.reference
+        ",
+    )
+    .unwrap();
+
+    let source = "
+function outer(x) {
+  let y = x;
+  function inner(x) {
+    return x + y;
+  }
+  return x + z;
+}
+"
+    .trim_start();
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let locals = resolve_locals(&query, &tree, source.as_bytes());
+
+    let position = |offset: usize| {
+        let row = source[..offset].matches('\n').count();
+        let column = offset - source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        Point::new(row, column)
+    };
+
+    assert_eq!(locals.scopes.len(), 3);
+    assert_eq!(locals.scopes[1].parent, Some(0));
+    assert_eq!(locals.scopes[2].parent, Some(1));
+
+    assert_eq!(
+        locals
+            .definitions
+            .iter()
+            .map(|def| (def.name.as_str(), position(def.range.start), def.scope))
+            .collect::<Vec<_>>(),
+        [
+            ("x", Point::new(0, 15), 1),
+            ("y", Point::new(1, 6), 1),
+            ("x", Point::new(2, 17), 2),
+        ]
+    );
+
+    // Each reference resolves to the innermost definition with the same name,
+    // so the parameter of `inner` shadows the one of `outer`.
+    assert_eq!(
+        locals
+            .references
+            .iter()
+            .map(|reference| (
+                reference.name.as_str(),
+                position(reference.range.start),
+                reference
+                    .definition
+                    .map(|def| position(locals.definitions[def].range.start)),
+            ))
+            .collect::<Vec<_>>(),
+        [
+            ("outer", Point::new(0, 9), None),
+            ("x", Point::new(1, 10), Some(Point::new(0, 15))),
+            ("inner", Point::new(2, 11), None),
+            ("x", Point::new(3, 11), Some(Point::new(2, 17))),
+            ("y", Point::new(3, 15), Some(Point::new(1, 6))),
+            ("x", Point::new(5, 9), Some(Point::new(0, 15))),
+            ("z", Point::new(5, 13), None),
+        ]
+    );
+
+    let reference = &locals.references[3];
+    assert_eq!(
+        locals
+            .definition_for(reference.range.clone())
+            .unwrap()
+            .range
+            .start,
+        source.find("inner(x").unwrap() + 6,
+    );
+}
+
 #[test]
 fn test_decode_utf8_lossy() {
     use tree_sitter::LossyUtf8;
@@ -771,7 +1135,9 @@ fn to_token_vector<'a>(
     )?;
     for event in events {
         match event? {
-            HighlightEvent::HighlightStart(s) => highlights.push(HIGHLIGHT_NAMES[s.0].as_str()),
+            HighlightEvent::HighlightStart { highlight, .. } => {
+                highlights.push(HIGHLIGHT_NAMES[highlight.0].as_str());
+            }
             HighlightEvent::HighlightEnd => {
                 highlights.pop();
             }
@@ -1,6 +1,15 @@
-use tree_sitter::{self, Parser};
+use std::collections::HashMap;
 
-use super::helpers::fixtures::get_language;
+use tree_sitter::{
+    self, AbiMismatch, ExternalScanner, LANGUAGE_VERSION, Language, LanguageMetadata, LoadError,
+    MIN_COMPATIBLE_LANGUAGE_VERSION, Parser, ScannerLexer,
+};
+
+use super::helpers::fixtures::{
+    get_language, get_language_library_path, get_test_fixture_language, get_test_language,
+    scratch_dir,
+};
+use crate::tests::generate_parser;
 
 #[test]
 fn test_lookahead_iterator() {
@@ -66,6 +75,139 @@ fn test_lookahead_iterator_modifiable_only_by_mut() {
     let _ = names.next();
 }
 
+#[test]
+fn test_language_extra_symbols() {
+    let language = get_language("rust");
+    let extras = language.extra_symbols();
+    assert_eq!(
+        extras
+            .iter()
+            .map(|id| language.node_kind_for_id(*id).unwrap())
+            .collect::<Vec<_>>(),
+        &["line_comment", "block_comment"]
+    );
+    assert!(extras.iter().all(|id| language.node_kind_is_extra(*id)));
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse("// a\nfn b() {}", None).unwrap();
+    let comment = tree.root_node().child(0).unwrap();
+    assert!(comment.is_extra());
+    assert!(extras.contains(&comment.kind_id()));
+    assert!(!language.node_kind_is_extra(tree.root_node().child(1).unwrap().kind_id()));
+
+    // Only the extras are reported, not the other tokens or rules of the grammar, and
+    // not the tokens that only appear within a non-terminal extra.
+    let language = get_test_fixture_language("extra_non_terminals");
+    let extras = language.extra_symbols();
+    assert_eq!(
+        extras
+            .iter()
+            .map(|id| language.node_kind_for_id(*id).unwrap())
+            .collect::<Vec<_>>(),
+        &["comment"]
+    );
+    for kind in [
+        "a",
+        "b",
+        "c",
+        "d",
+        "paren_comment",
+        "line_comment",
+        "module",
+    ] {
+        let id = language.id_for_node_kind(kind, kind.len() > 1);
+        assert_ne!(id, 0, "{kind}");
+        assert!(!language.node_kind_is_extra(id), "{kind}");
+    }
+}
+
+#[test]
+fn test_language_kind_ids() {
+    let language = get_language("json");
+    let kinds = language
+        .kind_ids()
+        .map(|(id, name, is_named)| ((name, is_named), id))
+        .collect::<HashMap<_, _>>();
+
+    let pair = kinds[&("pair", true)];
+    assert_eq!(pair, language.id_for_node_kind("pair", true));
+    assert_eq!(language.node_kind_for_id(pair), Some("pair"));
+
+    let open_brace = kinds[&("{", false)];
+    assert_eq!(open_brace, language.id_for_node_kind("{", false));
+    assert!(!language.node_kind_is_named(open_brace));
+}
+
+#[test]
+fn test_language_root_kind() {
+    for (language_name, root_kind, source) in [
+        ("rust", "source_file", "fn main() {}"),
+        ("javascript", "program", "let x = 1;"),
+        ("html", "document", "<p>hi</p>"),
+        ("python", "module", "x = 1"),
+        ("c", "translation_unit", "int x;"),
+        ("json", "document", "[1, 2]"),
+        ("ruby", "program", "x = 1"),
+    ] {
+        let language = get_language(language_name);
+        assert_eq!(language.root_kind(), root_kind, "{language_name}");
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        assert_eq!(tree.root_node().kind(), root_kind, "{language_name}");
+    }
+}
+
+#[test]
+fn test_language_is_compatible() {
+    for name in ["rust", "javascript", "json"] {
+        let language = get_language(name);
+        assert_eq!(language.is_compatible(), Ok(()));
+        assert!(language.abi_version() >= MIN_COMPATIBLE_LANGUAGE_VERSION);
+        assert!(language.abi_version() <= LANGUAGE_VERSION);
+    }
+
+    let error = AbiMismatch {
+        version: LANGUAGE_VERSION + 1,
+        min_version: MIN_COMPATIBLE_LANGUAGE_VERSION,
+        max_version: LANGUAGE_VERSION,
+    };
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Incompatible language version {}. Expected minimum {MIN_COMPATIBLE_LANGUAGE_VERSION}, maximum {LANGUAGE_VERSION}",
+            LANGUAGE_VERSION + 1
+        )
+    );
+}
+
+#[test]
+fn test_language_load_from_library() {
+    let path = get_language_library_path("json");
+    let language = unsafe { Language::load_from_library(&path, "tree_sitter_json") }.unwrap();
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse("[1, null]", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(document (array (number) (null)))"
+    );
+
+    let error = unsafe { Language::load_from_library(&path, "tree_sitter_jsonx") }.unwrap_err();
+    assert!(
+        matches!(&error, LoadError::Symbol { symbol, .. } if symbol == "tree_sitter_jsonx"),
+        "{error:?}"
+    );
+
+    let missing_path = scratch_dir().join("libraries").join("missing.so");
+    let error =
+        unsafe { Language::load_from_library(&missing_path, "tree_sitter_json") }.unwrap_err();
+    assert!(matches!(error, LoadError::Library(_)), "{error:?}");
+}
+
 #[test]
 fn test_symbol_metadata_checks() {
     let language = get_language("rust");
@@ -199,3 +341,162 @@ fn test_supertypes() {
         }
     }
 }
+
+#[test]
+fn test_language_fields_for_kind() {
+    let language = get_language("rust");
+    let field_names = |kind: &str| {
+        language
+            .fields_for_kind(language.id_for_node_kind(kind, true))
+            .into_iter()
+            .map(|id| language.field_name_for_id(id).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let mut function_fields = field_names("function_item");
+    function_fields.sort_unstable();
+    assert_eq!(
+        function_fields,
+        &[
+            "body",
+            "name",
+            "parameters",
+            "return_type",
+            "type_parameters"
+        ]
+    );
+    assert!(field_names("identifier").is_empty());
+
+    let fields = language.fields_for_kind(language.id_for_node_kind("function_item", true));
+    assert!(fields.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_language_name_and_metadata() {
+    let (parser_name, parser_code) = generate_parser(
+        r#"
+        {
+            "name": "test_language_name",
+            "rules": {
+                "root": {"type": "STRING", "value": "x"}
+            }
+        }
+        "#,
+    )
+    .unwrap();
+
+    let language = get_test_language(&parser_name, &parser_code, None);
+    assert_eq!(language.name(), Some("test_language_name"));
+
+    let metadata = language.metadata().unwrap();
+    assert_eq!(
+        metadata,
+        LanguageMetadata {
+            major_version: 0,
+            minor_version: 0,
+            patch_version: 0,
+        }
+    );
+    assert_eq!(metadata.to_string(), "0.0.0");
+}
+
+/// A scanner for the `external_and_internal_tokens` grammar that recognizes
+/// double-quoted strings, where the grammar's C scanner only recognizes
+/// single-quoted ones.
+struct DoubleQuotedStringScanner;
+
+impl ExternalScanner for DoubleQuotedStringScanner {
+    const TOKEN_COUNT: usize = 2;
+
+    fn create() -> Self {
+        Self
+    }
+
+    fn scan(&mut self, lexer: &mut ScannerLexer, valid_symbols: &[bool]) -> bool {
+        const STRING: u16 = 0;
+        const LINE_BREAK: u16 = 1;
+
+        if valid_symbols[LINE_BREAK as usize] {
+            while matches!(lexer.lookahead(), Some(' ' | '\r')) {
+                lexer.advance(true);
+            }
+            if lexer.lookahead() == Some('\n') {
+                lexer.advance(false);
+                lexer.set_result_symbol(LINE_BREAK);
+                return true;
+            }
+        }
+
+        if valid_symbols[STRING as usize] {
+            while matches!(lexer.lookahead(), Some(' ' | '\r' | '\n')) {
+                lexer.advance(true);
+            }
+            if lexer.lookahead() == Some('"') {
+                lexer.advance(false);
+                while lexer.lookahead() != Some('"') {
+                    if lexer.eof() {
+                        return false;
+                    }
+                    lexer.advance(false);
+                }
+                lexer.advance(false);
+                lexer.set_result_symbol(STRING);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn serialize(&self, _buffer: &mut [u8]) -> usize {
+        0
+    }
+
+    fn deserialize(&mut self, _buffer: &[u8]) {}
+}
+
+#[test]
+fn test_language_with_external_scanner() {
+    let language = get_test_fixture_language("external_and_internal_tokens");
+    let source = "\"hello\" world\n";
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    assert!(tree.root_node().has_error());
+
+    let rust_language =
+        unsafe { language.with_external_scanner::<DoubleQuotedStringScanner>() }.unwrap();
+    parser.set_language(&rust_language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(statement (string) (variable) (line_break))"
+    );
+
+    // The tree keeps the copy alive after the language itself is dropped.
+    drop(rust_language);
+    parser.reset();
+    assert_eq!(
+        tree.language().node_kind_count(),
+        language.node_kind_count()
+    );
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(statement (string) (variable) (line_break))"
+    );
+
+    // The original language still uses its own scanner.
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse("'hello' world\n", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(statement (string) (variable) (line_break))"
+    );
+
+    // Languages without a matching number of external tokens are rejected.
+    assert!(
+        unsafe { get_language("json").with_external_scanner::<DoubleQuotedStringScanner>() }
+            .is_none()
+    );
+}
@@ -1,4 +1,9 @@
-use tree_sitter::{InputEdit, Node, Parser, Point, Tree};
+use std::borrow::Cow;
+
+use tree_sitter::{
+    CharIndexMap, InputEdit, KindMapper, Node, Parser, Point, PositionParseError, Range,
+    SexpOptions, Tree, parse_byte_range,
+};
 use tree_sitter_generate::load_grammar_file;
 
 use super::{
@@ -270,6 +275,46 @@ fn test_node_parent_of_child_by_field_name() {
     );
 }
 
+#[test]
+fn test_node_ancestors() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    let tree = parser
+        .parse("fn main() { let y = (1 + 2) * 3; }", None)
+        .unwrap();
+    let root = tree.root_node();
+    let integer = root.descendant_for_byte_range(21, 22).unwrap();
+    assert_eq!(integer.kind(), "integer_literal");
+
+    assert_eq!(
+        integer.ancestors().map(|n| n.kind()).collect::<Vec<_>>(),
+        &[
+            "binary_expression",
+            "parenthesized_expression",
+            "binary_expression",
+            "let_declaration",
+            "block",
+            "function_item",
+            "source_file",
+        ]
+    );
+    assert_eq!(root.ancestors().next(), None);
+
+    // The iterator is lazy, so it composes with searches for enclosing nodes.
+    let enclosing_statement = integer
+        .ancestors()
+        .find(|n| n.kind() == "let_declaration")
+        .unwrap();
+    assert_eq!(enclosing_statement.start_byte(), 12);
+    assert_eq!(
+        integer
+            .ancestors()
+            .take_while(|n| n.kind() != "block")
+            .count(),
+        4
+    );
+}
+
 #[test]
 fn test_parent_of_zero_width_node() {
     let code = "def dupa(foo):";
@@ -370,6 +415,46 @@ fn test_first_named_child_for_offset() {
     );
 }
 
+#[test]
+fn test_first_child_for_offset_with_many_siblings() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    let source = format!(
+        "[{}]",
+        (0..1000)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let tree = parser.parse(&source, None).unwrap();
+    let array = tree.root_node().child(0).unwrap();
+    assert_eq!(array.named_child_count(), 1000);
+
+    let offset = source.find(" 500,").unwrap();
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap().to_string();
+
+    // The comma before the space contains the offset just before it.
+    let comma = array.first_child_for_byte(offset - 1).unwrap();
+    assert_eq!(comma.kind(), ",");
+    assert_eq!(comma.end_byte(), offset);
+
+    // The space is not part of any child, so the next child is returned.
+    assert_eq!(text(array.first_child_for_byte(offset).unwrap()), "500");
+    assert_eq!(
+        text(array.first_named_child_for_byte(offset - 1).unwrap()),
+        "500"
+    );
+
+    // A child that ends at the offset is skipped.
+    assert_eq!(text(array.first_child_for_byte(offset + 4).unwrap()), ",");
+    assert_eq!(
+        text(array.first_named_child_for_byte(offset + 4).unwrap()),
+        "501"
+    );
+
+    assert!(array.first_child_for_byte(source.len()).is_none());
+}
+
 #[test]
 fn test_node_field_name_for_child() {
     let mut parser = Parser::new();
@@ -756,6 +841,43 @@ fn test_node_descendant_for_range() {
     );
 }
 
+#[test]
+fn test_node_named_descendant_for_byte_range_at_keyword() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    let source = "fn main() { let x = 1; }";
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+
+    // At the `let` keyword, the deepest node is the anonymous token, but the
+    // deepest named node is the declaration that contains it.
+    let offset = source.find("let").unwrap();
+    let node = root.descendant_for_byte_range(offset, offset).unwrap();
+    assert_eq!(node.kind(), "let");
+    assert!(!node.is_named());
+    let named_node = root
+        .named_descendant_for_byte_range(offset, offset)
+        .unwrap();
+    assert_eq!(named_node.kind(), "let_declaration");
+    assert_eq!(node.parent(), Some(named_node));
+
+    // At an identifier, both lookups agree.
+    let offset = source.find('x').unwrap();
+    assert_eq!(
+        root.named_descendant_for_byte_range(offset, offset),
+        root.descendant_for_byte_range(offset, offset)
+    );
+
+    // The lookup is scoped to the node it is called on.
+    let body = root.child(0).unwrap().child_by_field_name("body").unwrap();
+    assert_eq!(
+        body.named_descendant_for_byte_range(offset, offset)
+            .unwrap()
+            .kind(),
+        "identifier"
+    );
+}
+
 #[test]
 fn test_node_edit() {
     let mut code = JSON_EXAMPLE.as_bytes().to_vec();
@@ -816,6 +938,336 @@ fn test_root_node_with_offset() {
     assert_eq!(child.end_position(), Point::new(2, 10));
 }
 
+#[test]
+fn test_node_descendant_for_point_range_with_offset() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("  if (a) b", None).unwrap();
+
+    // Treat the tree as injected content that starts on the third row of an
+    // enclosing document, and search within the `if` statement.
+    let root = tree.root_node_with_offset(6, Point::new(2, 2));
+    let if_statement = root.child(0).unwrap();
+    assert_eq!(if_statement.kind(), "if_statement");
+
+    let node = if_statement
+        .descendant_for_point_range(Point::new(2, 8), Point::new(2, 9))
+        .unwrap();
+    assert_eq!(node.kind(), "identifier");
+    assert_eq!(node.byte_range(), 12..13);
+    assert_eq!(node.start_position(), Point::new(2, 8));
+
+    let node = if_statement
+        .descendant_for_point_range(Point::new(2, 7), Point::new(2, 8))
+        .unwrap();
+    assert_eq!(node.kind(), "(");
+    let node = if_statement
+        .named_descendant_for_point_range(Point::new(2, 7), Point::new(2, 8))
+        .unwrap();
+    assert_eq!(node.kind(), "parenthesized_expression");
+    assert_eq!(node.start_position(), Point::new(2, 7));
+    assert_eq!(node.end_position(), Point::new(2, 10));
+
+    // Points in the coordinates of the unshifted tree don't fall within any of
+    // the statement's descendants, so the statement itself is returned.
+    let node = if_statement
+        .descendant_for_point_range(Point::new(0, 6), Point::new(0, 7))
+        .unwrap();
+    assert_eq!(node, if_statement);
+
+    // Searching a subtree doesn't find nodes outside of it.
+    let condition = if_statement.child_by_field_name("condition").unwrap();
+    let node = condition
+        .named_descendant_for_point_range(Point::new(2, 11), Point::new(2, 12))
+        .unwrap();
+    assert_eq!(node, condition);
+    let node = root
+        .named_descendant_for_point_range(Point::new(2, 11), Point::new(2, 12))
+        .unwrap();
+    assert_eq!(node.kind(), "identifier");
+    assert_eq!(node.byte_range(), 15..16);
+}
+
+#[test]
+fn test_node_text_with_included_ranges() {
+    let source = "<% foo( %>ignored<% bar) %>";
+    let ranges = [" foo( ", " bar) "].map(|text| {
+        let start_byte = source.find(text).unwrap();
+        let end_byte = start_byte + text.len();
+        Range {
+            start_byte,
+            end_byte,
+            start_point: Point::new(0, start_byte),
+            end_point: Point::new(0, end_byte),
+        }
+    });
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    parser.set_included_ranges(&ranges).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let call = tree.root_node().child(0).unwrap().child(0).unwrap();
+    assert_eq!(call.kind(), "call_expression");
+
+    // The call spans the excluded text between the two ranges, which is left out.
+    assert_eq!(
+        call.utf8_text(source.as_bytes()).unwrap(),
+        "foo( %>ignored<% bar)"
+    );
+    assert_eq!(
+        call.utf8_text_ranges(source.as_bytes()).unwrap(),
+        "foo(  bar)"
+    );
+
+    // Nodes within a single range are borrowed from the source.
+    let argument = call
+        .child_by_field_name("arguments")
+        .unwrap()
+        .named_child(0)
+        .unwrap();
+    let text = argument.utf8_text_ranges(source.as_bytes()).unwrap();
+    assert!(matches!(text, Cow::Borrowed("bar")));
+
+    // Without included ranges, the text is the same as `utf8_text`.
+    parser.set_included_ranges(&[]).unwrap();
+    let source = "foo(bar)";
+    let tree = parser.parse(source, None).unwrap();
+    let call = tree.root_node().child(0).unwrap().child(0).unwrap();
+    assert!(matches!(
+        call.utf8_text_ranges(source.as_bytes()).unwrap(),
+        Cow::Borrowed("foo(bar)")
+    ));
+}
+
+#[test]
+fn test_node_anonymous_text() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    let tree = parser.parse("struct A { b: u8 }", None).unwrap();
+
+    let struct_item = tree.root_node().child(0).unwrap();
+    assert_eq!(struct_item.anonymous_text(), None);
+    assert_eq!(
+        struct_item.child(0).unwrap().anonymous_text(),
+        Some("struct")
+    );
+
+    let body = struct_item.child_by_field_name("body").unwrap();
+    assert_eq!(body.kind(), "field_declaration_list");
+    assert_eq!(body.anonymous_text(), None);
+    let open_brace = body.child(0).unwrap();
+    assert_eq!(open_brace.anonymous_text(), Some("{"));
+    assert_eq!(open_brace.utf8_text(b"struct A { b: u8 }").unwrap(), "{");
+    assert_eq!(body.child(2).unwrap().anonymous_text(), Some("}"));
+}
+
+#[test]
+fn test_node_path_to_root() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    let source = "mod m { impl S { fn main() { let x = 1; } } }";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+
+    let offset = source.find('1').unwrap();
+    let literal = root_node
+        .named_descendant_for_byte_range(offset, offset + 1)
+        .unwrap();
+    let mut cursor = tree.walk();
+    assert_eq!(
+        literal.path_to_root(&mut cursor),
+        &[
+            (Some("value"), "integer_literal"),
+            (None, "let_declaration"),
+            (Some("body"), "block"),
+            (None, "function_item"),
+            (Some("body"), "declaration_list"),
+            (None, "impl_item"),
+            (Some("body"), "declaration_list"),
+            (None, "mod_item"),
+            (None, "source_file"),
+        ]
+    );
+
+    assert_eq!(
+        root_node.path_to_root(&mut cursor),
+        &[(None, "source_file")]
+    );
+}
+
+#[test]
+fn test_node_iteration_with_reused_cursor() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    let source = "fn a(x: u8) {}\nstruct B { c: u8, d: u8 }\nfn e() { f(); }";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+
+    let mut cursor = tree.walk();
+    let mut item_cursor = tree.walk();
+    let mut summary = Vec::new();
+    for item in root_node.named_children(&mut item_cursor) {
+        let name = item.child_by_field_name("name").unwrap();
+        let child_count = item.children(&mut cursor).count();
+        let named_kinds = item
+            .named_children(&mut cursor)
+            .map(|child| child.kind())
+            .collect::<Vec<_>>();
+        let names = item
+            .children_by_field_name("name", &mut cursor)
+            .map(|node| node.utf8_text(source.as_bytes()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, [name.utf8_text(source.as_bytes()).unwrap()]);
+        assert_eq!(
+            name.path_to_root(&mut cursor),
+            &[
+                (Some("name"), name.kind()),
+                (None, item.kind()),
+                (None, "source_file"),
+            ]
+        );
+        summary.push((item.kind(), child_count, named_kinds));
+    }
+
+    assert_eq!(
+        summary,
+        &[
+            (
+                "function_item",
+                4,
+                vec!["identifier", "parameters", "block"]
+            ),
+            (
+                "struct_item",
+                3,
+                vec!["type_identifier", "field_declaration_list"]
+            ),
+            (
+                "function_item",
+                4,
+                vec!["identifier", "parameters", "block"]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_node_char_range() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    let source = "[\"日本\", 1]";
+    let tree = parser.parse(source, None).unwrap();
+    let array = tree.root_node().named_child(0).unwrap();
+    let string = array.named_child(0).unwrap();
+    let number = array.named_child(1).unwrap();
+
+    let index_map = CharIndexMap::new(source);
+    assert_eq!(string.byte_range(), 1..9);
+    assert_eq!(string.char_range(&index_map), 1..5);
+    assert_eq!(number.byte_range(), 11..12);
+    assert_eq!(number.char_range(&index_map), 7..8);
+    assert_eq!(array.char_range(&index_map), 0..source.chars().count());
+
+    // Offsets inside of a character round up, and offsets past the end clamp.
+    assert_eq!(index_map.char_index(2), 2);
+    assert_eq!(index_map.char_index(3), 3);
+    assert_eq!(index_map.char_index(4), 3);
+    assert_eq!(index_map.char_index(100), 9);
+}
+
+#[test]
+fn test_node_utf16_code_unit_offsets() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    let source = "[\"😀日\", 1]".encode_utf16().collect::<Vec<_>>();
+    let tree = parser.parse_utf16_le(&source, None).unwrap();
+    let array = tree.root_node().named_child(0).unwrap();
+    let string = array.named_child(0).unwrap();
+    let number = array.named_child(1).unwrap();
+
+    // Byte offsets count two bytes per code unit.
+    assert_eq!(string.byte_range(), 2..12);
+    assert_eq!(number.byte_range(), 16..18);
+
+    assert_eq!(string.utf16_code_unit_start(), 1);
+    assert_eq!(string.utf16_code_unit_end(), 6);
+    assert_eq!(
+        String::from_utf16(&source[string.utf16_code_unit_start()..string.utf16_code_unit_end()])
+            .unwrap(),
+        "\"😀日\""
+    );
+    assert_eq!(
+        &source[number.utf16_code_unit_start()..number.utf16_code_unit_end()],
+        &['1' as u16]
+    );
+    assert_eq!(array.utf16_code_unit_end(), source.len());
+}
+
+#[test]
+fn test_node_debug() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "let x = 1;\nfoo(\"a long string that will not fit in the preview\");\n";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+    let declaration = root_node.named_child(0).unwrap();
+    let string = root_node
+        .named_child(1)
+        .unwrap()
+        .named_descendant_for_byte_range(16, 16)
+        .unwrap()
+        .parent()
+        .unwrap();
+
+    assert_eq!(format!("{root_node:?}"), "program @ 0..66 (0:0-2:0)");
+    assert_eq!(
+        format!("{declaration:?}"),
+        "lexical_declaration @ 0..10 (0:0-0:10)"
+    );
+    assert_eq!(
+        format!("{:?}", declaration.debug_with_source(source.as_bytes())),
+        "lexical_declaration @ 0..10 (0:0-0:10) \"let x = 1;\""
+    );
+    assert_eq!(string.kind(), "string");
+    assert_eq!(
+        format!("{:?}", string.debug_with_source(source.as_bytes())),
+        "string @ 15..63 (1:4-1:52) \"\\\"a long string that will not fit\u{2026}\""
+    );
+}
+
+#[test]
+fn test_node_kind_mapper() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    let source = "// a\nfn f() {} /* b */\n";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+
+    let mut mapper = KindMapper::new();
+    mapper
+        .map("line_comment", "comment")
+        .map("block_comment", "comment");
+
+    let mut cursor = root_node.walk();
+    let kinds = root_node
+        .named_children(&mut cursor)
+        .map(|node| (node.kind(), mapper.apply(node).kind().to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ("line_comment", "comment".to_string()),
+            ("function_item", "function_item".to_string()),
+            ("block_comment", "comment".to_string()),
+        ]
+    );
+
+    let comment = mapper.apply(root_node.named_child(2).unwrap());
+    assert_eq!(comment.node().kind(), "block_comment");
+    assert_eq!(comment.start_byte(), source.find("/*").unwrap());
+    assert_eq!(mapper.kind(&root_node), "source_file");
+}
+
 #[test]
 fn test_node_is_extra() {
     let mut parser = Parser::new();
@@ -831,6 +1283,64 @@ fn test_node_is_extra() {
     assert!(comment_node.is_extra());
 }
 
+#[test]
+fn test_node_extras_are_children_at_their_positions() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "// a\nfoo(/* b */ 1, // c\n  2);\n/* d */\n";
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut comments = Vec::new();
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if node.is_extra() {
+            comments.push(node);
+        }
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        while cursor.goto_parent() && !cursor.goto_next_sibling() {}
+        if cursor.node() == tree.root_node() {
+            break;
+        }
+    }
+
+    assert_eq!(
+        comments
+            .iter()
+            .map(|c| (
+                c.utf8_text(source.as_bytes()).unwrap(),
+                c.parent().unwrap().kind()
+            ))
+            .collect::<Vec<_>>(),
+        &[
+            ("// a", "program"),
+            ("/* b */", "arguments"),
+            ("// c", "arguments"),
+            ("/* d */", "program"),
+        ]
+    );
+
+    // The comments can be located relative to the nodes around them.
+    let comment = comments[1];
+    assert_eq!(comment.prev_sibling().unwrap().kind(), "(");
+    assert_eq!(comment.next_sibling().unwrap().kind(), "number");
+    let arguments = comment.parent().unwrap();
+    assert_eq!(
+        arguments
+            .named_children(&mut arguments.walk())
+            .map(|n| n.kind())
+            .collect::<Vec<_>>(),
+        &["comment", "number", "comment", "number"]
+    );
+
+    // The whitespace between the comment and its next sibling is recoverable
+    // from the gap between their byte ranges.
+    let next = comment.next_sibling().unwrap();
+    assert_eq!(&source[comment.end_byte()..next.start_byte()], " ");
+}
+
 #[test]
 fn test_node_is_error() {
     let mut parser = Parser::new();
@@ -845,6 +1355,204 @@ fn test_node_is_error() {
     assert!(child.is_error());
 }
 
+#[test]
+fn test_node_has_error_is_localized_to_the_erroneous_subtree() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser
+        .parse(b"var a = 1;\nvar \0 something;\nvar b = 2;", None)
+        .unwrap();
+    let root_node = tree.root_node();
+    assert!(root_node.has_error());
+    assert!(!root_node.is_error());
+    assert_eq!(root_node.named_child_count(), 3);
+
+    let first = root_node.named_child(0).unwrap();
+    let second = root_node.named_child(1).unwrap();
+    let third = root_node.named_child(2).unwrap();
+    assert!(!first.has_error());
+    assert!(!third.has_error());
+
+    // The statement containing the error reports it, but is not itself an
+    // `ERROR` node.
+    assert_eq!(second.kind(), "variable_declaration");
+    assert!(second.has_error());
+    assert!(!second.is_error());
+
+    let error = second.child(1).unwrap();
+    assert!(error.is_error());
+    assert!(error.has_error());
+
+    let declarator = second.named_child(1).unwrap();
+    assert_eq!(declarator.kind(), "variable_declarator");
+    assert!(!declarator.has_error());
+}
+
+#[test]
+fn test_point_from_str() {
+    assert_eq!("3,12".parse::<Point>(), Ok(Point::new(3, 12)));
+    assert_eq!(" 0 , 7 ".parse::<Point>(), Ok(Point::new(0, 7)));
+
+    for input in ["", "3", "3;12", "3,", ",12", "-1,2", "a,b", "1,2,3"] {
+        let error = input.parse::<Point>().unwrap_err();
+        assert_eq!(error, PositionParseError::Point(input.to_string()));
+        assert_eq!(
+            error.to_string(),
+            format!("Invalid point {input:?}, expected `row,column`")
+        );
+    }
+}
+
+#[test]
+fn test_point_and_range_display() {
+    assert_eq!(Point::new(0, 0).to_string(), "(0, 0)");
+    assert_eq!(Point::new(3, 12).to_string(), "(3, 12)");
+
+    let range = Range {
+        start_byte: 10,
+        end_byte: 42,
+        start_point: Point::new(0, 10),
+        end_point: Point::new(2, 3),
+    };
+    assert_eq!(range.to_string(), "10..42 (0, 10) - (2, 3)");
+
+    // The positions of nodes are displayed the same way.
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("x;\nfoo(bar);", None).unwrap();
+    let call = tree
+        .root_node()
+        .named_child(1)
+        .unwrap()
+        .named_child(0)
+        .unwrap();
+    assert_eq!(call.start_position().to_string(), "(1, 0)");
+    assert_eq!(call.range().to_string(), "3..11 (1, 0) - (1, 8)");
+}
+
+#[test]
+fn test_point_ordering_and_arithmetic() {
+    assert!(Point::new(0, 10) < Point::new(1, 0));
+    assert!(Point::new(1, 2) < Point::new(1, 3));
+    assert_eq!(
+        Point::new(2, 0).cmp(&Point::new(1, 100)),
+        std::cmp::Ordering::Greater
+    );
+
+    // Adding an extent within a single row moves the column.
+    assert_eq!(Point::new(3, 4) + Point::new(0, 5), Point::new(3, 9));
+    // Adding an extent that spans rows moves the row, and takes the extent's
+    // column.
+    assert_eq!(Point::new(3, 4) + Point::new(2, 1), Point::new(5, 1));
+
+    // Subtracting gives back the extent.
+    for (start, extent) in [
+        (Point::new(3, 4), Point::new(0, 5)),
+        (Point::new(3, 4), Point::new(2, 1)),
+        (Point::new(0, 0), Point::new(7, 0)),
+    ] {
+        assert_eq!((start + extent).saturating_sub(start), extent);
+    }
+    // Subtracting a later point gives zero.
+    assert_eq!(
+        Point::new(1, 4).saturating_sub(Point::new(1, 6)),
+        Point::new(0, 0)
+    );
+    assert_eq!(
+        Point::new(1, 4).saturating_sub(Point::new(2, 0)),
+        Point::new(0, 0)
+    );
+
+    // The extent of a node's text translates its start point to its end point.
+    let source = "a;\nfoo(\n  bar\n);";
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let call = tree
+        .root_node()
+        .named_child(1)
+        .unwrap()
+        .named_child(0)
+        .unwrap();
+    let extent = call.end_position().saturating_sub(call.start_position());
+    assert_eq!(extent, Point::new(2, 1));
+    assert_eq!(call.start_position() + extent, call.end_position());
+}
+
+#[test]
+fn test_range_containment_and_intersection() {
+    let range = |start_byte, end_byte, start_point, end_point| Range {
+        start_byte,
+        end_byte,
+        start_point,
+        end_point,
+    };
+
+    // `abc\ndef\nghi`, from the `c` on the first row to the `h` on the third.
+    let outer = range(2, 10, Point::new(0, 2), Point::new(2, 2));
+    assert!(!outer.contains_byte(1));
+    assert!(outer.contains_byte(2));
+    assert!(outer.contains_byte(9));
+    assert!(!outer.contains_byte(10));
+    assert!(!outer.contains_point(Point::new(0, 1)));
+    assert!(outer.contains_point(Point::new(0, 2)));
+    // Rows are compared before columns.
+    assert!(outer.contains_point(Point::new(1, 0)));
+    assert!(outer.contains_point(Point::new(1, 100)));
+    assert!(outer.contains_point(Point::new(2, 1)));
+    assert!(!outer.contains_point(Point::new(2, 2)));
+    assert!(!outer.contains_point(Point::new(3, 0)));
+
+    let inner = range(4, 7, Point::new(1, 0), Point::new(1, 3));
+    assert!(outer.intersects(&inner));
+    assert_eq!(outer.intersection(&inner), Some(inner));
+    assert_eq!(inner.intersection(&outer), Some(inner));
+
+    let overlapping = range(6, 12, Point::new(1, 2), Point::new(3, 0));
+    assert!(outer.intersects(&overlapping));
+    assert_eq!(
+        outer.intersection(&overlapping),
+        Some(range(6, 10, Point::new(1, 2), Point::new(2, 2)))
+    );
+    assert_eq!(
+        overlapping.intersection(&outer),
+        outer.intersection(&overlapping)
+    );
+
+    // Adjacent ranges don't intersect.
+    let adjacent = range(10, 12, Point::new(2, 2), Point::new(3, 0));
+    assert!(!outer.intersects(&adjacent));
+    assert!(!adjacent.intersects(&outer));
+    assert_eq!(outer.intersection(&adjacent), None);
+
+    // Empty ranges contain and intersect nothing.
+    let empty = range(5, 5, Point::new(1, 1), Point::new(1, 1));
+    assert!(!empty.contains_byte(5));
+    assert!(!empty.contains_point(Point::new(1, 1)));
+    assert!(!outer.intersects(&empty));
+    assert!(!empty.intersects(&outer));
+    assert!(!empty.intersects(&empty));
+    assert_eq!(outer.intersection(&empty), None);
+}
+
+#[test]
+fn test_parse_byte_range() {
+    assert_eq!(parse_byte_range("10..42"), Ok(10..42));
+    assert_eq!(parse_byte_range("5..5"), Ok(5..5));
+    assert_eq!(parse_byte_range(" 1 .. 2 "), Ok(1..2));
+
+    for input in ["", "10", "10-42", "10..", "..42", "42..10", "a..b", "1...2"] {
+        assert_eq!(
+            parse_byte_range(input),
+            Err(PositionParseError::ByteRange(input.to_string()))
+        );
+    }
+    assert_eq!(
+        parse_byte_range("42..10").unwrap_err().to_string(),
+        "Invalid byte range \"42..10\", expected `start..end` with start <= end"
+    );
+}
+
 #[test]
 fn test_edit_point() {
     let edit = InputEdit {
@@ -948,6 +1656,114 @@ fn test_node_sexp() {
     assert_eq!(identifier_node.to_sexp(), "(identifier)");
 }
 
+#[test]
+fn test_node_sexp_with_options() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("x;\na + b;", None).unwrap();
+    let statement = tree.root_node().named_child(1).unwrap();
+
+    assert_eq!(
+        statement.to_sexp_with_options(SexpOptions::default()),
+        statement.to_sexp()
+    );
+    assert_eq!(
+        statement.to_sexp(),
+        "(expression_statement (binary_expression left: (identifier) right: (identifier)))"
+    );
+    assert_eq!(
+        statement.to_sexp_with_options(SexpOptions::new().field_names(false)),
+        "(expression_statement (binary_expression (identifier) (identifier)))"
+    );
+    assert_eq!(
+        statement.to_sexp_with_options(SexpOptions::new().point_ranges(true)),
+        concat!(
+            "(expression_statement [1, 0] - [1, 6] (binary_expression [1, 0] - [1, 5]",
+            " left: (identifier [1, 0] - [1, 1]) right: (identifier [1, 4] - [1, 5])))"
+        )
+    );
+    assert_eq!(
+        statement.to_sexp_with_options(
+            SexpOptions::new()
+                .field_names(false)
+                .byte_ranges(true)
+                .anonymous_nodes(true)
+        ),
+        concat!(
+            "(expression_statement 3..9 (binary_expression 3..8",
+            " (identifier 3..4) (\"+\" 5..6) (identifier 7..8)) (\";\" 8..9))"
+        )
+    );
+
+    // Unexpected characters are shown the same way as in the default output.
+    let tree = parser.parse(b"var \0 something;", None).unwrap();
+    let sexp = tree
+        .root_node()
+        .to_sexp_with_options(SexpOptions::new().byte_ranges(true));
+    assert!(sexp.starts_with("(program 0..16 (variable_declaration 0..16 "));
+    assert!(sexp.contains("(UNEXPECTED '\\0' "));
+    assert!(sexp.ends_with("name: (identifier 6..15))))"));
+}
+
+#[test]
+fn test_node_write_sexp() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse(b"var \0 something;", None).unwrap();
+    let root_node = tree.root_node();
+
+    let mut sexp = String::new();
+    root_node.write_sexp(&mut sexp).unwrap();
+    assert_eq!(
+        sexp,
+        "(program (variable_declaration (ERROR (UNEXPECTED '\\0')) (variable_declarator name: (identifier))))"
+    );
+    assert_eq!(sexp, root_node.to_sexp());
+
+    let declarator = root_node.named_child(0).unwrap().named_child(1).unwrap();
+    let mut sexp = String::new();
+    declarator.write_sexp(&mut sexp).unwrap();
+    assert_eq!(sexp, "(variable_declarator name: (identifier))");
+
+    // The output is written in small pieces, and writing stops at the first
+    // error.
+    struct LimitedWriter {
+        text: String,
+        pieces: Vec<usize>,
+        limit: usize,
+    }
+
+    impl std::fmt::Write for LimitedWriter {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            if self.text.len() + s.len() > self.limit {
+                return Err(std::fmt::Error);
+            }
+            self.text.push_str(s);
+            self.pieces.push(s.len());
+            Ok(())
+        }
+    }
+
+    let mut writer = LimitedWriter {
+        text: String::new(),
+        pieces: Vec::new(),
+        limit: usize::MAX,
+    };
+    root_node.write_sexp(&mut writer).unwrap();
+    assert_eq!(writer.text, root_node.to_sexp());
+    assert!(writer.pieces.len() > 1);
+    assert!(*writer.pieces.iter().max().unwrap() <= "variable_declaration".len());
+
+    let mut writer = LimitedWriter {
+        text: String::new(),
+        pieces: Vec::new(),
+        limit: 20,
+    };
+    assert!(root_node.write_sexp(&mut writer).is_err());
+    assert!(!writer.text.is_empty());
+    assert!(root_node.to_sexp().starts_with(&writer.text));
+}
+
 #[test]
 fn test_node_field_names() {
     // - "x":
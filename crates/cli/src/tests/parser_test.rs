@@ -8,8 +8,12 @@ use std::{
     time::{self, Duration},
 };
 
+use indoc::indoc;
+use streaming_iterator::StreamingIterator;
 use tree_sitter::{
-    Decode, IncludedRangesError, InputEdit, LogType, ParseOptions, ParseState, Parser, Point, Range,
+    AmbiguityInfo, ChunkedText, ChunkedTextProvider, Decode, DecodeStop, IncludedRangesError,
+    IncrementalParseError, InputEdit, LogType, ParseOptions, ParseState, Parser, ParserError,
+    Point, Query, QueryCursor, Range, RangeErrorKind, Tree, validate_included_ranges,
 };
 use tree_sitter_generate::load_grammar_file;
 use tree_sitter_proc_macro::retry;
@@ -21,7 +25,7 @@ use super::helpers::{
 };
 use crate::{
     fuzz::edits::Edit,
-    parse::perform_edit,
+    parse::{perform_edit, render_sexp},
     tests::{
         generate_parser,
         helpers::fixtures::{fixtures_dir, get_test_fixture_language},
@@ -118,6 +122,10 @@ fn test_parsing_with_debug_graph_enabled() {
             !has_zero_indexed_row(&line),
             "Graph log output includes zero-indexed row: {line}",
         );
+        assert!(
+            !line.ends_with('\r'),
+            "Graph log output includes a carriage return: {line:?}",
+        );
     }
 }
 
@@ -165,6 +173,81 @@ fn test_parsing_with_custom_utf8_input() {
     assert_eq!(root.child(0).unwrap().kind(), "function_item");
 }
 
+#[test]
+fn test_parsing_lines() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let lines = &["pub fn foo() {", "  1", "}"];
+    let tree = parser.parse_lines(lines, None).unwrap();
+    let root = tree.root_node();
+    assert_eq!(
+        root.to_sexp(),
+        concat!(
+            "(source_file ",
+            "(function_item ",
+            "(visibility_modifier) ",
+            "name: (identifier) ",
+            "parameters: (parameters) ",
+            "body: (block (integer_literal))))"
+        )
+    );
+
+    // Positions match the text with the lines joined by single newlines.
+    let joined = lines.join("\n");
+    assert_eq!(root.end_byte(), joined.len());
+    assert_eq!(root.end_position(), Point::new(2, 1));
+    let literal = root.descendant_for_byte_range(17, 17).unwrap();
+    assert_eq!(literal.kind(), "integer_literal");
+    assert_eq!(literal.utf8_text(joined.as_bytes()).unwrap(), "1");
+    assert_eq!(literal.start_position(), Point::new(1, 2));
+}
+
+#[test]
+fn test_parsing_with_chunked_text_provider() {
+    struct Rope {
+        chunks: Vec<&'static str>,
+    }
+
+    impl ChunkedText for Rope {
+        fn chunk_at(&self, byte: usize) -> &[u8] {
+            let mut start = 0;
+            for chunk in &self.chunks {
+                if byte < start + chunk.len() {
+                    return &chunk.as_bytes()[byte - start..];
+                }
+                start += chunk.len();
+            }
+            &[]
+        }
+    }
+
+    // The chunk boundaries split both identifiers.
+    let rope = Rope {
+        chunks: vec!["const ab", "c = x", "yz; abc;"],
+    };
+    let source = rope.chunks.concat();
+
+    let mut parser = Parser::new();
+    let language = get_language("javascript");
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse_with_provider(&rope, None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        parser.parse(&source, None).unwrap().root_node().to_sexp()
+    );
+
+    // Text predicates can read node text that spans multiple chunks.
+    let query = Query::new(&language, r#"((identifier) @id (#eq? @id "abc"))"#).unwrap();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, tree.root_node(), ChunkedTextProvider(&rope));
+    let mut ranges = Vec::new();
+    while let Some(m) = matches.next() {
+        ranges.push(m.captures[0].node.byte_range());
+    }
+    assert_eq!(ranges, [6..9, 17..20]);
+}
+
 #[test]
 fn test_parsing_with_custom_utf16le_input() {
     let mut parser = Parser::new();
@@ -413,6 +496,29 @@ fn test_parsing_unexpected_null_characters_within_source() {
     );
 }
 
+#[test]
+fn test_parsing_null_characters_between_valid_statements() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = b"var a = 1;\n\0\nvar b = 2;";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+
+    // The null character does not end the input.
+    assert_eq!(root_node.end_byte(), source.len());
+    assert!(root_node.has_error());
+
+    let first = root_node.named_child(0).unwrap();
+    let last = root_node
+        .named_child(root_node.named_child_count() as u32 - 1)
+        .unwrap();
+    assert_eq!(first.kind(), "variable_declaration");
+    assert_eq!(last.kind(), "variable_declaration");
+    assert!(!first.has_error());
+    assert!(!last.has_error());
+    assert_eq!(last.utf8_text(source).unwrap(), "var b = 2;");
+}
+
 #[test]
 fn test_parsing_ends_when_input_callback_returns_empty() {
     let mut parser = Parser::new();
@@ -436,6 +542,306 @@ fn test_parsing_ends_when_input_callback_returns_empty() {
     assert_eq!(tree.root_node().end_byte(), 6);
 }
 
+#[test]
+fn test_parsing_with_max_input_bytes() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+
+    // The callback never signals the end of the input.
+    let mut max_offset = 0;
+    let tree = parser
+        .parse_with_options(
+            &mut |offset, _| {
+                max_offset = max_offset.max(offset);
+                if offset == 0 { &b"["[..] } else { b"0," }
+            },
+            None,
+            Some(ParseOptions::new().max_input_bytes(100)),
+        )
+        .unwrap();
+    assert_eq!(tree.root_node().end_byte(), 100);
+    assert!(tree.root_node().has_error());
+    assert!(max_offset < 100);
+
+    // The limit also applies when reading a slice.
+    let tree = parser
+        .parse_with_options(
+            &mut |offset, _| b"[1, 2]".get(offset..).unwrap_or_default(),
+            None,
+            Some(ParseOptions::new().max_input_bytes(5)),
+        )
+        .unwrap();
+    assert_eq!(tree.root_node().end_byte(), 5);
+    assert!(tree.root_node().has_error());
+}
+
+#[test]
+fn test_parsing_with_max_column() {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&get_test_fixture_language("depends_on_column"))
+        .unwrap();
+
+    let parse = |parser: &mut Parser, code: &[u8], max_column: Option<u32>| {
+        let options = max_column.map(|max_column| ParseOptions::new().max_column(max_column));
+        parser
+            .parse_with_options(&mut |i, _| code.get(i..).unwrap_or_default(), None, options)
+            .unwrap()
+            .root_node()
+            .to_sexp()
+    };
+
+    // Without a limit, columns are counted in characters from the start of the line.
+    assert_eq!(
+        parse(&mut parser, b"\n    x", None),
+        "(x_is_at (even_column))"
+    );
+
+    // Columns past the limit are reported as the limit itself.
+    assert_eq!(
+        parse(&mut parser, b"\n    x", Some(3)),
+        "(x_is_at (odd_column))"
+    );
+    assert_eq!(
+        parse(&mut parser, b"\n            x", Some(3)),
+        "(x_is_at (odd_column))"
+    );
+    assert_eq!(
+        parse(&mut parser, b"\n  x", Some(3)),
+        "(x_is_at (even_column))"
+    );
+
+    // The limit only applies to the parse it was given to.
+    assert_eq!(
+        parse(&mut parser, b"\n            x", None),
+        "(x_is_at (even_column))"
+    );
+}
+
+#[test]
+fn test_parsing_with_incremental_verification() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut code = b"123 + 456 * (10 + x);".to_vec();
+    let mut tree = parser.parse(&code, None).unwrap();
+    perform_edit(
+        &mut tree,
+        &mut code,
+        &Edit {
+            position: 3,
+            deleted_length: 0,
+            inserted_text: b" || 5".to_vec(),
+        },
+    )
+    .unwrap();
+
+    let tree = parser
+        .parse_with_verification(&mut |i, _| &code[i.min(code.len())..], &tree, None)
+        .unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        parser.parse(&code, None).unwrap().root_node().to_sexp()
+    );
+}
+
+#[test]
+fn test_parsing_with_incremental_verification_detects_mismatches() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("abc;", None).unwrap();
+
+    // The text changes without a corresponding edit to the old tree, so the
+    // incrementally-parsed tree is stale.
+    let code = b"123;";
+    let mut reads = 0;
+    let mut on_read = |_, _| reads += 1;
+    let error = parser
+        .parse_with_verification(
+            &mut |i, _| &code[i.min(code.len())..],
+            &tree,
+            Some(ParseOptions::new().on_read(&mut on_read)),
+        )
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Incremental parse does not match a parse from scratch")
+    );
+
+    let IncrementalParseError::Mismatch {
+        incremental,
+        fresh,
+        diff,
+    } = error
+    else {
+        panic!("Expected a mismatch, got {error:?}");
+    };
+    assert_ne!(incremental, fresh);
+    assert!(diff.lines().any(|line| line.starts_with("- ")));
+    assert!(diff.lines().any(|line| line.starts_with("+ ")));
+
+    // Only the incremental parse is reported to the read callback.
+    let mut fresh_reads = 0;
+    let mut on_read = |_, _| fresh_reads += 1;
+    parser.parse_with_options(
+        &mut |i, _| &code[i.min(code.len())..],
+        Some(&tree),
+        Some(ParseOptions::new().on_read(&mut on_read)),
+    );
+    assert_eq!(reads, fresh_reads);
+}
+
+#[test]
+fn test_parsing_with_ambiguity_reporting() {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&get_test_fixture_language("dynamic_precedence"))
+        .unwrap();
+
+    // `T * x` is either a declaration or a multiplication. The declaration is
+    // chosen because of its dynamic precedence.
+    let mut ambiguities = Vec::new();
+    let mut report = |ambiguity: AmbiguityInfo| ambiguities.push(ambiguity);
+    let code = b"T * x";
+    let tree = parser
+        .parse_with_options(
+            &mut |i, _| &code[i.min(code.len())..],
+            None,
+            Some(ParseOptions::new().report_ambiguity(&mut report)),
+        )
+        .unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (declaration (type (identifier)) (declarator (identifier))))"
+    );
+    assert_eq!(
+        ambiguities,
+        &[AmbiguityInfo {
+            kind: "program",
+            byte_range: 0..5,
+            chosen_children: vec!["declaration"],
+            rejected_children: vec!["expression"],
+        }]
+    );
+
+    // The callback is only installed for the parse that it was given to.
+    ambiguities.clear();
+    parser.parse(code, None).unwrap();
+    assert!(ambiguities.is_empty());
+
+    // Left-associative multiplication is resolved when the grammar is
+    // generated, so there is no ambiguity at parse time.
+    let mut report = |ambiguity: AmbiguityInfo| ambiguities.push(ambiguity);
+    let code = b"w * x * y";
+    parser
+        .parse_with_options(
+            &mut |i, _| &code[i.min(code.len())..],
+            None,
+            Some(ParseOptions::new().report_ambiguity(&mut report)),
+        )
+        .unwrap();
+    assert!(ambiguities.is_empty());
+}
+
+#[test]
+fn test_parsing_with_a_read_callback() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut code = b"123 + 456 * (10 + x);".to_vec();
+    let mut reads = Vec::new();
+    let tree = parser
+        .parse_with_options(
+            &mut |i, _| &code[i.min(code.len())..],
+            None,
+            Some(ParseOptions::new().on_read(&mut |offset, len| reads.push((offset, len)))),
+        )
+        .unwrap();
+    assert_eq!(reads.first(), Some(&(0, code.len())));
+    assert_eq!(reads.last(), Some(&(code.len(), 0)));
+
+    // During an incremental parse, only the text around the edit is read.
+    let mut tree = tree;
+    perform_edit(
+        &mut tree,
+        &mut code,
+        &Edit {
+            position: 3,
+            deleted_length: 0,
+            inserted_text: b" || 5".to_vec(),
+        },
+    )
+    .unwrap();
+    let mut reads = Vec::new();
+    let mut recorder = ReadRecorder::new(&code);
+    parser
+        .parse_with_options(
+            &mut |i, _| recorder.read(i),
+            Some(&tree),
+            Some(ParseOptions::new().on_read(&mut |offset, len| {
+                reads.push((offset, len));
+            })),
+        )
+        .unwrap();
+    assert_eq!(recorder.strings_read(), vec!["123 || 5 "]);
+    let mut offsets = reads
+        .iter()
+        .filter(|(_, len)| *len > 0)
+        .map(|(offset, _)| *offset)
+        .collect::<Vec<_>>();
+    offsets.sort_unstable();
+    offsets.dedup();
+    // The semicolon after the unchanged subtree is lexed again as well.
+    let mut expected_offsets = (0.."123 || 5 ".len()).collect::<Vec<_>>();
+    expected_offsets.push(code.len() - 1);
+    assert_eq!(offsets, expected_offsets);
+
+    // Offsets and lengths are measured in code units for UTF-16 input.
+    let utf16_code = "a;".encode_utf16().collect::<Vec<_>>();
+    let mut reads = Vec::new();
+    parser
+        .parse_utf16_le_slice(
+            &utf16_code,
+            None,
+            Some(ParseOptions::new().on_read(&mut |offset, len| reads.push((offset, len)))),
+        )
+        .unwrap();
+    assert_eq!(reads.first(), Some(&(0, 2)));
+    assert_eq!(reads.last(), Some(&(2, 0)));
+}
+
+#[test]
+fn test_parsing_coverage() {
+    let language = get_language("javascript");
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+
+    let simple = parser.parse_coverage("x;").unwrap();
+    assert!(!simple.states.is_empty());
+    let reduced_kinds = simple
+        .reduced_symbols
+        .iter()
+        .map(|id| language.node_kind_for_id(*id).unwrap())
+        .collect::<Vec<_>>();
+    assert!(reduced_kinds.contains(&"program"));
+    assert!(reduced_kinds.contains(&"expression_statement"));
+
+    // Parsing the same input again exercises exactly the same parts of the table.
+    assert_eq!(parser.parse_coverage("x;").unwrap(), simple);
+
+    // More varied input exercises more of the table.
+    let varied = parser
+        .parse_coverage("class A { m() { for (const x of y) { if (x) return [x, ...y]; } } }")
+        .unwrap();
+    let mut combined = simple.clone();
+    combined.merge(&varied);
+    assert!(combined.states.len() > simple.states.len());
+    assert!(combined.reduced_symbols.len() > simple.reduced_symbols.len());
+    assert!(combined.states.is_superset(&simple.states));
+    assert!(combined.states.is_superset(&varied.states));
+}
+
 // Incremental parsing
 
 #[test]
@@ -539,11 +945,21 @@ fn test_parsing_empty_file_with_reused_tree() {
     let mut parser = Parser::new();
     parser.set_language(&get_language("rust")).unwrap();
 
-    let tree = parser.parse("", None);
-    parser.parse("", tree.as_ref());
-
-    let tree = parser.parse("\n  ", None);
-    parser.parse("\n  ", tree.as_ref());
+    for source in ["", "\n  "] {
+        let tree = parser.parse(source, None).unwrap();
+        let new_tree = parser.parse(source, Some(&tree)).unwrap();
+
+        for tree in [tree, new_tree] {
+            let root = tree.root_node();
+            assert_eq!(root.kind(), "source_file");
+            assert_eq!(root.child_count(), 0);
+            assert_eq!(root.named_child_count(), 0);
+            assert_eq!(root.child(0), None);
+            assert!(!root.has_error());
+            assert_eq!(root.byte_range(), source.len()..source.len());
+            assert_eq!(root.to_sexp(), "(source_file)");
+        }
+    }
 }
 
 #[test]
@@ -817,47 +1233,64 @@ fn test_parsing_cancelled_by_another_thread() {
 // Timeouts
 
 #[test]
-#[retry(10)]
 fn test_parsing_with_a_timeout() {
     let mut parser = Parser::new();
     parser.set_language(&get_language("json")).unwrap();
 
-    // Parse an infinitely-long array, but pause after 1ms of processing.
-    let start_time = time::Instant::now();
+    // Parse an infinitely-long array, but pause after 1000 steps.
+    let mut step_counts = Vec::new();
     let tree = parser.parse_with_options(
         &mut |offset, _| {
             if offset == 0 { b" [" } else { b",0" }
         },
         None,
-        Some(ParseOptions::new().progress_callback(&mut |_| {
-            if start_time.elapsed().as_micros() > 1000 {
-                ControlFlow::Break(())
-            } else {
-                ControlFlow::Continue(())
-            }
-        })),
+        Some(
+            ParseOptions::new()
+                .progress_every_n_steps(1)
+                .progress_callback(&mut |state| {
+                    step_counts.push(state.step_count());
+                    if state.step_count() >= 1000 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                }),
+        ),
     );
     assert!(tree.is_none());
-    assert!(start_time.elapsed().as_micros() < 2000);
+    assert_eq!(step_counts.last(), Some(&1000));
 
-    // Continue parsing, but pause after 1 ms of processing.
-    let start_time = time::Instant::now();
+    // Continue parsing, but pause after 2000 more steps. The step count keeps
+    // growing across the resumed parse.
+    let first_step_counts = step_counts.clone();
+    step_counts.clear();
     let tree = parser.parse_with_options(
         &mut |offset, _| {
             if offset == 0 { b" [" } else { b",0" }
         },
         None,
-        Some(ParseOptions::new().progress_callback(&mut |_| {
-            if start_time.elapsed().as_micros() > 5000 {
-                ControlFlow::Break(())
-            } else {
-                ControlFlow::Continue(())
-            }
-        })),
+        Some(
+            ParseOptions::new()
+                .progress_every_n_steps(1)
+                .progress_callback(&mut |state| {
+                    step_counts.push(state.step_count());
+                    if state.step_count() >= 3000 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                }),
+        ),
     );
     assert!(tree.is_none());
-    assert!(start_time.elapsed().as_micros() > 100);
-    assert!(start_time.elapsed().as_micros() < 10000);
+    assert!(step_counts[0] > 1000);
+    assert_eq!(step_counts.last(), Some(&3000));
+    assert!(
+        first_step_counts
+            .iter()
+            .chain(&step_counts)
+            .is_sorted_by(|a, b| a < b)
+    );
 
     // Finish parsing
     let tree = parser
@@ -875,29 +1308,176 @@ fn test_parsing_with_a_timeout() {
 }
 
 #[test]
-#[retry(10)]
-fn test_parsing_with_a_timeout_and_a_reset() {
+fn test_parsing_with_a_fixed_progress_interval() {
+    let source = format!("[{}0]", "0,".repeat(1000));
+    let parse = |interval: Option<u32>| {
+        let mut parser = Parser::new();
+        parser.set_language(&get_language("json")).unwrap();
+
+        let mut states = Vec::new();
+        let mut callback = |state: &ParseState| {
+            states.push((state.step_count(), state.current_byte_offset()));
+            ControlFlow::Continue(())
+        };
+        let mut options = ParseOptions::new().progress_callback(&mut callback);
+        if let Some(interval) = interval {
+            options = options.progress_every_n_steps(interval);
+        }
+        let tree = parser
+            .parse_with_options(
+                &mut |offset, _| &source.as_bytes()[offset.min(source.len())..],
+                None,
+                Some(options),
+            )
+            .unwrap();
+        assert!(!tree.root_node().has_error());
+        states
+    };
+
+    // The callback is called at the same points on every run.
+    let states = parse(Some(7));
+    assert!(!states.is_empty());
+    assert_eq!(parse(Some(7)), states);
+    assert!(states.is_sorted_by(|a, b| a.0 < b.0));
+
+    // By default, the callback is called much less often.
+    let default_states = parse(None);
+    assert_eq!(parse(Some(0)), default_states);
+    assert!(default_states.len() < states.len());
+}
+
+#[test]
+fn test_parsing_utf16_slice_with_a_timeout() {
     let mut parser = Parser::new();
     parser.set_language(&get_language("json")).unwrap();
 
-    let start_time = time::Instant::now();
-    let code = "[\"ok\", 1, 2, 3, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32]";
-    let tree = parser.parse_with_options(
-        &mut |offset, _| {
-            if offset >= code.len() {
-                &[]
-            } else {
-                &code.as_bytes()[offset..]
-            }
-        },
+    let source = format!("[{}0]", "0,".repeat(10000));
+    let le = source.encode_utf16().collect::<Vec<_>>();
+    let be = le.iter().map(|c| c.to_be()).collect::<Vec<_>>();
+    let le = le.iter().map(|c| c.to_le()).collect::<Vec<_>>();
+
+    // Cancel parsing as soon as the progress callback is invoked.
+    let tree = parser.parse_utf16_le_slice(
+        &le,
         None,
-        Some(ParseOptions::new().progress_callback(&mut |_| {
-            if start_time.elapsed().as_micros() > 5 {
-                ControlFlow::Break(())
+        Some(ParseOptions::new().progress_callback(&mut |_| ControlFlow::Break(()))),
+    );
+    assert!(tree.is_none());
+    parser.reset();
+
+    let tree = parser.parse_utf16_be_slice(
+        &be,
+        None,
+        Some(ParseOptions::new().progress_callback(&mut |_| ControlFlow::Break(()))),
+    );
+    assert!(tree.is_none());
+    parser.reset();
+
+    // Without cancellation, both encodings parse to completion.
+    for tree in [
+        parser.parse_utf16_le_slice(
+            &le,
+            None,
+            Some(ParseOptions::new().progress_callback(&mut |_| ControlFlow::Continue(()))),
+        ),
+        parser.parse_utf16_be_slice(&be, None, None),
+    ] {
+        let tree = tree.unwrap();
+        assert_eq!(tree.root_node().child(0).unwrap().kind(), "array");
+        assert!(!tree.root_node().has_error());
+    }
+}
+
+#[test]
+fn test_parsing_utf16_slice_after_editing() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let code = "123 + 456 * (10 + x);".encode_utf16().collect::<Vec<_>>();
+    let mut tree = parser.parse_utf16_le_slice(&code, None, None).unwrap();
+
+    // Insert " || 5" after "123". Edits are expressed in bytes, which are
+    // twice the number of code units.
+    let inserted = " || 5".encode_utf16().collect::<Vec<_>>();
+    let mut new_code = code[..3].to_vec();
+    new_code.extend_from_slice(&inserted);
+    new_code.extend_from_slice(&code[3..]);
+    tree.edit(&InputEdit {
+        start_byte: 6,
+        old_end_byte: 6,
+        new_end_byte: 6 + inserted.len() * 2,
+        start_position: Point::new(0, 6),
+        old_end_position: Point::new(0, 6),
+        new_end_position: Point::new(0, 6 + inserted.len() * 2),
+    });
+
+    let new_tree = parser
+        .parse_utf16_le_slice(&new_code, Some(&tree), None)
+        .unwrap();
+    let expected_sexp = concat!(
+        "(program (expression_statement (binary_expression ",
+        "left: (number) ",
+        "right: (binary_expression ",
+        "left: (number) ",
+        "right: (binary_expression ",
+        "left: (number) ",
+        "right: (parenthesized_expression (binary_expression left: (number) right: (identifier))))))))",
+    );
+    assert_eq!(new_tree.root_node().to_sexp(), expected_sexp);
+
+    // The slice entry point is incremental: only the edited region is re-read
+    // when the same buffer is served one code unit at a time.
+    let mut indices_read = Vec::new();
+    let recorded_tree = parser
+        .parse_utf16_le_with_options(
+            &mut |i, _| {
+                if i < new_code.len() {
+                    indices_read.push(i);
+                    &new_code[i..=i]
+                } else {
+                    &[]
+                }
+            },
+            Some(&tree),
+            None,
+        )
+        .unwrap();
+    assert_eq!(recorded_tree.root_node().to_sexp(), expected_sexp);
+    indices_read.sort_unstable();
+    indices_read.dedup();
+    let strings_read = indices_read
+        .chunk_by(|a, b| a + 1 == *b)
+        .map(|run| String::from_utf16(&new_code[run[0]..=run[run.len() - 1]]).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(strings_read, ["123 || 5 ", ";"]);
+}
+
+#[test]
+fn test_parsing_with_a_timeout_and_a_reset() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+
+    let code = "[\"ok\", 1, 2, 3, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32]";
+    let tree = parser.parse_with_options(
+        &mut |offset, _| {
+            if offset >= code.len() {
+                &[]
             } else {
-                ControlFlow::Continue(())
+                &code.as_bytes()[offset..]
             }
-        })),
+        },
+        None,
+        Some(
+            ParseOptions::new()
+                .progress_every_n_steps(1)
+                .progress_callback(&mut |state| {
+                    if state.step_count() >= 50 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                }),
+        ),
     );
     assert!(tree.is_none());
 
@@ -917,7 +1497,6 @@ fn test_parsing_with_a_timeout_and_a_reset() {
         "string"
     );
 
-    let start_time = time::Instant::now();
     let code = "[\"ok\", 1, 2, 3, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32]";
     let tree = parser.parse_with_options(
         &mut |offset, _| {
@@ -928,13 +1507,17 @@ fn test_parsing_with_a_timeout_and_a_reset() {
             }
         },
         None,
-        Some(ParseOptions::new().progress_callback(&mut |_| {
-            if start_time.elapsed().as_micros() > 5 {
-                ControlFlow::Break(())
-            } else {
-                ControlFlow::Continue(())
-            }
-        })),
+        Some(
+            ParseOptions::new()
+                .progress_every_n_steps(1)
+                .progress_callback(&mut |state| {
+                    if state.step_count() >= 50 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                }),
+        ),
     );
     assert!(tree.is_none());
 
@@ -957,14 +1540,74 @@ fn test_parsing_with_a_timeout_and_a_reset() {
 }
 
 #[test]
-#[retry(10)]
+fn test_parser_is_resumable() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    assert!(!parser.is_resumable());
+
+    let code = format!("[{}0]", "0, ".repeat(1000));
+    let tree = parser.parse_with_options(
+        &mut |offset, _| code.as_bytes().get(offset..).unwrap_or_default(),
+        None,
+        Some(ParseOptions::new().progress_callback(&mut |_| ControlFlow::Break(()))),
+    );
+    assert!(tree.is_none());
+    assert!(parser.is_resumable());
+
+    // Finishing the parse leaves no resumable state behind.
+    let tree = parser.parse(&code, None).unwrap();
+    assert!(!tree.root_node().has_error());
+    assert!(!parser.is_resumable());
+
+    let tree = parser.parse_with_options(
+        &mut |offset, _| code.as_bytes().get(offset..).unwrap_or_default(),
+        None,
+        Some(ParseOptions::new().progress_callback(&mut |_| ControlFlow::Break(()))),
+    );
+    assert!(tree.is_none());
+    assert!(parser.is_resumable());
+    parser.discard_state();
+    assert!(!parser.is_resumable());
+}
+
+#[test]
+fn test_parser_records_where_a_halted_parse_stopped() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    assert_eq!(parser.parsed_through_byte(), None);
+
+    let code = format!("[{}0]", "0, ".repeat(1000));
+    let mut offset = 0;
+    let mut callback_count = 0;
+    let tree = parser.parse_with_options(
+        &mut |i, _| code.as_bytes().get(i..).unwrap_or_default(),
+        None,
+        Some(ParseOptions::new().progress_callback(&mut |state| {
+            offset = state.current_byte_offset();
+            callback_count += 1;
+            if callback_count == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })),
+    );
+    assert!(tree.is_none());
+    assert!(offset > 0 && offset < code.len());
+    assert_eq!(parser.parsed_through_byte(), Some(offset));
+
+    // Once the parse is finished, there is no halted position.
+    parser.parse(&code, None).unwrap();
+    assert_eq!(parser.parsed_through_byte(), None);
+}
+
+#[test]
 fn test_parsing_with_a_timeout_and_implicit_reset() {
     allocations::record(|| {
         let mut parser = Parser::new();
         parser.set_language(&get_language("javascript")).unwrap();
 
         let code = "[\"ok\", 1, 2, 3, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32]";
-        let start_time = time::Instant::now();
         let tree = parser.parse_with_options(
             &mut |offset, _| {
                 if offset >= code.len() {
@@ -974,13 +1617,17 @@ fn test_parsing_with_a_timeout_and_implicit_reset() {
                 }
             },
             None,
-            Some(ParseOptions::new().progress_callback(&mut |_| {
-                if start_time.elapsed().as_micros() > 5 {
-                    ControlFlow::Break(())
-                } else {
-                    ControlFlow::Continue(())
-                }
-            })),
+            Some(
+                ParseOptions::new()
+                    .progress_every_n_steps(1)
+                    .progress_callback(&mut |state| {
+                        if state.step_count() >= 50 {
+                            ControlFlow::Break(())
+                        } else {
+                            ControlFlow::Continue(())
+                        }
+                    }),
+            ),
         );
         assert!(tree.is_none());
 
@@ -1004,14 +1651,12 @@ fn test_parsing_with_a_timeout_and_implicit_reset() {
 }
 
 #[test]
-#[retry(10)]
 fn test_parsing_with_timeout_and_no_completion() {
     allocations::record(|| {
         let mut parser = Parser::new();
         parser.set_language(&get_language("javascript")).unwrap();
 
         let code = "[\"ok\", 1, 2, 3, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32]";
-        let start_time = time::Instant::now();
         let tree = parser.parse_with_options(
             &mut |offset, _| {
                 if offset >= code.len() {
@@ -1021,13 +1666,17 @@ fn test_parsing_with_timeout_and_no_completion() {
                 }
             },
             None,
-            Some(ParseOptions::new().progress_callback(&mut |_| {
-                if start_time.elapsed().as_micros() > 5 {
-                    ControlFlow::Break(())
-                } else {
-                    ControlFlow::Continue(())
-                }
-            })),
+            Some(
+                ParseOptions::new()
+                    .progress_every_n_steps(1)
+                    .progress_callback(&mut |state| {
+                        if state.step_count() >= 50 {
+                            ControlFlow::Break(())
+                        } else {
+                            ControlFlow::Continue(())
+                        }
+                    }),
+            ),
         );
         assert!(tree.is_none());
 
@@ -1035,6 +1684,33 @@ fn test_parsing_with_timeout_and_no_completion() {
     });
 }
 
+#[test]
+fn test_parsing_with_phase_profiling() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let code = "function a() { return [1, 2, 3].map(x => x * 2); }\n".repeat(200);
+
+    parser.parse(&code, None).unwrap();
+    assert_eq!(parser.phase_timings(), None);
+
+    let tree = parser
+        .parse_with_options(
+            &mut |offset, _| &code.as_bytes()[offset.min(code.len())..],
+            None,
+            Some(ParseOptions::new().profile_phases(true)),
+        )
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+
+    // Valid code never enters error recovery.
+    let timings = parser.phase_timings().unwrap();
+    assert_eq!(timings.error_recovery, Duration::ZERO);
+
+    // Profiling only applies to the parse that requested it.
+    parser.parse(&code, None).unwrap();
+    assert_eq!(parser.phase_timings(), None);
+}
+
 #[test]
 fn test_parsing_with_timeout_during_balancing() {
     allocations::record(|| {
@@ -1127,6 +1803,131 @@ fn test_parsing_with_timeout_during_balancing() {
     });
 }
 
+#[test]
+#[retry(10)]
+fn test_parsing_with_a_deadline() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+
+    // Parse an infinitely-long array, but stop after 1ms.
+    let start_time = time::Instant::now();
+    let tree = parser.parse_with_options(
+        &mut |offset, _| {
+            if offset == 0 { b" [" } else { b",0" }
+        },
+        None,
+        Some(ParseOptions::new().deadline(start_time + Duration::from_millis(1))),
+    );
+    assert!(tree.is_none());
+    assert!(start_time.elapsed().as_micros() < 2000);
+
+    // Continue parsing with both a deadline and a progress callback. The
+    // callback halts the parse first.
+    let mut offsets = Vec::new();
+    let tree = parser.parse_with_options(
+        &mut |offset, _| {
+            if offset == 0 { b" [" } else { b",0" }
+        },
+        None,
+        Some(
+            ParseOptions::new()
+                .deadline(time::Instant::now() + Duration::from_secs(60))
+                .progress_callback(&mut |state| {
+                    offsets.push(state.current_byte_offset());
+                    if offsets.len() > 10 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                }),
+        ),
+    );
+    assert!(tree.is_none());
+    assert_eq!(offsets.len(), 11);
+
+    // Continue parsing with a deadline that has already passed. The progress
+    // callback is not called.
+    let tree = parser.parse_with_options(
+        &mut |offset, _| {
+            if offset == 0 { b" [" } else { b",0" }
+        },
+        None,
+        Some(
+            ParseOptions::new()
+                .deadline(time::Instant::now())
+                .progress_callback(&mut |_| panic!("unexpected progress callback")),
+        ),
+    );
+    assert!(tree.is_none());
+
+    // Finish parsing, continuing from where the parse was halted.
+    let tree = parser
+        .parse_with_options(
+            &mut |offset, _| match offset {
+                5001.. => "".as_bytes(),
+                5000 => "]".as_bytes(),
+                _ => ",0".as_bytes(),
+            },
+            None,
+            Some(ParseOptions::new().deadline(time::Instant::now() + Duration::from_secs(60))),
+        )
+        .unwrap();
+    assert_eq!(tree.root_node().child(0).unwrap().kind(), "array");
+}
+
+#[test]
+fn test_parsing_with_a_deadline_during_balancing() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let function_count: u32 = 100;
+    let code = "function() {}\n".repeat(function_count as usize);
+    let mut read = |offset: usize, _: Point| code.as_bytes().get(offset..).unwrap_or_default();
+
+    // As in `test_parsing_with_timeout_during_balancing`, the balancing phase is
+    // detected by seeing the same byte offset twice. Once it starts, wait until
+    // the deadline has passed, so that it is the deadline that halts the parse.
+    let deadline = time::Instant::now() + Duration::from_millis(500);
+    let mut current_byte_offset = 0;
+    let mut in_balancing = false;
+    let tree = parser.parse_with_options(
+        &mut read,
+        None,
+        Some(
+            ParseOptions::new()
+                .deadline(deadline)
+                .progress_callback(&mut |state| {
+                    if state.current_byte_offset() == current_byte_offset {
+                        in_balancing = true;
+                        thread::sleep(deadline.saturating_duration_since(time::Instant::now()));
+                    }
+                    current_byte_offset = state.current_byte_offset();
+                    ControlFlow::Continue(())
+                }),
+        ),
+    );
+    assert!(tree.is_none());
+    assert!(in_balancing);
+
+    // Resuming without a reset finishes the balancing phase.
+    let tree = parser
+        .parse_with_options(
+            &mut read,
+            None,
+            Some(
+                ParseOptions::new()
+                    .deadline(time::Instant::now() + Duration::from_secs(60))
+                    .progress_callback(&mut |state| {
+                        assert_eq!(state.current_byte_offset(), current_byte_offset);
+                        ControlFlow::Continue(())
+                    }),
+            ),
+        )
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+    assert_eq!(tree.root_node().child_count(), function_count);
+}
+
 #[test]
 fn test_parsing_with_timeout_when_error_detected() {
     let mut parser = Parser::new();
@@ -1160,6 +1961,215 @@ fn test_parsing_with_timeout_when_error_detected() {
     assert!(tree.is_none());
 }
 
+#[test]
+fn test_parsing_with_max_errors() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+
+    let code = format!("[{}0]", "0 @, ".repeat(20));
+
+    let tree = parser.parse_with_options(
+        &mut |i, _| code.as_bytes().get(i..).unwrap_or_default(),
+        None,
+        Some(ParseOptions::new().max_errors(3)),
+    );
+    assert!(tree.is_none());
+    parser.reset();
+
+    // With a higher limit, the whole file is parsed.
+    let tree = parser
+        .parse_with_options(
+            &mut |i, _| code.as_bytes().get(i..).unwrap_or_default(),
+            None,
+            Some(ParseOptions::new().max_errors(1000)),
+        )
+        .unwrap();
+    assert!(tree.root_node().has_error());
+    assert_eq!(tree.root_node().end_byte(), code.len());
+
+    // The limit does not affect parses without errors.
+    let tree = parser
+        .parse_with_options(
+            &mut |i, _| b"[0, 0, 0]".get(i..).unwrap_or_default(),
+            None,
+            Some(ParseOptions::new().max_errors(1)),
+        )
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+}
+
+#[test]
+fn test_parsing_with_error_count_in_progress_callback() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+
+    let code = format!("[{}0]", "0 @, ".repeat(500));
+    let mut read = |i: usize, _: Point| code.as_bytes().get(i..).unwrap_or_default();
+
+    // Halt the parse once the parser has recovered from a few errors.
+    let mut error_counts = Vec::new();
+    let tree = parser.parse_with_options(
+        &mut read,
+        None,
+        Some(ParseOptions::new().progress_callback(&mut |state| {
+            error_counts.push(state.error_count());
+            if state.error_count() >= 5 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })),
+    );
+    assert!(tree.is_none());
+    assert!(error_counts.is_sorted());
+    assert!(*error_counts.last().unwrap() >= 5);
+
+    // When the parse is resumed, the count continues from where it was.
+    let halted_error_count = *error_counts.last().unwrap();
+    error_counts.clear();
+    let tree = parser
+        .parse_with_options(
+            &mut read,
+            None,
+            Some(ParseOptions::new().progress_callback(&mut |state| {
+                error_counts.push(state.error_count());
+                ControlFlow::Continue(())
+            })),
+        )
+        .unwrap();
+    assert!(tree.root_node().has_error());
+    assert!(error_counts.is_sorted());
+    assert!(error_counts[0] >= halted_error_count);
+    assert!(*error_counts.last().unwrap() > halted_error_count);
+
+    // A new parse of valid input starts from zero.
+    let code = format!("[{}0]", "0, ".repeat(500));
+    let mut read = |i: usize, _: Point| code.as_bytes().get(i..).unwrap_or_default();
+    error_counts.clear();
+    parser
+        .parse_with_options(
+            &mut read,
+            None,
+            Some(ParseOptions::new().progress_callback(&mut |state| {
+                error_counts.push(state.error_count());
+                ControlFlow::Continue(())
+            })),
+        )
+        .unwrap();
+    assert!(!error_counts.is_empty());
+    assert!(error_counts.iter().all(|count| *count == 0));
+}
+
+#[test]
+fn test_parsing_with_bytes_read_in_progress_callback() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+
+    let mut code = format!("[{}0]", "1, ".repeat(1000)).into_bytes();
+    let bytes_read = AtomicUsize::new(0);
+
+    // Read the input in small chunks, and check that the parser reports exactly
+    // the number of bytes that the callback has returned so far.
+    let mut parse = |code: &[u8], old_tree: Option<&Tree>| {
+        bytes_read.store(0, Ordering::SeqCst);
+        let mut reported = Vec::new();
+        let tree = parser
+            .parse_with_options(
+                &mut |i: usize, _: Point| {
+                    let chunk = code.get(i..(i + 16).min(code.len())).unwrap_or_default();
+                    bytes_read.fetch_add(chunk.len(), Ordering::SeqCst);
+                    chunk
+                },
+                old_tree,
+                Some(ParseOptions::new().progress_callback(&mut |state| {
+                    assert_eq!(state.bytes_read(), bytes_read.load(Ordering::SeqCst));
+                    reported.push(state.bytes_read());
+                    ControlFlow::Continue(())
+                })),
+            )
+            .unwrap();
+        (tree, reported)
+    };
+
+    let (mut tree, reported) = parse(&code, None);
+    assert!(!tree.root_node().has_error());
+    assert!(!reported.is_empty());
+    assert!(reported.is_sorted());
+    assert!(bytes_read.load(Ordering::SeqCst) >= code.len());
+
+    // A reparse after a small edit starts counting from zero, and only counts
+    // the text around the edit that had to be read again.
+    let edit = Edit {
+        position: code.len() - 2,
+        deleted_length: 1,
+        inserted_text: b"2".to_vec(),
+    };
+    perform_edit(&mut tree, &mut code, &edit).unwrap();
+    let (tree, reported) = parse(&code, Some(&tree));
+    assert!(!tree.root_node().has_error());
+    assert!(reported.is_sorted());
+    assert!(bytes_read.load(Ordering::SeqCst) < code.len() / 4);
+}
+
+#[test]
+fn test_parsing_without_error_recovery() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let parse = |parser: &mut Parser, code: &str, error_recovery: bool| {
+        parser
+            .parse_with_options(
+                &mut |i, _| code.as_bytes().get(i..).unwrap_or_default(),
+                None,
+                Some(ParseOptions::new().error_recovery(error_recovery)),
+            )
+            .unwrap()
+    };
+
+    // By default, the parser recovers from the error, and parses the following
+    // statements normally.
+    let code = "const a = 1;\nconst b = ;\nconst c = 3;\nconst d = 4;\n";
+    let tree = parse(&mut parser, code, true);
+    let root = tree.root_node();
+    assert_eq!(root.kind(), "program");
+    assert!(root.has_error());
+    assert_eq!(
+        root.named_child(root.named_child_count() as u32 - 1)
+            .unwrap()
+            .to_sexp(),
+        "(lexical_declaration (variable_declarator name: (identifier) value: (number)))"
+    );
+
+    // Without error recovery, everything after the error ends up in a single
+    // ERROR node at the root of the tree.
+    let tree = parse(&mut parser, code, false);
+    let root = tree.root_node();
+    assert_eq!(root.kind(), "ERROR");
+    assert_eq!(
+        root.named_child(0).unwrap().to_sexp(),
+        "(lexical_declaration (variable_declarator name: (identifier) value: (number)))"
+    );
+    let mut cursor = root.walk();
+    assert!(
+        root.children(&mut cursor)
+            .skip(1)
+            .all(|child| child.kind() != "lexical_declaration")
+    );
+
+    // No missing nodes are inserted either.
+    let code = "let x = (1 + 2;\nlet y = 3;\n";
+    let tree = parse(&mut parser, code, false);
+    assert_eq!(tree.root_node().kind(), "ERROR");
+    assert!(!tree.root_node().to_sexp().contains("MISSING"));
+
+    // Valid input is parsed the same way in both modes.
+    let code = "function f(x) { return x * 2; }\nf(1);\n";
+    assert_eq!(
+        parse(&mut parser, code, false).root_node().to_sexp(),
+        parse(&mut parser, code, true).root_node().to_sexp()
+    );
+}
+
 // Included Ranges
 
 #[test]
@@ -1197,10 +2207,88 @@ fn test_parsing_with_one_included_range() {
         )
     );
     assert_eq!(
-        js_tree.root_node().start_position(),
-        Point::new(0, source_code.find("console").unwrap())
+        js_tree.root_node().start_position(),
+        Point::new(0, source_code.find("console").unwrap())
+    );
+    assert_eq!(js_tree.included_ranges(), &[script_content_node.range()]);
+}
+
+#[test]
+fn test_configuring_language_and_included_ranges() {
+    let source_code = "<span>hi</span><script>console.log('sup');</script>";
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("html")).unwrap();
+    let html_tree = parser.parse(source_code, None).unwrap();
+    let script_content_node = html_tree.root_node().child(1).unwrap().child(1).unwrap();
+    assert_eq!(script_content_node.kind(), "raw_text");
+
+    let js = get_language("javascript");
+    parser
+        .configure(&js, &[script_content_node.range()])
+        .unwrap();
+    assert_eq!(parser.language().unwrap().name(), js.name());
+    assert_eq!(parser.included_ranges(), &[script_content_node.range()]);
+
+    let js_tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(
+        js_tree.root_node().to_sexp(),
+        concat!(
+            "(program (expression_statement (call_expression ",
+            "function: (member_expression object: (identifier) property: (property_identifier)) ",
+            "arguments: (arguments (string (string_fragment))))))",
+        )
+    );
+    assert_eq!(js_tree.included_ranges(), &[script_content_node.range()]);
+
+    // Invalid ranges leave both the language and the ranges unchanged.
+    let range = script_content_node.range();
+    let error = parser
+        .configure(
+            &get_language("html"),
+            &[range, html_tree.root_node().range()],
+        )
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ParserError::IncludedRanges(IncludedRangesError {
+            index: 1,
+            kind: RangeErrorKind::NotOrdered,
+        })
+    );
+    assert_eq!(parser.language().unwrap().name(), js.name());
+    assert_eq!(parser.included_ranges(), &[script_content_node.range()]);
+}
+
+#[test]
+fn test_parsing_with_unused_included_ranges() {
+    let source_code = "x;  y;";
+    let range = |start_byte: usize, end_byte: usize| Range {
+        start_byte,
+        end_byte,
+        start_point: Point::new(0, start_byte),
+        end_point: Point::new(0, end_byte),
+    };
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let ranges = [range(0, 2), range(3, 3), range(4, 6), range(100, 110)];
+    parser.set_included_ranges(&ranges).unwrap();
+    let tree = parser.parse(source_code, None).unwrap();
+
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (expression_statement (identifier)) (expression_statement (identifier)))"
     );
-    assert_eq!(js_tree.included_ranges(), &[script_content_node.range()]);
+    assert_eq!(tree.included_ranges(), &ranges);
+
+    // The empty range and the range past the end of the input contributed
+    // nothing.
+    assert_eq!(tree.used_range_count(), 2);
+
+    parser.set_included_ranges(&[range(2, 4)]).unwrap();
+    let tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(tree.used_range_count(), 0);
 }
 
 #[test]
@@ -1361,7 +2449,42 @@ fn test_parsing_error_in_invalid_included_ranges() {
             },
         ])
         .unwrap_err();
-    assert_eq!(error, IncludedRangesError(1));
+    assert_eq!(
+        error,
+        IncludedRangesError {
+            index: 1,
+            kind: RangeErrorKind::NotOrdered
+        }
+    );
+
+    // Range starts before the previous range ends
+    let error = parser
+        .set_included_ranges(&[
+            Range {
+                start_byte: 0,
+                end_byte: 10,
+                start_point: Point::new(0, 0),
+                end_point: Point::new(0, 10),
+            },
+            Range {
+                start_byte: 5,
+                end_byte: 15,
+                start_point: Point::new(0, 5),
+                end_point: Point::new(0, 15),
+            },
+        ])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        IncludedRangesError {
+            index: 1,
+            kind: RangeErrorKind::Overlapping
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Incorrect range at index 1: the range overlaps the previous range"
+    );
 
     // Range ends before it starts
     let error = parser
@@ -1372,7 +2495,197 @@ fn test_parsing_error_in_invalid_included_ranges() {
             end_point: Point::new(0, 5),
         }])
         .unwrap_err();
-    assert_eq!(error, IncludedRangesError(0));
+    assert_eq!(
+        error,
+        IncludedRangesError {
+            index: 0,
+            kind: RangeErrorKind::EndBeforeStart
+        }
+    );
+}
+
+#[test]
+fn test_parser_clear() {
+    let mut parser = Parser::new();
+    let default_parser = Parser::new();
+    let default_ranges = default_parser.included_ranges();
+
+    let debug_graph_file = tempfile::tempfile().unwrap();
+    parser.set_language(&get_language("json")).unwrap();
+    parser.set_included_ranges(&[simple_range(1, 20)]).unwrap();
+    parser.set_logger(Some(Box::new(|_, _| {})));
+    parser.print_dot_graphs(&debug_graph_file);
+    parser.set_use_external_scanner(false);
+
+    // Leave the parser in the middle of a parse.
+    let tree = parser.parse_with_options(
+        &mut |offset, _| {
+            if offset == 0 { b" [" } else { b",0" }
+        },
+        None,
+        Some(
+            ParseOptions::new()
+                .progress_every_n_steps(1)
+                .progress_callback(&mut |state| {
+                    if state.step_count() < 4 {
+                        ControlFlow::Continue(())
+                    } else {
+                        ControlFlow::Break(())
+                    }
+                }),
+        ),
+    );
+    assert!(tree.is_none());
+    assert!(parser.is_resumable());
+
+    parser.clear();
+    assert!(parser.language().is_none());
+    assert_eq!(parser.included_ranges(), default_ranges);
+    assert!(parser.logger().is_none());
+    assert!(parser.use_external_scanner());
+    assert!(!parser.is_resumable());
+
+    // The parser no longer writes debugging graphs.
+    let graph_len = debug_graph_file.metadata().unwrap().len();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("a = [1];", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (expression_statement (assignment_expression left: (identifier) right: (array (number)))))"
+    );
+    assert_eq!(debug_graph_file.metadata().unwrap().len(), graph_len);
+}
+
+#[test]
+fn test_parsing_with_unsorted_included_ranges() {
+    let source_code = "<span>a</span><%= foo() %><span>b</span><%= bar() %><%= baz() %>";
+    let range_for = |text: &str| {
+        let start = source_code.find(text).unwrap();
+        simple_range(start, start + text.len())
+    };
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    // The ranges are sorted, and the ranges that touch are merged. The text
+    // between `bar()` and `baz()` touches both of them.
+    let foo = range_for(" foo() ");
+    let bar = range_for(" bar() ");
+    let bar_end = range_for("%><%=");
+    let baz = range_for(" baz() ");
+    parser
+        .set_included_ranges_sorted(&[baz, foo, bar, bar_end])
+        .unwrap();
+    assert_eq!(
+        parser.included_ranges(),
+        [foo, simple_range(bar.start_byte, baz.end_byte)]
+    );
+
+    // The order of the ranges doesn't change the result.
+    let tree = parser.parse(source_code, None).unwrap();
+    parser
+        .set_included_ranges_sorted(&[bar_end, bar, foo, baz])
+        .unwrap();
+    let tree2 = parser.parse(source_code, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), tree2.root_node().to_sexp());
+    assert_eq!(tree.root_node().start_byte(), foo.start_byte + 1);
+
+    // Overlapping ranges are rejected, with the index of the range in the
+    // given slice.
+    assert_eq!(
+        parser.set_included_ranges_sorted(&[simple_range(20, 30), simple_range(0, 25)]),
+        Err(IncludedRangesError {
+            index: 0,
+            kind: RangeErrorKind::Overlapping
+        })
+    );
+    assert_eq!(
+        parser.set_included_ranges_sorted(&[simple_range(30, 20)]),
+        Err(IncludedRangesError {
+            index: 0,
+            kind: RangeErrorKind::EndBeforeStart
+        })
+    );
+
+    // The strict method still rejects unsorted ranges.
+    assert_eq!(
+        parser.set_included_ranges(&[baz, foo]),
+        Err(IncludedRangesError {
+            index: 1,
+            kind: RangeErrorKind::NotOrdered
+        })
+    );
+}
+
+#[test]
+fn test_validating_included_ranges() {
+    let range = |start_byte, end_byte| Range {
+        start_byte,
+        end_byte,
+        start_point: Point::new(0, start_byte),
+        end_point: Point::new(0, end_byte),
+    };
+
+    assert_eq!(validate_included_ranges(&[]), Ok(()));
+    assert_eq!(
+        validate_included_ranges(&[range(0, 5), range(5, 10), range(20, 30)]),
+        Ok(())
+    );
+    assert_eq!(
+        validate_included_ranges(&[range(0, 5), range(10, 8)]),
+        Err(IncludedRangesError {
+            index: 1,
+            kind: RangeErrorKind::EndBeforeStart
+        })
+    );
+    assert_eq!(
+        validate_included_ranges(&[range(10, 20), range(0, 5)]),
+        Err(IncludedRangesError {
+            index: 1,
+            kind: RangeErrorKind::NotOrdered
+        })
+    );
+    assert_eq!(
+        validate_included_ranges(&[range(0, 5), range(10, 20), range(15, 25)]),
+        Err(IncludedRangesError {
+            index: 2,
+            kind: RangeErrorKind::Overlapping
+        })
+    );
+
+    // Validation agrees with the parser.
+    let mut parser = Parser::new();
+    let ranges = [range(0, 5), range(3, 8)];
+    assert_eq!(
+        parser.set_included_ranges(&ranges),
+        validate_included_ranges(&ranges)
+    );
+    let ranges = [range(0, 5), range(6, 8)];
+    assert_eq!(
+        parser.set_included_ranges(&ranges),
+        validate_included_ranges(&ranges)
+    );
+}
+
+#[test]
+fn test_sorting_included_ranges() {
+    let range = |start_byte, end_byte| Range {
+        start_byte,
+        end_byte,
+        start_point: Point::new(0, start_byte),
+        end_point: Point::new(0, end_byte),
+    };
+
+    let mut ranges = vec![range(20, 30), range(5, 5), range(0, 5), range(10, 15)];
+    assert!(validate_included_ranges(&ranges).is_err());
+    ranges.sort();
+    assert_eq!(
+        ranges,
+        &[range(0, 5), range(5, 5), range(10, 15), range(20, 30)]
+    );
+    assert_eq!(validate_included_ranges(&ranges), Ok(()));
+    assert!(range(0, 5) < range(0, 6));
+    assert!(range(0, 6) < range(1, 2));
 }
 
 #[test]
@@ -1447,6 +2760,63 @@ fn test_parsing_with_external_scanner_that_uses_included_range_boundaries() {
     assert_eq!(statement2.end_byte(), source_code.len() - " %>".len());
 }
 
+#[test]
+fn test_parsing_with_current_range_index_in_progress_callback() {
+    let statements = "x;".repeat(300);
+    let source_code = format!("<%{statements}%> a <%{statements}%> b <%{statements}%>");
+    let ranges = source_code
+        .match_indices(&statements)
+        .map(|(start, text)| simple_range(start, start + text.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(ranges.len(), 3);
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    parser.set_included_ranges(&ranges).unwrap();
+
+    let mut indices = Vec::new();
+    let tree = parser
+        .parse_with_options(
+            &mut |i: usize, _: Point| source_code.as_bytes().get(i..).unwrap_or_default(),
+            None,
+            Some(ParseOptions::new().progress_callback(&mut |state| {
+                let index = state.current_range_index();
+                if let Some(index) = index {
+                    let range = ranges[index];
+                    assert!(state.current_byte_offset() <= range.end_byte);
+                }
+                indices.push(index);
+                ControlFlow::Continue(())
+            })),
+        )
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+
+    // The index advances through each of the ranges in order, and only becomes
+    // `None` at the end of the last one.
+    indices.dedup();
+    if indices.last() == Some(&None) {
+        indices.pop();
+    }
+    assert_eq!(indices, [Some(0), Some(1), Some(2)]);
+
+    // Without included ranges, the whole document is the first range.
+    parser.set_included_ranges(&[]).unwrap();
+    indices.clear();
+    parser
+        .parse_with_options(
+            &mut |i: usize, _: Point| statements.as_bytes().get(i..).unwrap_or_default(),
+            None,
+            Some(ParseOptions::new().progress_callback(&mut |state| {
+                indices.push(state.current_range_index());
+                ControlFlow::Continue(())
+            })),
+        )
+        .unwrap();
+    indices.dedup();
+    assert_eq!(indices.first(), Some(&Some(0)));
+}
+
 #[test]
 fn test_parsing_with_a_newly_excluded_range() {
     let mut source_code = String::from("<div><span><%= something %></span></div>");
@@ -1665,6 +3035,136 @@ fn test_parsing_with_included_ranges_and_missing_tokens() {
     assert_eq!(root.child(3).unwrap().start_byte(), 4);
 }
 
+#[test]
+fn test_rendering_trees_with_missing_nodes() {
+    let (parser_name, parser_code) = generate_parser(
+        r#"{
+            "name": "test_rendering_missing_nodes",
+            "rules": {
+                "program": {
+                    "type": "CHOICE",
+                    "members": [
+                        {
+                            "type": "SEQ",
+                            "members": [
+                                {"type": "STRING", "value": "("},
+                                {"type": "SYMBOL", "name": "x"},
+                                {"type": "STRING", "value": ")"}
+                            ]
+                        },
+                        {
+                            "type": "SEQ",
+                            "members": [
+                                {"type": "STRING", "value": "["},
+                                {"type": "STRING", "value": "]"},
+                                {"type": "SYMBOL", "name": "x"}
+                            ]
+                        }
+                    ]
+                },
+                "x": {"type": "STRING", "value": "x"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+    let render = |tree: &Tree, no_ranges: bool| {
+        let mut output = Vec::new();
+        render_sexp(&mut tree.walk(), no_ranges, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    };
+
+    // A missing anonymous token is rendered, unlike the anonymous tokens that
+    // are present in the source.
+    let tree = parser.parse("(x", None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), "(program (x) (MISSING \")\"))");
+    assert_eq!(
+        render(&tree, false),
+        [
+            "(program [0, 0] - [0, 2]",
+            "  (x [0, 1] - [0, 2])",
+            "  (MISSING \")\" [0, 2] - [0, 2]))",
+        ]
+        .join("\n")
+    );
+
+    // A missing named node is marked as missing.
+    let tree = parser.parse("[]", None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), "(program (MISSING x))");
+    assert_eq!(render(&tree, true), "(program\n  (MISSING x))");
+}
+
+#[test]
+fn test_parsing_with_lf_positions() {
+    let (parser_name, parser_code) = generate_parser(
+        r#"
+        {
+            "name": "test_lf_positions",
+            "rules": {
+                "source_file": {
+                    "type": "REPEAT",
+                    "content": { "type": "SYMBOL", "name": "line" }
+                },
+                "line": { "type": "PATTERN", "value": "[a-z\\r]+" }
+            },
+            "extras": [ { "type": "PATTERN", "value": "[ \\n]" } ]
+        }
+        "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let code = "ab\r\ncd\r\n e\rf\r\n";
+    let line_ranges = |parser: &mut Parser, report_lf_positions: bool| {
+        let tree = parser
+            .parse_with_options(
+                &mut |i, _| code.as_bytes().get(i..).unwrap_or_default(),
+                None,
+                Some(ParseOptions::new().report_lf_positions(report_lf_positions)),
+            )
+            .unwrap();
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        root.named_children(&mut cursor)
+            .map(|line| {
+                (
+                    line.byte_range(),
+                    line.start_position(),
+                    line.end_position(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // By default, the `\r` of each line ending takes up a column.
+    assert_eq!(
+        line_ranges(&mut parser, false),
+        [
+            (0..3, Point::new(0, 0), Point::new(0, 3)),
+            (4..7, Point::new(1, 0), Point::new(1, 3)),
+            (9..13, Point::new(2, 1), Point::new(2, 5)),
+        ]
+    );
+
+    // With the option, only the `\r` that is not part of a line ending does.
+    assert_eq!(
+        line_ranges(&mut parser, true),
+        [
+            (0..3, Point::new(0, 0), Point::new(0, 2)),
+            (4..7, Point::new(1, 0), Point::new(1, 2)),
+            (9..13, Point::new(2, 1), Point::new(2, 4)),
+        ]
+    );
+}
+
 #[test]
 fn test_grammars_that_can_hang_on_eof() {
     let (parser_name, parser_code) = generate_parser(
@@ -1797,6 +3297,37 @@ fn test_parsing_with_scanner_logging() {
     assert!(found);
 }
 
+#[test]
+fn test_parsing_without_external_scanner() {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&get_test_fixture_language("external_tokens"))
+        .unwrap();
+    assert!(parser.use_external_scanner());
+
+    let mut scanner_was_called = false;
+    parser.set_logger(Some(Box::new(|log_type, message| {
+        if log_type == LogType::Lex && message == "Found a percent string" {
+            scanner_was_called = true;
+        }
+    })));
+
+    let source_code = "x + %(sup (external) scanner?)";
+
+    // Without the external scanner, the percent string can't be lexed.
+    parser.set_use_external_scanner(false);
+    assert!(!parser.use_external_scanner());
+    let tree = parser.parse(source_code, None).unwrap();
+    assert!(tree.root_node().has_error());
+
+    parser.set_use_external_scanner(true);
+    let tree = parser.parse(source_code, None).unwrap();
+    assert!(!tree.root_node().has_error());
+
+    parser.set_logger(None);
+    assert!(scanner_was_called);
+}
+
 #[test]
 fn test_parsing_get_column_at_eof() {
     let mut parser = Parser::new();
@@ -1928,6 +3459,44 @@ fn test_decode_cp1252() {
         tree.root_node().to_sexp(),
         "(source_file (function_item (visibility_modifier) name: (identifier) parameters: (parameters) body: (block (expression_statement (macro_invocation macro: (identifier) (token_tree (string_literal (string_content))))))))"
     );
+    assert_eq!(parser.decode_stop(), DecodeStop::Eof);
+}
+
+#[test]
+fn test_decode_stall() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let text = "fn foo() { let x = 1 ^ 2; }";
+
+    // A buggy decoder that fails to consume the `^` character.
+    struct StallingDecoder;
+
+    impl Decode for StallingDecoder {
+        fn decode(bytes: &[u8]) -> (i32, u32) {
+            match bytes.first() {
+                Some(b'^') => (i32::from(b'^'), 0),
+                Some(byte) => (i32::from(*byte), 1),
+                None => (0, 0),
+            }
+        }
+    }
+
+    let tree = parser
+        .parse_custom_encoding::<StallingDecoder, _, _>(
+            &mut |offset, _| &text.as_bytes()[offset..],
+            None,
+            None,
+        )
+        .unwrap();
+
+    let stall_offset = text.find('^').unwrap();
+    assert_eq!(parser.decode_stop(), DecodeStop::Stall(stall_offset));
+    assert!(tree.root_node().end_byte() <= stall_offset);
+
+    parser.reset();
+    parser.parse("fn foo() {}", None).unwrap();
+    assert_eq!(parser.decode_stop(), DecodeStop::Eof);
 }
 
 #[test]
@@ -2235,3 +3804,39 @@ fn test_grammar_that_should_hang_and_not_segfault() {
         }
     }
 }
+
+#[test]
+fn test_parsing_with_incremental_verification_aligns_the_diff() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("aa;bb;", None).unwrap();
+
+    // The stale tree makes the middle statement differ, but the statements
+    // before and after it are still reported as unchanged.
+    let code = b"aa;\n1(2);bb;";
+    let error = parser
+        .parse_with_verification(&mut |i, _| &code[i.min(code.len())..], &tree, None)
+        .unwrap_err();
+    let IncrementalParseError::Mismatch { diff, .. } = error else {
+        panic!("Expected a mismatch, got {error:?}");
+    };
+    assert_eq!(
+        diff,
+        indoc! {"
+              (program
+                (expression_statement
+                  (identifier))
+                (expression_statement
+            -     (identifier))
+            -   (expression_statement
+            -     (number)
+            -     (ERROR))
+            +     (call_expression
+            +       function: (number)
+            +       arguments: (arguments
+            +         (number))))
+                (expression_statement
+                  (identifier)))
+        "}
+    );
+}
@@ -4,7 +4,7 @@ use indoc::indoc;
 use rand::{SeedableRng, prelude::StdRng};
 use streaming_iterator::StreamingIterator;
 use tree_sitter::{
-    CaptureQuantifier, InputEdit, Language, Node, Parser, Point, Query, QueryCursor,
+    CaptureQuantifier, InputEdit, Language, Node, Parser, Point, Query, QueryCost, QueryCursor,
     QueryCursorOptions, QueryError, QueryErrorKind, QueryPredicate, QueryPredicateArg,
     QueryProperty, Range,
 };
@@ -697,6 +697,41 @@ fn test_query_matches_with_simple_pattern() {
     });
 }
 
+#[test]
+fn test_query_matches_with_callback() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(&language, "(identifier) @id").unwrap();
+        let source = "a; b; c; d;";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let mut names = Vec::new();
+        let result = cursor.for_each_match(&query, tree.root_node(), source.as_bytes(), |m| {
+            names.push(m.captures[0].node.utf8_text(source.as_bytes()).unwrap());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(result, ControlFlow::Continue(()));
+        assert_eq!(names, ["a", "b", "c", "d"]);
+
+        // Breaking out of the callback stops the search.
+        names.clear();
+        let result = cursor.for_each_match(&query, tree.root_node(), source.as_bytes(), |m| {
+            let name = m.captures[0].node.utf8_text(source.as_bytes()).unwrap();
+            if name == "c" {
+                return ControlFlow::Break(());
+            }
+            names.push(name);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(result, ControlFlow::Break(()));
+        assert_eq!(names, ["a", "b"]);
+    });
+}
+
 #[test]
 fn test_query_matches_with_multiple_on_same_root() {
     allocations::record(|| {
@@ -808,6 +843,60 @@ fn test_query_matches_with_multiple_patterns_same_root() {
     });
 }
 
+#[test]
+fn test_query_matches_for_pattern() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            "
+                (function_declaration name:(identifier) @fn-def)
+                (call_expression function:(identifier) @fn-ref)
+                (_ (identifier) @id)
+            ",
+        )
+        .unwrap();
+        let source = "
+            function f1() {
+                f2(f3());
+            }
+        ";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let matches = cursor.matches_for_pattern(&query, 1, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(1, vec![("fn-ref", "f2")]), (1, vec![("fn-ref", "f3")])],
+        );
+
+        let matches = cursor.matches_for_pattern(&query, 0, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(0, vec![("fn-def", "f1")])],
+        );
+
+        // Running the whole query on the same cursor afterwards attempts every pattern again.
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let mut all_matches = collect_matches(matches, &query, source);
+        all_matches.sort();
+        assert_eq!(
+            all_matches,
+            &[
+                (0, vec![("fn-def", "f1")]),
+                (1, vec![("fn-ref", "f2")]),
+                (1, vec![("fn-ref", "f3")]),
+                (2, vec![("id", "f1")]),
+                (2, vec![("id", "f2")]),
+                (2, vec![("id", "f3")]),
+            ],
+        );
+    });
+}
+
 #[test]
 fn test_query_matches_with_nesting_and_no_fields() {
     allocations::record(|| {
@@ -3701,6 +3790,87 @@ fn test_query_captures_basic() {
     });
 }
 
+#[test]
+fn test_query_negated_text_predicates() {
+    struct Row {
+        description: &'static str,
+        pattern: &'static str,
+        source: &'static str,
+        matches: &'static [&'static [(&'static str, &'static str)]],
+    }
+
+    #[rustfmt::skip]
+    let rows = &[
+        Row {
+            description: "not-eq? with a string",
+            pattern: r#"((identifier) @id (#not-eq? @id "bar"))"#,
+            source: "foo; bar; baz;",
+            matches: &[&[("id", "foo")], &[("id", "baz")]],
+        },
+        Row {
+            description: "not-eq? with a capture",
+            pattern: r"
+                ((assignment_expression
+                    left: (identifier) @left
+                    right: (identifier) @right)
+                 (#not-eq? @left @right))
+            ",
+            source: "a = a; b = c;",
+            matches: &[&[("left", "b"), ("right", "c")]],
+        },
+        Row {
+            description: "not-match?",
+            pattern: r#"((identifier) @id (#not-match? @id "^ba"))"#,
+            source: "foo; bar; baz;",
+            matches: &[&[("id", "foo")]],
+        },
+        Row {
+            description: "not-any-of?",
+            pattern: r#"((identifier) @id (#not-any-of? @id "foo" "baz"))"#,
+            source: "foo; bar; baz;",
+            matches: &[&[("id", "bar")]],
+        },
+        Row {
+            description: "any-not-eq?",
+            pattern: r#"((identifier) @id (#any-not-eq? @id "bar"))"#,
+            source: "foo; bar; baz;",
+            matches: &[&[("id", "foo")], &[("id", "baz")]],
+        },
+        Row {
+            description: "any-not-match?",
+            pattern: r#"((identifier) @id (#any-not-match? @id "^ba"))"#,
+            source: "foo; bar; baz;",
+            matches: &[&[("id", "foo")]],
+        },
+        Row {
+            description: "multiple negated predicates must all pass",
+            pattern: r#"((identifier) @id (#not-match? @id "^f") (#not-eq? @id "baz"))"#,
+            source: "foo; bar; baz;",
+            matches: &[&[("id", "bar")]],
+        },
+        Row {
+            description: "negated and positive predicates must all pass",
+            pattern: r#"((identifier) @id (#match? @id "^ba") (#not-any-of? @id "bar"))"#,
+            source: "foo; bar; baz;",
+            matches: &[&[("id", "baz")]],
+        },
+    ];
+
+    allocations::record(|| {
+        let language = get_language("javascript");
+        for row in rows {
+            eprintln!("  negated predicate example: {:?}", row.description);
+            let query = Query::new(&language, row.pattern).unwrap();
+            let expected = row
+                .matches
+                .iter()
+                .map(|captures| (0, captures.to_vec()))
+                .collect::<Vec<_>>();
+            assert_query_matches(&language, &query, row.source, &expected);
+        }
+    });
+}
+
 #[test]
 fn test_query_captures_with_text_conditions() {
     allocations::record(|| {
@@ -4436,6 +4606,54 @@ fn test_query_text_callback_returns_chunks() {
     });
 }
 
+#[test]
+fn test_query_text_callback_is_only_called_for_text_predicates() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let source = "let a = b; let c = d;";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        // Without any text predicates, the text of the nodes is never requested.
+        let query = Query::new(&language, "(identifier) @id").unwrap();
+        let mut requested_ranges = Vec::new();
+        {
+            let mut matches = cursor.matches(&query, tree.root_node(), |node: Node| {
+                requested_ranges.push(node.byte_range());
+                std::iter::once(&source.as_bytes()[node.byte_range()])
+            });
+            let mut match_count = 0;
+            while matches.next().is_some() {
+                match_count += 1;
+            }
+            assert_eq!(match_count, 4);
+        }
+        assert!(requested_ranges.is_empty());
+
+        // With a text predicate, only the captured nodes' text is requested.
+        let query = Query::new(
+            &language,
+            r#"((variable_declarator name: (identifier) @name) (#eq? @name "c"))"#,
+        )
+        .unwrap();
+        let mut requested_ranges = Vec::new();
+        {
+            let matches = cursor.matches(&query, tree.root_node(), |node: Node| {
+                requested_ranges.push(node.byte_range());
+                std::iter::once(&source.as_bytes()[node.byte_range()])
+            });
+            assert_eq!(
+                collect_matches(matches, &query, source),
+                &[(0, vec![("name", "c")])]
+            );
+        }
+        assert_eq!(requested_ranges, [4..5, 15..16]);
+    });
+}
+
 #[test]
 fn test_query_start_end_byte_for_pattern() {
     let language = get_language("javascript");
@@ -4692,6 +4910,68 @@ fn test_query_deep_clone() {
     });
 }
 
+#[test]
+fn test_query_serialization() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            r#"
+                (function_declaration
+                    name: (identifier) @name
+                    (#match? @name "^f"))
+                (function_declaration
+                    body: (statement_block) @body)
+            "#,
+        )
+        .unwrap();
+
+        let bytes = query.to_bytes();
+        let loaded = Query::from_bytes(&language, &bytes).unwrap();
+        assert_eq!(loaded.pattern_count(), query.pattern_count());
+        assert_eq!(loaded.capture_names(), query.capture_names());
+        assert_eq!(loaded.to_bytes(), bytes);
+
+        let source = "function foo() { return 1; } function bar() {}";
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+        let mut loaded_cursor = QueryCursor::new();
+
+        let expected = collect_matches(
+            cursor.matches(&query, tree.root_node(), source.as_bytes()),
+            &query,
+            source,
+        );
+        let actual = collect_matches(
+            loaded_cursor.matches(&loaded, tree.root_node(), source.as_bytes()),
+            &loaded,
+            source,
+        );
+        assert_eq!(actual, expected);
+
+        // Loading with a different language fails.
+        let error = Query::from_bytes(&get_language("rust"), &bytes).unwrap_err();
+        assert_eq!(error.kind, QueryErrorKind::Language);
+
+        // Loading truncated data fails.
+        let error = Query::from_bytes(&language, &bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(error.kind, QueryErrorKind::Syntax);
+
+        // Corrupted data is either rejected, or produces a query that can be run safely.
+        for i in 0..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0xff;
+            if let Ok(query) = Query::from_bytes(&language, &corrupted) {
+                let mut cursor = QueryCursor::new();
+                let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+                while matches.next().is_some() {}
+            }
+        }
+    });
+}
+
 #[test]
 fn test_query_alternative_predicate_prefix() {
     allocations::record(|| {
@@ -5284,6 +5564,214 @@ fn test_query_is_pattern_non_local() {
     });
 }
 
+#[test]
+fn test_query_captures_in_point_range() {
+    let language = get_language("javascript");
+    let query = Query::new(
+        &language,
+        "(function_declaration) @fn (identifier) @id (template_string) @str",
+    )
+    .unwrap();
+    let source = "const a = 1;\nfunction foo() {\n  return `x\ny`;\n}\n";
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut cursor = QueryCursor::new();
+    let captures = cursor.captures_in_point_range(
+        &query,
+        tree.root_node(),
+        Point::new(1, 9),
+        Point::new(3, 1),
+        source.as_bytes(),
+    );
+    assert_eq!(
+        captures
+            .iter()
+            .map(|(capture, range)| (
+                query.capture_names()[capture.index as usize],
+                range.start_point,
+                range.end_point,
+                &source[range.start_byte..range.end_byte],
+            ))
+            .collect::<Vec<_>>(),
+        &[
+            (
+                "fn",
+                Point::new(1, 9),
+                Point::new(3, 1),
+                "foo() {\n  return `x\ny"
+            ),
+            ("id", Point::new(1, 9), Point::new(1, 12), "foo"),
+            ("str", Point::new(2, 9), Point::new(3, 1), "`x\ny"),
+        ]
+    );
+
+    // The cursor's previous point range is restored afterward.
+    let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    let first_capture = matches.next().unwrap().captures[0].node;
+    assert_eq!(first_capture.utf8_text(source.as_bytes()), Ok("a"));
+}
+
+#[test]
+fn test_query_captures_in_fragment_with_offset() {
+    allocations::record(|| {
+        let html_source = "<div>\n  <script>\n    let a = b(c);\n  </script>\n</div>";
+        let mut parser = Parser::new();
+        parser.set_language(&get_language("html")).unwrap();
+        let html_tree = parser.parse(html_source, None).unwrap();
+        let script_start = html_source.find("let").unwrap();
+        let script_node = html_tree
+            .root_node()
+            .descendant_for_byte_range(script_start, script_start)
+            .unwrap();
+        assert_eq!(script_node.kind(), "raw_text");
+
+        // Parse the script as a detached fragment, and query its root node
+        // shifted to the fragment's position within the HTML document.
+        let js_language = get_language("javascript");
+        let js_source = script_node.utf8_text(html_source.as_bytes()).unwrap();
+        parser.set_language(&js_language).unwrap();
+        let js_tree = parser.parse(js_source, None).unwrap();
+        let js_root =
+            js_tree.root_node_with_offset(script_node.start_byte(), script_node.start_position());
+
+        let query = Query::new(
+            &js_language,
+            r#"
+            (call_expression
+                function: (identifier) @function
+                arguments: (arguments (identifier) @argument)
+                (#eq? @function "b"))
+            "#,
+        )
+        .unwrap();
+        let mut cursor = QueryCursor::new();
+        let mut captures = cursor.captures(&query, js_root, html_source.as_bytes());
+        let mut results = Vec::new();
+        while let Some((m, i)) = captures.next() {
+            let capture = m.captures[*i];
+            results.push((
+                query.capture_names()[capture.index as usize],
+                capture.node.byte_range(),
+                capture.node.start_position(),
+                capture.node.utf8_text(html_source.as_bytes()).unwrap(),
+            ));
+        }
+        assert_eq!(
+            results,
+            &[
+                ("function", 29..30, Point::new(2, 12), "b"),
+                ("argument", 31..32, Point::new(2, 14), "c"),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_query_captures_with_pattern() {
+    let language = get_language("javascript");
+    let query = Query::new(
+        &language,
+        "(identifier) @variable (call_expression function: (identifier) @function)",
+    )
+    .unwrap();
+    let source = "a(b);";
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut cursor = QueryCursor::new();
+    let captures = cursor.captures_with_pattern(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        captures
+            .iter()
+            .map(|(node, capture_index, pattern_index)| (
+                node.utf8_text(source.as_bytes()).unwrap(),
+                query.capture_names()[*capture_index as usize],
+                *pattern_index,
+            ))
+            .collect::<Vec<_>>(),
+        &[
+            ("a", "variable", 0),
+            ("a", "function", 1),
+            ("b", "variable", 0)
+        ]
+    );
+}
+
+#[test]
+fn test_query_match_satisfies_predicates() {
+    let language = get_language("javascript");
+    let query = Query::new(
+        &language,
+        r#"((identifier) @id (#eq? @id "foo")) ((identifier) @id (#match? @id "^b"))"#,
+    )
+    .unwrap();
+    let source = "foo;";
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    let m = matches.next().unwrap();
+    assert_eq!(m.pattern_index, 0);
+
+    assert!(query.match_satisfies_predicates(m, source.as_bytes()));
+
+    // Re-check the held match against edited text of the same length.
+    assert!(!query.match_satisfies_predicates(m, "bar;".as_bytes()));
+    assert!(query.match_satisfies_predicates(m, "foo;".as_bytes()));
+
+    assert!(matches.next().is_none());
+}
+
+#[test]
+fn test_query_estimated_cost() {
+    let language = get_language("javascript");
+
+    let query = Query::new(&language, "(identifier) @id").unwrap();
+    assert_eq!(
+        query.estimated_cost(),
+        QueryCost {
+            pattern_count: 1,
+            max_depth: 1,
+            ..QueryCost::default()
+        }
+    );
+
+    let query = Query::new(
+        &language,
+        r#"
+        (function_declaration name: (identifier) @name)
+        ((_) @id (#match? @id "^a"))
+        ((identifier) @x (#eq? @x "b") (#not-match? @x "c") (#set! foo))
+        (call_expression function: (member_expression object: (identifier) @obj))
+        ((comment) (function_declaration))
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        query.estimated_cost(),
+        QueryCost {
+            pattern_count: 5,
+            non_rooted_pattern_count: 1,
+            wildcard_pattern_count: 1,
+            predicate_count: 4,
+            regex_count: 2,
+            max_depth: 3,
+        }
+    );
+
+    // A wildcard with a named child is only tried at nodes that match the child.
+    let query = Query::new(&language, "(_ (identifier) @id)").unwrap();
+    assert_eq!(query.estimated_cost().wildcard_pattern_count, 0);
+}
+
 #[test]
 fn test_capture_quantifiers() {
     struct Row {
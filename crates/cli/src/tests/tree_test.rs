@@ -1,6 +1,8 @@
 use std::str;
 
-use tree_sitter::{InputEdit, Parser, Point, Range, Tree};
+use tree_sitter::{
+    EditError, InputEdit, InputEditError, Node, Parser, Point, Range, Tree, apply_edit,
+};
 
 use super::helpers::fixtures::get_language;
 use crate::{
@@ -237,6 +239,87 @@ fn test_tree_edit() {
     }
 }
 
+#[test]
+fn test_tree_try_edit() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "abc;\ndef;\nghi;";
+    let tree = parser.parse(source, None).unwrap();
+
+    // Replace "def;\ng" with "x".
+    let valid_edit = InputEdit {
+        start_byte: 5,
+        old_end_byte: 11,
+        new_end_byte: 6,
+        start_position: Point::new(1, 0),
+        old_end_position: Point::new(2, 1),
+        new_end_position: Point::new(1, 1),
+    };
+    assert_eq!(valid_edit.validate(), Ok(()));
+    let mut edited_tree = tree.clone();
+    edited_tree.try_edit(&valid_edit).unwrap();
+    assert!(edited_tree.root_node().has_changes());
+
+    let invalid_edits = [
+        (
+            InputEdit {
+                old_end_byte: 4,
+                ..valid_edit
+            },
+            InputEditError::OldEndBeforeStart,
+        ),
+        (
+            InputEdit {
+                new_end_byte: 4,
+                ..valid_edit
+            },
+            InputEditError::NewEndBeforeStart,
+        ),
+        // The old end position comes before the start position.
+        (
+            InputEdit {
+                old_end_position: Point::new(0, 3),
+                ..valid_edit
+            },
+            InputEditError::InconsistentOldEndPosition,
+        ),
+        // The old end position spans more rows than bytes.
+        (
+            InputEdit {
+                old_end_position: Point::new(8, 0),
+                ..valid_edit
+            },
+            InputEditError::InconsistentOldEndPosition,
+        ),
+        // The new end position is on the start row, but its column doesn't
+        // match the number of inserted bytes.
+        (
+            InputEdit {
+                new_end_position: Point::new(1, 3),
+                ..valid_edit
+            },
+            InputEditError::InconsistentNewEndPosition,
+        ),
+        // The new end position is on a different row, but no bytes were inserted.
+        (
+            InputEdit {
+                new_end_byte: 5,
+                new_end_position: Point::new(2, 0),
+                ..valid_edit
+            },
+            InputEditError::InconsistentNewEndPosition,
+        ),
+    ];
+    for (edit, expected_error) in invalid_edits {
+        assert_eq!(edit.validate(), Err(expected_error));
+
+        // The tree is left unchanged.
+        let mut edited_tree = tree.clone();
+        assert_eq!(edited_tree.try_edit(&edit), Err(expected_error));
+        assert!(!edited_tree.root_node().has_changes());
+    }
+}
+
 #[test]
 fn test_tree_edit_with_included_ranges() {
     let mut parser = Parser::new();
@@ -302,6 +385,124 @@ fn test_tree_edit_with_included_ranges() {
     );
 }
 
+#[test]
+fn test_tree_copies_are_edited_independently() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("abc !== def", None).unwrap();
+    let original_sexp = tree.root_node().to_sexp();
+
+    let edit = InputEdit {
+        start_byte: 0,
+        old_end_byte: 0,
+        new_end_byte: 2,
+        start_position: Point::new(0, 0),
+        old_end_position: Point::new(0, 0),
+        new_end_position: Point::new(0, 2),
+    };
+
+    let mut shallow_copy = tree.clone();
+    let mut deep_copy = tree.deep_copy();
+    assert_eq!(deep_copy.root_node().to_sexp(), original_sexp);
+
+    shallow_copy.edit(&edit);
+    deep_copy.edit(&edit);
+    for copy in [&shallow_copy, &deep_copy] {
+        assert!(copy.root_node().has_changes());
+        assert_eq!(copy.root_node().end_byte(), 13);
+    }
+
+    // Neither edit is visible through the original tree.
+    assert!(!tree.root_node().has_changes());
+    assert_eq!(tree.root_node().end_byte(), 11);
+    assert_eq!(
+        tree.root_node()
+            .child(0)
+            .unwrap()
+            .child(0)
+            .unwrap()
+            .byte_range(),
+        0..11
+    );
+    assert_eq!(tree.root_node().to_sexp(), original_sexp);
+
+    // The deep copy remains valid after the original is dropped.
+    drop(tree);
+    drop(shallow_copy);
+    assert_eq!(deep_copy.root_node().to_sexp(), original_sexp);
+}
+
+#[test]
+fn test_tree_named_node_summaries() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+    let source = "struct A { b: u8 }\nfn c() { d(1); }\n";
+    let tree = parser.parse(source, None).unwrap();
+
+    fn collect_named(node: Node, result: &mut Vec<(u16, Range)>) {
+        if node.is_named() {
+            result.push((node.kind_id(), node.range()));
+        }
+        for child in node.children(&mut node.walk()) {
+            collect_named(child, result);
+        }
+    }
+    let mut expected = Vec::new();
+    collect_named(tree.root_node(), &mut expected);
+
+    let summaries = tree.named_node_summaries().collect::<Vec<_>>();
+    assert_eq!(summaries, expected);
+
+    let language = tree.language();
+    assert_eq!(
+        summaries
+            .iter()
+            .take(4)
+            .map(|(kind_id, _)| language.node_kind_for_id(*kind_id).unwrap())
+            .collect::<Vec<_>>(),
+        &[
+            "source_file",
+            "struct_item",
+            "type_identifier",
+            "field_declaration_list"
+        ]
+    );
+    assert_eq!(summaries[0].1, tree.root_node().range());
+}
+
+#[test]
+fn test_tree_edited_ranges() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let mut source = b"let a = 1;\nlet b = 2;\nlet c = 3;\n".to_vec();
+    let mut tree = parser.parse(&source, None).unwrap();
+    assert_eq!(tree.edited_ranges().count(), 0);
+
+    // Insert a digit after the `2` on the second line.
+    let edit = apply_edit(
+        &mut tree,
+        &mut source,
+        &Edit {
+            position: 20,
+            deleted_length: 0,
+            inserted_text: b"0".to_vec(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        str::from_utf8(&source).unwrap(),
+        "let a = 1;\nlet b = 20;\nlet c = 3;\n"
+    );
+
+    let ranges = tree.edited_ranges().collect::<Vec<_>>();
+    assert_eq!(ranges.len(), 1);
+    assert!(ranges[0].start_byte >= 11 && ranges[0].start_byte <= edit.start_byte);
+    assert!(ranges[0].end_byte >= edit.new_end_byte && ranges[0].end_byte <= 22);
+
+    let new_tree = parser.parse(&source, Some(&tree)).unwrap();
+    assert_eq!(new_tree.edited_ranges().count(), 0);
+}
+
 #[test]
 fn test_tree_cursor() {
     let mut parser = Parser::new();
@@ -599,6 +800,119 @@ fn test_tree_cursor_child_for_point() {
     );
 }
 
+#[test]
+fn test_tree_node_at() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "function f() { return a + b + c; }";
+    let tree = parser.parse(source, None).unwrap();
+
+    // Of the nested binary expressions that start at the same offset, the
+    // innermost one is found.
+    let sum_start = source.find("a +").unwrap();
+    let inner_sum = tree.node_at(sum_start, "binary_expression").unwrap();
+    assert_eq!(inner_sum.utf8_text(source.as_bytes()).unwrap(), "a + b");
+    let outer_sum = inner_sum.parent().unwrap();
+    assert_eq!(outer_sum.utf8_text(source.as_bytes()).unwrap(), "a + b + c");
+
+    // Store the positions and kinds of some nodes, and find them again after
+    // parsing the same text from scratch.
+    let stored = [
+        (0, "function_declaration"),
+        (source.find("return").unwrap(), "return_statement"),
+        (inner_sum.start_byte(), inner_sum.kind()),
+        (source.find("c;").unwrap(), "identifier"),
+    ];
+    let new_tree = parser.parse(source, None).unwrap();
+    for (offset, kind) in stored {
+        let old_node = tree.node_at(offset, kind).unwrap();
+        let new_node = new_tree.node_at(offset, kind).unwrap();
+        assert_eq!(new_node.kind(), kind);
+        assert_eq!(new_node.byte_range(), old_node.byte_range());
+        assert_eq!(new_node.to_sexp(), old_node.to_sexp());
+    }
+
+    // There is no node of the given kind at the given offset.
+    assert_eq!(new_tree.node_at(sum_start, "return_statement"), None);
+    assert_eq!(new_tree.node_at(sum_start + 1, "identifier"), None);
+    assert_eq!(new_tree.node_at(source.len() + 10, "identifier"), None);
+}
+
+#[test]
+fn test_tree_node_count() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let tree = parser.parse("let x = f(1, y);", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (lexical_declaration (variable_declarator name: (identifier) value: (call_expression function: (identifier) arguments: (arguments (number) (identifier))))))"
+    );
+    // The named nodes, plus `let`, `=`, `(`, `,`, `)` and `;`.
+    assert_eq!(tree.named_node_count(), 9);
+    assert_eq!(tree.node_count(), 15);
+
+    // ERROR and MISSING nodes are counted, and the counts match a full walk of
+    // the tree.
+    let tree = parser.parse("let x = f(1, y;\nlet = 2;", None).unwrap();
+    assert!(tree.root_node().has_error());
+    let mut all = 0;
+    let mut named = 0;
+    let mut cursor = tree.walk();
+    'outer: loop {
+        all += 1;
+        if cursor.node().is_named() {
+            named += 1;
+        }
+        if !cursor.goto_first_child() {
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+    }
+    assert_eq!(tree.node_count(), all);
+    assert_eq!(tree.named_node_count(), named);
+}
+
+#[test]
+fn test_tree_reconstruct_source() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let source = r#"
+// A comment before the first item.
+fn main() {
+    let s = "a \"quoted\" string";   /* a block comment */
+    println!("{s} {}", 1 + 2);
+}
+
+struct Foo<'a> { bar: &'a [u8] }
+"#;
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(
+        str::from_utf8(&tree.reconstruct_source(source.as_bytes())).unwrap(),
+        source
+    );
+
+    // Text outside of the included ranges is copied from the source.
+    parser
+        .set_included_ranges(&[Range {
+            start_byte: 37,
+            end_byte: 48,
+            start_point: Point::new(2, 0),
+            end_point: Point::new(2, 11),
+        }])
+        .unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(tree.root_node().start_byte(), 37);
+    assert_eq!(
+        str::from_utf8(&tree.reconstruct_source(source.as_bytes())).unwrap(),
+        source
+    );
+}
+
 #[test]
 fn test_tree_node_equality() {
     let mut parser = Parser::new();
@@ -741,6 +1055,73 @@ fn test_consistency_with_mid_codepoint_edit() {
     assert_eq!(tree3.root_node().to_sexp(), tree.root_node().to_sexp());
 }
 
+#[test]
+fn test_tree_apply_edit_and_undo() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let mut source_code = b"let a = 1;\nlet b = 2;".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+    let original_sexp = tree.root_node().to_sexp();
+
+    // Replace `b = 2` with `c = [3, 4]`.
+    let edit = Edit {
+        position: 15,
+        deleted_length: 5,
+        inserted_text: b"c = [3, 4]".to_vec(),
+    };
+    let inverse = edit.invert(&source_code).unwrap();
+    let input_edit = apply_edit(&mut tree, &mut source_code, &edit).unwrap();
+    assert_eq!(source_code, b"let a = 1;\nlet c = [3, 4];");
+    assert_eq!(
+        input_edit,
+        InputEdit {
+            start_byte: 15,
+            old_end_byte: 20,
+            new_end_byte: 25,
+            start_position: Point::new(1, 4),
+            old_end_position: Point::new(1, 9),
+            new_end_position: Point::new(1, 14),
+        }
+    );
+
+    let tree = parser.parse(&source_code, Some(&tree)).unwrap();
+    assert!(tree.root_node().to_sexp().contains("array"));
+
+    // Applying the inverse edit restores the original document.
+    let mut tree = tree;
+    apply_edit(&mut tree, &mut source_code, &inverse).unwrap();
+    assert_eq!(source_code, b"let a = 1;\nlet b = 2;");
+    let tree = parser.parse(&source_code, Some(&tree)).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), original_sexp);
+
+    // Edits past the end of the document are rejected.
+    let mut tree = tree;
+    let edit = Edit {
+        position: 100,
+        deleted_length: 0,
+        inserted_text: b"x".to_vec(),
+    };
+    assert_eq!(
+        apply_edit(&mut tree, &mut source_code, &edit),
+        Err(EditError { offset: 100 })
+    );
+    assert_eq!(edit.invert(&source_code), Err(EditError { offset: 100 }));
+
+    // Edits whose end overflows are rejected without changing the document.
+    let edit = Edit {
+        position: 1,
+        deleted_length: usize::MAX,
+        inserted_text: Vec::new(),
+    };
+    let original_source = source_code.clone();
+    assert_eq!(
+        apply_edit(&mut tree, &mut source_code, &edit),
+        Err(EditError { offset: 1 })
+    );
+    assert_eq!(edit.invert(&source_code), Err(EditError { offset: 1 }));
+    assert_eq!(source_code, original_source);
+}
+
 #[test]
 fn test_tree_cursor_on_aliased_root_with_extra_child() {
     let source = r"
@@ -768,6 +1149,54 @@ fn main() {
     assert_eq!(cursor.node().kind(), "block_comment");
 }
 
+#[test]
+fn test_get_changed_ranges_in_points_and_utf16() {
+    let mut source_code = "y;\nx = ['é😀', ab];\n".as_bytes().to_vec();
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+
+    // Replace the identifier `ab` with the number `12`.
+    let edit = Edit {
+        position: index_of(&source_code, "ab"),
+        deleted_length: 2,
+        inserted_text: b"12".to_vec(),
+    };
+    perform_edit(&mut tree, &mut source_code, &edit).unwrap();
+    let new_tree = parser.parse(&source_code, Some(&tree)).unwrap();
+
+    // Byte offsets and columns count the four-byte emoji and two-byte `é`.
+    let ranges = tree.changed_ranges(&new_tree).collect::<Vec<_>>();
+    assert_eq!(
+        ranges,
+        vec![Range {
+            start_byte: 18,
+            end_byte: 20,
+            start_point: Point::new(1, 15),
+            end_point: Point::new(1, 17),
+        }]
+    );
+    assert_eq!(&source_code[18..20], b"12");
+
+    // In UTF-16, the emoji is a surrogate pair and `é` is a single code unit.
+    let ranges = tree.changed_ranges_utf16(&new_tree, &source_code);
+    assert_eq!(
+        ranges,
+        vec![Range {
+            start_byte: 15,
+            end_byte: 17,
+            start_point: Point::new(1, 12),
+            end_point: Point::new(1, 14),
+        }]
+    );
+    let utf16 = str::from_utf8(&source_code)
+        .unwrap()
+        .encode_utf16()
+        .collect::<Vec<_>>();
+    assert_eq!(String::from_utf16(&utf16[15..17]).unwrap(), "12");
+}
+
 fn index_of(text: &[u8], substring: &str) -> usize {
     str::from_utf8(text).unwrap().find(substring).unwrap()
 }
@@ -783,6 +1212,49 @@ fn range_of(text: &[u8], substring: &str) -> Range {
     }
 }
 
+#[test]
+fn test_tree_changed_subtrees() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut source_code = b"function a() { return 1; }\nfunction b() { return 2; }\n".to_vec();
+    let mut old_tree = parser.parse(&source_code, None).unwrap();
+    let position = source_code.iter().position(|c| *c == b'2').unwrap();
+    perform_edit(
+        &mut old_tree,
+        &mut source_code,
+        &Edit {
+            position,
+            deleted_length: 1,
+            inserted_text: b"x + y".to_vec(),
+        },
+    )
+    .unwrap();
+    let new_tree = parser.parse(&source_code, Some(&old_tree)).unwrap();
+
+    let function_b = new_tree.root_node().named_child(1).unwrap();
+    assert_eq!(function_b.kind(), "function_declaration");
+
+    let subtrees = new_tree.changed_subtrees(&old_tree).collect::<Vec<_>>();
+    assert!(!subtrees.is_empty());
+    for node in &subtrees {
+        assert!(
+            function_b.start_byte() <= node.start_byte()
+                && node.end_byte() <= function_b.end_byte(),
+            "{node:?} is outside of the edited function"
+        );
+    }
+    assert!(
+        subtrees
+            .iter()
+            .any(|node| node.start_byte() <= position && position + 5 <= node.end_byte())
+    );
+
+    // Reparsing without any changes yields no subtrees.
+    let unchanged_tree = parser.parse(&source_code, Some(&new_tree)).unwrap();
+    assert_eq!(unchanged_tree.changed_subtrees(&new_tree).count(), 0);
+}
+
 fn get_changed_ranges(
     parser: &mut Parser,
     tree: &mut Tree,
@@ -836,3 +1308,25 @@ fn test_reuse_invalidates_scanner_token_when_included_range_expands() {
         "(document (span (open_delim) (close_delim)))"
     );
 }
+
+#[test]
+fn test_tree_print_dot_graph() {
+    use std::io::{Read, Seek};
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("const zero = 0", None).unwrap();
+
+    let mut debug_graph_file = tempfile::tempfile().unwrap();
+    tree.print_dot_graph(&debug_graph_file);
+
+    debug_graph_file.rewind().unwrap();
+    let mut graph = String::new();
+    debug_graph_file.read_to_string(&mut graph).unwrap();
+
+    // The output is identical on every platform, with no newline translation.
+    assert!(graph.starts_with("digraph tree {\nedge [arrowhead=none]\n"));
+    assert!(graph.ends_with("}\n"));
+    assert!(!graph.contains('\r'));
+    assert!(graph.contains("label=\"lexical_declaration\""));
+}
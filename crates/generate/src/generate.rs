@@ -10,7 +10,7 @@ use std::{
 
 use bitflags::bitflags;
 use node_types::VariableInfo;
-use rules::{Alias, Symbol};
+use rules::{Alias, Symbol, SymbolType};
 #[cfg(feature = "load")]
 use semver::Version;
 use serde::{Deserialize, Serialize};
@@ -32,7 +32,7 @@ mod tables;
 
 pub use build_tables::ParseTableBuilderError;
 use build_tables::build_tables;
-use grammars::{InlinedProductionMap, InputGrammar, LexicalGrammar, SyntaxGrammar};
+use grammars::{InlinedProductionMap, InputGrammar, LexicalGrammar, SyntaxGrammar, VariableType};
 pub use node_types::{InvalidSupertypeError, SuperTypeCycleError, VariableInfoError};
 pub use parse_grammar::ParseGrammarError;
 use parse_grammar::parse_grammar;
@@ -425,6 +425,63 @@ pub fn generate_parser_for_grammar(
     Ok((input_grammar.name, parser.c_code))
 }
 
+/// A rule that was marked `inline` in the grammar, and so never appears as a
+/// node in the syntax tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InlinedRule {
+    pub name: String,
+    /// The alternatives that are substituted wherever the rule is used. Each
+    /// one is a sequence of symbol names, with anonymous tokens quoted.
+    pub expansions: Vec<Vec<String>>,
+}
+
+/// List the rules that are inlined when generating a parser for the given
+/// grammar, along with what each of them expands to.
+pub fn get_inlined_rules(
+    grammar_json: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> GenerateResult<Vec<InlinedRule>> {
+    let input_grammar = parse_grammar(grammar_json, diagnostics)?;
+    let (syntax_grammar, lexical_grammar, _, _) = prepare_grammar(&input_grammar, diagnostics)?;
+
+    let symbol_name = |symbol: Symbol| match symbol.kind {
+        SymbolType::End | SymbolType::EndOfNonTerminalExtra => "EOF".to_string(),
+        SymbolType::External => syntax_grammar.external_tokens[symbol.index].name.clone(),
+        SymbolType::NonTerminal => syntax_grammar.variables[symbol.index].name.clone(),
+        SymbolType::Terminal => {
+            let variable = &lexical_grammar.variables[symbol.index];
+            if variable.kind == VariableType::Named {
+                variable.name.clone()
+            } else {
+                format!("'{}'", variable.name)
+            }
+        }
+    };
+
+    Ok(syntax_grammar
+        .variables_to_inline
+        .iter()
+        .filter(|symbol| symbol.is_non_terminal())
+        .map(|symbol| {
+            let variable = &syntax_grammar.variables[symbol.index];
+            InlinedRule {
+                name: variable.name.clone(),
+                expansions: variable
+                    .productions
+                    .iter()
+                    .map(|production| {
+                        production
+                            .steps
+                            .iter()
+                            .map(|step| symbol_name(step.symbol))
+                            .collect()
+                    })
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
 fn generate_node_types_from_grammar(
     input_grammar: &InputGrammar,
     diagnostics: &mut Vec<Diagnostic>,
@@ -687,7 +744,7 @@ pub fn write_file(path: &Path, body: impl AsRef<[u8]>) -> GenerateResult<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::{LANGUAGE_VERSION, PARSER_HEADER};
+    use super::{InlinedRule, LANGUAGE_VERSION, PARSER_HEADER, get_inlined_rules};
     #[test]
     fn test_language_versions_are_in_sync() {
         let api_h = include_str!("../../../lib/include/tree_sitter/api.h");
@@ -710,4 +767,48 @@ mod tests {
             "parser.h.inc is out of sync with lib/src/parser.h. Run: cp lib/src/parser.h crates/generate/src/parser.h.inc"
         );
     }
+
+    #[test]
+    fn test_get_inlined_rules() {
+        let inlined_rules = get_inlined_rules(
+            r#"{
+                "name": "test",
+                "inline": ["_item"],
+                "rules": {
+                    "program": {
+                        "type": "REPEAT",
+                        "content": { "type": "SYMBOL", "name": "_item" }
+                    },
+                    "_item": {
+                        "type": "CHOICE",
+                        "members": [
+                            { "type": "SYMBOL", "name": "number" },
+                            {
+                                "type": "SEQ",
+                                "members": [
+                                    { "type": "STRING", "value": "(" },
+                                    { "type": "SYMBOL", "name": "number" },
+                                    { "type": "STRING", "value": ")" }
+                                ]
+                            }
+                        ]
+                    },
+                    "number": { "type": "PATTERN", "value": "\\d+" }
+                }
+            }"#,
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            inlined_rules,
+            vec![InlinedRule {
+                name: "_item".to_string(),
+                expansions: vec![
+                    vec!["number".to_string()],
+                    vec!["'('".to_string(), "number".to_string(), "')'".to_string()],
+                ],
+            }]
+        );
+    }
 }
@@ -42,6 +42,8 @@ pub struct NodeInfoJSON {
     root: bool,
     #[serde(skip_serializing_if = "std::ops::Not::not")]
     extra: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    external: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     fields: Option<BTreeMap<String, FieldInfoJSON>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -612,6 +614,7 @@ pub fn generate_node_types_json(
                         named: true,
                         root: false,
                         extra: extra_names.contains(&variable.name),
+                        external: false,
                         fields: None,
                         children: None,
                         subtypes: None,
@@ -662,6 +665,7 @@ pub fn generate_node_types_json(
                         named: is_named,
                         root: i == 0,
                         extra: extra_names.contains(&kind),
+                        external: false,
                         fields: Some(BTreeMap::new()),
                         children: None,
                         subtypes: None,
@@ -748,7 +752,7 @@ pub fn generate_node_types_json(
         }
     }
 
-    let mut anonymous_node_types = Vec::new();
+    let mut anonymous_node_types = BTreeMap::new();
 
     let regular_tokens = lexical_grammar
         .variables
@@ -762,8 +766,8 @@ pub fn generate_node_types_json(
                 .map(move |alias| {
                     alias
                         .as_ref()
-                        .map_or((&variable.name, variable.kind), |alias| {
-                            (&alias.value, alias.kind())
+                        .map_or((&variable.name, variable.kind, false), |alias| {
+                            (&alias.value, alias.kind(), false)
                         })
                 })
         });
@@ -778,27 +782,36 @@ pub fn generate_node_types_json(
                     .unwrap_or(&empty)
                     .iter()
                     .map(move |alias| {
-                        alias.as_ref().map_or((&token.name, token.kind), |alias| {
-                            (&alias.value, alias.kind())
-                        })
+                        alias
+                            .as_ref()
+                            .map_or((&token.name, token.kind, true), |alias| {
+                                (&alias.value, alias.kind(), true)
+                            })
                     })
             });
 
-    for (name, kind) in regular_tokens.chain(external_tokens) {
+    // A token is only marked as external if it can't also be produced by the
+    // lexer, or by a rule with the same name.
+    for (name, kind, is_external) in regular_tokens.chain(external_tokens) {
         match kind {
             VariableType::Named => {
-                let node_type_json =
-                    node_types_json
-                        .entry(name.clone())
-                        .or_insert_with(|| NodeInfoJSON {
-                            kind: name.clone(),
-                            named: true,
-                            root: false,
-                            extra: extra_names.contains(&name),
-                            fields: None,
-                            children: None,
-                            subtypes: None,
-                        });
+                let mut node_type_existed = true;
+                let node_type_json = node_types_json.entry(name.clone()).or_insert_with(|| {
+                    node_type_existed = false;
+                    NodeInfoJSON {
+                        kind: name.clone(),
+                        named: true,
+                        root: false,
+                        extra: extra_names.contains(&name),
+                        external: is_external,
+                        fields: None,
+                        children: None,
+                        subtypes: None,
+                    }
+                });
+                if node_type_existed {
+                    node_type_json.external &= is_external;
+                }
                 if let Some(children) = &mut node_type_json.children {
                     children.required = false;
                 }
@@ -808,21 +821,29 @@ pub fn generate_node_types_json(
                     }
                 }
             }
-            VariableType::Anonymous => anonymous_node_types.push(NodeInfoJSON {
-                kind: name.clone(),
-                named: false,
-                root: false,
-                extra: extra_names.contains(&name),
-                fields: None,
-                children: None,
-                subtypes: None,
-            }),
+            VariableType::Anonymous => {
+                anonymous_node_types
+                    .entry(name.clone())
+                    .and_modify(|node_type_json: &mut NodeInfoJSON| {
+                        node_type_json.external &= is_external;
+                    })
+                    .or_insert_with(|| NodeInfoJSON {
+                        kind: name.clone(),
+                        named: false,
+                        root: false,
+                        extra: extra_names.contains(&name),
+                        external: is_external,
+                        fields: None,
+                        children: None,
+                        subtypes: None,
+                    });
+            }
             _ => {}
         }
     }
 
     let mut result = node_types_json.into_iter().map(|e| e.1).collect::<Vec<_>>();
-    result.extend(anonymous_node_types);
+    result.extend(anonymous_node_types.into_values());
     result.sort_unstable_by(|a, b| {
         b.subtypes
             .is_some()
@@ -836,6 +857,7 @@ pub fn generate_node_types_json(
             .then_with(|| a.named.cmp(&b.named))
             .then_with(|| a.root.cmp(&b.root))
             .then_with(|| a.extra.cmp(&b.extra))
+            .then_with(|| a.external.cmp(&b.external))
     });
     result.dedup();
     Ok(result)
@@ -937,6 +959,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -976,6 +999,7 @@ mod tests {
                 named: false,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -988,6 +1012,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1037,6 +1062,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -1076,6 +1102,7 @@ mod tests {
                 named: false,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1088,6 +1115,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1100,6 +1128,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: true,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1149,6 +1178,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -1188,6 +1218,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: true,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: Some(BTreeMap::default())
@@ -1200,6 +1231,7 @@ mod tests {
                 named: false,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1212,6 +1244,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1260,6 +1293,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 fields: None,
                 children: None,
                 subtypes: Some(vec![
@@ -1285,6 +1319,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -1349,6 +1384,46 @@ mod tests {
         assert!(node_types.is_ok());
     }
 
+    #[test]
+    fn test_node_types_with_external_tokens() {
+        let node_types = get_node_types(&InputGrammar {
+            variables: vec![
+                Variable {
+                    name: "v1".to_string(),
+                    kind: VariableType::Named,
+                    rule: Rule::seq(vec![
+                        Rule::named("heredoc"),
+                        Rule::named("v2"),
+                        Rule::string(";"),
+                    ]),
+                },
+                Variable {
+                    name: "v2".to_string(),
+                    kind: VariableType::Named,
+                    rule: Rule::pattern("\\w+", ""),
+                },
+            ],
+            external_tokens: vec![Rule::named("heredoc")],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let get = |kind: &str| node_types.iter().find(|t| t.kind == kind).unwrap();
+        assert!(get("heredoc").external);
+        assert!(!get("v2").external);
+        assert!(!get(";").external);
+        assert!(!get("v1").external);
+
+        assert_eq!(
+            serde_json::to_value(get("heredoc")).unwrap(),
+            serde_json::json!({"type": "heredoc", "named": true, "external": true})
+        );
+        assert_eq!(
+            serde_json::to_value(get("v2")).unwrap(),
+            serde_json::json!({"type": "v2", "named": true})
+        );
+    }
+
     #[test]
     fn test_node_types_for_children_without_fields() {
         let node_types = get_node_types(&InputGrammar {
@@ -1393,6 +1468,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
@@ -1432,6 +1508,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: false,
@@ -1479,6 +1556,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
@@ -1555,6 +1633,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None,
@@ -1567,6 +1646,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: None,
                 fields: None,
@@ -1611,6 +1691,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
@@ -1661,6 +1742,7 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                external: false,
                 fields: Some(BTreeMap::new()),
                 children: None,
                 subtypes: None
@@ -1720,6 +1802,7 @@ mod tests {
                     named: true,
                     root: false,
                     extra: false,
+                    external: false,
                     subtypes: None,
                     children: None,
                     fields: Some(
@@ -1777,6 +1860,7 @@ mod tests {
                     named: true,
                     root: true,
                     extra: false,
+                    external: false,
                     subtypes: None,
                     // Only one node
                     children: Some(FieldInfoJSON {
@@ -1834,6 +1918,7 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                external: false,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
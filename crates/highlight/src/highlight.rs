@@ -3,6 +3,7 @@
 pub mod c_lib;
 use core::slice;
 use std::{
+    cmp,
     collections::HashSet,
     iter,
     marker::PhantomData,
@@ -104,8 +105,17 @@ pub enum Error {
 /// Represents a single step in rendering a syntax-highlighted document.
 #[derive(Copy, Clone, Debug)]
 pub enum HighlightEvent {
-    Source { start: usize, end: usize },
-    HighlightStart(Highlight),
+    Source {
+        start: usize,
+        end: usize,
+    },
+    /// The start of a highlighted region. The `pattern_index` identifies the
+    /// pattern that assigned the highlight, within the [`HighlightConfiguration::query`]
+    /// of the language being highlighted, which may be an injected language.
+    HighlightStart {
+        highlight: Highlight,
+        pattern_index: usize,
+    },
     HighlightEnd,
 }
 
@@ -127,6 +137,7 @@ pub struct HighlightConfiguration {
     local_def_capture_index: Option<u32>,
     local_def_value_capture_index: Option<u32>,
     local_ref_capture_index: Option<u32>,
+    fold_capture_index: Option<u32>,
 }
 
 /// Performs syntax highlighting, recognizing a given list of highlight names.
@@ -137,6 +148,7 @@ pub struct HighlightConfiguration {
 pub struct Highlighter {
     pub parser: Parser,
     cursors: Vec<QueryCursor>,
+    fold_ranges: Vec<Range>,
 }
 
 /// Converts a general-purpose syntax highlighting iterator into a sequence of lines of HTML.
@@ -162,6 +174,50 @@ struct LocalScope<'a> {
     local_defs: Vec<LocalDef<'a>>,
 }
 
+/// The local variable scopes, definitions, and references in a syntax tree,
+/// as computed by [`resolve_locals`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocalsResolution {
+    /// The scopes, in the order that they start. The first scope is the root
+    /// scope, which covers the whole tree.
+    pub scopes: Vec<LocalsScope>,
+    /// The definitions, in the order that they appear.
+    pub definitions: Vec<LocalsDefinition>,
+    /// The references, in the order that they appear.
+    pub references: Vec<LocalsReference>,
+}
+
+/// A scope captured with `@local.scope`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalsScope {
+    pub range: ops::Range<usize>,
+    /// The index of the enclosing scope, or `None` for the root scope.
+    pub parent: Option<usize>,
+    /// Whether references in this scope can resolve to definitions in the
+    /// enclosing scopes. This is `false` when the scope's pattern sets the
+    /// `local.scope-inherits` property to `false`.
+    pub inherits: bool,
+}
+
+/// A definition captured with `@local.definition`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalsDefinition {
+    pub name: String,
+    pub range: ops::Range<usize>,
+    /// The index of the scope that contains the definition.
+    pub scope: usize,
+}
+
+/// A reference captured with `@local.reference`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalsReference {
+    pub name: String,
+    pub range: ops::Range<usize>,
+    /// The index of the definition that the reference resolves to, or `None` if
+    /// it does not refer to a local variable.
+    pub definition: Option<usize>,
+}
+
 struct HighlightIter<'a, F>
 where
     F: FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
@@ -170,6 +226,7 @@ where
     encoding: Option<u32>,
     language_name: &'a str,
     byte_offset: usize,
+    byte_range: ops::Range<usize>,
     highlighter: &'a mut Highlighter,
     injection_callback: F,
     cancellation_flag: Option<&'a AtomicUsize>,
@@ -179,12 +236,21 @@ where
     last_highlight_range: Option<(usize, usize, usize)>,
 }
 
+// The part of a highlight that lies in a later included range of an injection
+// than the one in which the highlighted node starts.
+struct PendingHighlight {
+    range: ops::Range<usize>,
+    highlight: Highlight,
+    pattern_index: usize,
+}
+
 struct HighlightIterLayer<'a> {
     _tree: Tree,
     cursor: QueryCursor,
     captures: iter::Peekable<_QueryCaptures<'a, 'a, &'a [u8], &'a [u8]>>,
     config: &'a HighlightConfiguration,
     highlight_end_stack: Vec<usize>,
+    pending_highlights: Vec<PendingHighlight>,
     scope_stack: Vec<LocalScope<'a>>,
     ranges: Vec<Range>,
     depth: usize,
@@ -284,6 +350,7 @@ impl Highlighter {
         Self {
             parser: Parser::new(),
             cursors: Vec::new(),
+            fold_ranges: Vec::new(),
         }
     }
 
@@ -291,15 +358,64 @@ impl Highlighter {
         &mut self.parser
     }
 
+    /// Get the ranges of the nodes that were captured with `@fold` by the folds
+    /// queries of the most recent call to [`Highlighter::highlight`], in the order
+    /// in which they start.
+    ///
+    /// The ranges are collected while the highlight events are iterated, so this
+    /// is only complete once the iterator has been exhausted. See
+    /// [`HighlightConfiguration::new_with_folds`].
+    #[must_use]
+    pub fn fold_ranges(&self) -> &[Range] {
+        &self.fold_ranges
+    }
+
     /// Iterate over the highlighted regions for a given slice of source code.
+    ///
+    /// Highlighting can be cancelled from another thread by setting
+    /// `cancellation_flag` to a non-zero value. This halts any parse that is
+    /// in progress, and the iterator then yields a single
+    /// [`Error::Cancelled`] and ends.
     pub fn highlight<'a>(
         &'a mut self,
         config: &'a HighlightConfiguration,
         source: &'a [u8],
         encoding: Option<u32>,
         cancellation_flag: Option<&'a AtomicUsize>,
+        injection_callback: impl FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
+    ) -> Result<impl Iterator<Item = Result<HighlightEvent, Error>> + 'a, Error> {
+        self.highlight_range(
+            config,
+            source,
+            0..usize::MAX,
+            encoding,
+            cancellation_flag,
+            injection_callback,
+        )
+    }
+
+    /// Iterate over the highlighted regions within a given byte range of some
+    /// source code, such as the lines that are visible in an editor's viewport.
+    ///
+    /// The whole document is still parsed, but only the highlights that
+    /// intersect `byte_range` are processed, and the [`HighlightEvent::Source`]
+    /// events only cover that range. The events are always well-nested: a
+    /// highlight that begins before the range is opened at the start of the
+    /// range, and a highlight that continues past the end of the range is closed
+    /// at the end of it.
+    ///
+    /// Local variables that are defined before the start of the range are not
+    /// taken into account when highlighting references within the range.
+    pub fn highlight_range<'a>(
+        &'a mut self,
+        config: &'a HighlightConfiguration,
+        source: &'a [u8],
+        byte_range: ops::Range<usize>,
+        encoding: Option<u32>,
+        cancellation_flag: Option<&'a AtomicUsize>,
         mut injection_callback: impl FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
     ) -> Result<impl Iterator<Item = Result<HighlightEvent, Error>> + 'a, Error> {
+        self.fold_ranges.clear();
         let layers = HighlightIterLayer::new(
             source,
             encoding,
@@ -309,6 +425,7 @@ impl Highlighter {
             &mut injection_callback,
             config,
             0,
+            &byte_range,
             vec![Range {
                 start_byte: 0,
                 end_byte: usize::MAX,
@@ -321,7 +438,8 @@ impl Highlighter {
             source,
             encoding,
             language_name: &config.language_name,
-            byte_offset: 0,
+            byte_offset: byte_range.start,
+            byte_range,
             injection_callback,
             cancellation_flag,
             highlighter: self,
@@ -356,15 +474,43 @@ impl HighlightConfiguration {
         highlights_query: &str,
         injection_query: &str,
         locals_query: &str,
+    ) -> Result<Self, QueryError> {
+        Self::new_with_folds(
+            language,
+            name,
+            highlights_query,
+            injection_query,
+            locals_query,
+            "",
+        )
+    }
+
+    /// Creates a `HighlightConfiguration` like [`HighlightConfiguration::new`], which also
+    /// collects folding ranges while highlighting.
+    ///
+    /// * `folds_query` - A string containing tree patterns that capture foldable nodes with
+    ///   `@fold`. The ranges of the captured nodes are available from
+    ///   [`Highlighter::fold_ranges`] after highlighting, so that highlights and folding ranges
+    ///   are computed in a single pass over the syntax tree.
+    pub fn new_with_folds(
+        language: Language,
+        name: impl Into<String>,
+        highlights_query: &str,
+        injection_query: &str,
+        locals_query: &str,
+        folds_query: &str,
     ) -> Result<Self, QueryError> {
         // Concatenate the query strings, keeping track of the start offset of each section.
+        // The folds patterns are placed before the highlights patterns, so that, like the
+        // locals patterns, they are processed before a node's highlight is determined.
         let mut query_source = String::with_capacity(
-            injection_query.len() + locals_query.len() + highlights_query.len(),
+            injection_query.len() + locals_query.len() + folds_query.len() + highlights_query.len(),
         );
         query_source.push_str(injection_query);
         let locals_query_offset = injection_query.len();
         query_source.push_str(locals_query);
-        let highlights_query_offset = injection_query.len() + locals_query.len();
+        query_source.push_str(folds_query);
+        let highlights_query_offset = query_source.len();
         query_source.push_str(highlights_query);
 
         // Construct a single query by concatenating the three query strings, but record the
@@ -421,6 +567,7 @@ impl HighlightConfiguration {
         let mut local_def_value_capture_index = None;
         let mut local_ref_capture_index = None;
         let mut local_scope_capture_index = None;
+        let mut fold_capture_index = None;
         for (i, name) in query.capture_names().iter().enumerate() {
             let i = Some(i as u32);
             match *name {
@@ -430,6 +577,7 @@ impl HighlightConfiguration {
                 "local.definition-value" => local_def_value_capture_index = i,
                 "local.reference" => local_ref_capture_index = i,
                 "local.scope" => local_scope_capture_index = i,
+                "fold" => fold_capture_index = i,
                 _ => {}
             }
         }
@@ -450,6 +598,7 @@ impl HighlightConfiguration {
             local_def_value_capture_index,
             local_ref_capture_index,
             local_scope_capture_index,
+            fold_capture_index,
         })
     }
 
@@ -535,6 +684,7 @@ impl<'a> HighlightIterLayer<'a> {
         injection_callback: &mut F,
         mut config: &'a HighlightConfiguration,
         mut depth: usize,
+        byte_range: &ops::Range<usize>,
         mut ranges: Vec<Range>,
     ) -> Result<Vec<Self>, Error> {
         let mut result = Vec::with_capacity(1);
@@ -564,20 +714,17 @@ impl<'a> HighlightIterLayer<'a> {
                             .iter()
                             .map(|&chunk| u16::from_le_bytes(chunk))
                             .collect::<Vec<_>>();
-                        highlighter
-                            .parser
-                            .parse_utf16_le_with_options(
-                                &mut |i, _| {
-                                    if i < source_code_utf16.len() {
-                                        &source_code_utf16[i..]
-                                    } else {
-                                        &[]
-                                    }
-                                },
-                                None,
-                                Some(parse_opts),
-                            )
-                            .ok_or(Error::Cancelled)?
+                        highlighter.parser.parse_utf16_le_with_options(
+                            &mut |i, _| {
+                                if i < source_code_utf16.len() {
+                                    &source_code_utf16[i..]
+                                } else {
+                                    &[]
+                                }
+                            },
+                            None,
+                            Some(parse_opts),
+                        )
                     }
                     Some(encoding) if encoding == ffi::TSInputEncodingUTF16BE => {
                         let source_code_utf16 = source
@@ -586,33 +733,34 @@ impl<'a> HighlightIterLayer<'a> {
                             .iter()
                             .map(|&chunk| u16::from_be_bytes(chunk))
                             .collect::<Vec<_>>();
-                        highlighter
-                            .parser
-                            .parse_utf16_be_with_options(
-                                &mut |i, _| {
-                                    if i < source_code_utf16.len() {
-                                        &source_code_utf16[i..]
-                                    } else {
-                                        &[]
-                                    }
-                                },
-                                None,
-                                Some(parse_opts),
-                            )
-                            .ok_or(Error::Cancelled)?
-                    }
-                    _ => highlighter
-                        .parser
-                        .parse_with_options(
+                        highlighter.parser.parse_utf16_be_with_options(
                             &mut |i, _| {
-                                if i < source.len() { &source[i..] } else { &[] }
+                                if i < source_code_utf16.len() {
+                                    &source_code_utf16[i..]
+                                } else {
+                                    &[]
+                                }
                             },
                             None,
                             Some(parse_opts),
                         )
-                        .ok_or(Error::Cancelled)?,
+                    }
+                    _ => highlighter.parser.parse_with_options(
+                        &mut |i, _| {
+                            if i < source.len() { &source[i..] } else { &[] }
+                        },
+                        None,
+                        Some(parse_opts),
+                    ),
+                };
+                // A cancelled parse leaves the parser ready to resume, so reset it before the
+                // highlighter is used for another document.
+                let Some(tree) = tree else {
+                    highlighter.parser.reset();
+                    return Err(Error::Cancelled);
                 };
                 let mut cursor = highlighter.cursors.pop().unwrap_or_default();
+                cursor.set_byte_range(byte_range.clone());
 
                 // Process combined injections.
                 if let Some(combined_injections_query) = &config.combined_injections_query {
@@ -672,6 +820,7 @@ impl<'a> HighlightIterLayer<'a> {
 
                 result.push(HighlightIterLayer {
                     highlight_end_stack: Vec::new(),
+                    pending_highlights: Vec::new(),
                     scope_stack: vec![LocalScope {
                         inherits: false,
                         range: 0..usize::MAX,
@@ -793,15 +942,26 @@ impl<'a> HighlightIterLayer<'a> {
         result
     }
 
+    // Get the position of the next highlight start, either for a capture or for
+    // a part of an earlier highlight that continues in a later included range.
+    fn next_start(&mut self) -> Option<usize> {
+        let capture_start = self
+            .captures
+            .peek()
+            .map(|(m, i)| m.captures[*i].node.start_byte());
+        let pending_start = self.pending_highlights.last().map(|p| p.range.start);
+        match (capture_start, pending_start) {
+            (Some(capture_start), Some(pending_start)) => Some(capture_start.min(pending_start)),
+            (capture_start, pending_start) => capture_start.or(pending_start),
+        }
+    }
+
     // First, sort scope boundaries by their byte offset in the document. At a
     // given position, emit scope endings before scope beginnings. Finally, emit
     // scope boundaries from deeper layers first.
     fn sort_key(&mut self) -> Option<(usize, bool, isize)> {
         let depth = -(self.depth as isize);
-        let next_start = self
-            .captures
-            .peek()
-            .map(|(m, i)| m.captures[*i].node.start_byte());
+        let next_start = self.next_start();
         let next_end = self.highlight_end_stack.last().copied();
         match (next_start, next_end) {
             (Some(start), Some(end)) => {
@@ -818,6 +978,19 @@ impl<'a> HighlightIterLayer<'a> {
     }
 }
 
+// Split a highlighted byte range into its parts within each included range.
+// When an injection combines several separate nodes, a node in the injected
+// tree can span more than one included range, and its highlight must not
+// cover the text of the enclosing document that lies between them.
+fn split_by_included_ranges(ranges: &[Range], range: ops::Range<usize>) -> Vec<ops::Range<usize>> {
+    let parts = ranges
+        .iter()
+        .filter(|r| r.start_byte < range.end && r.end_byte > range.start)
+        .map(|r| range.start.max(r.start_byte)..range.end.min(r.end_byte))
+        .collect::<Vec<_>>();
+    if parts.is_empty() { vec![range] } else { parts }
+}
+
 impl<'a, F> HighlightIter<'a, F>
 where
     F: FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
@@ -827,6 +1000,7 @@ where
         offset: usize,
         event: Option<HighlightEvent>,
     ) -> Option<Result<HighlightEvent, Error>> {
+        let offset = offset.min(self.byte_range.end);
         let result;
         if self.byte_offset < offset {
             result = Some(Ok(HighlightEvent::Source {
@@ -898,12 +1072,14 @@ where
             }
 
             // Periodically check for cancellation, returning `Cancelled` error if the
-            // cancellation flag was flipped.
+            // cancellation flag was flipped. No further events are produced after that.
             if let Some(cancellation_flag) = self.cancellation_flag {
                 self.iter_count += 1;
                 if self.iter_count >= CANCELLATION_CHECK_INTERVAL {
                     self.iter_count = 0;
                     if cancellation_flag.load(Ordering::Relaxed) != 0 {
+                        self.layers.clear();
+                        self.byte_offset = self.byte_range.end;
                         return Some(Err(Error::Cancelled));
                     }
                 }
@@ -911,12 +1087,13 @@ where
 
             // If none of the layers have any more highlight boundaries, terminate.
             if self.layers.is_empty() {
-                return if self.byte_offset < self.source.len() {
+                let end = self.source.len().min(self.byte_range.end);
+                return if self.byte_offset < end {
                     let result = Some(Ok(HighlightEvent::Source {
                         start: self.byte_offset,
-                        end: self.source.len(),
+                        end,
                     }));
-                    self.byte_offset = self.source.len();
+                    self.byte_offset = end;
                     result
                 } else {
                     None
@@ -926,19 +1103,40 @@ where
             // Get the next capture from whichever layer has the earliest highlight boundary.
             let range;
             let layer = &mut self.layers[0];
-            if let Some((next_match, capture_index)) = layer.captures.peek() {
-                let next_capture = next_match.captures[*capture_index];
-                range = next_capture.node.byte_range();
-
-                // If any previous highlight ends before this node starts, then before
-                // processing this capture, emit the source code up until the end of the
+            if let Some(next_start) = layer.next_start() {
+                // If any previous highlight ends before the next one starts, then before
+                // processing the next one, emit the source code up until the end of the
                 // previous highlight, and an end event for that highlight.
                 if let Some(end_byte) = layer.highlight_end_stack.last().copied()
-                    && end_byte <= range.start
+                    && end_byte <= next_start
                 {
                     layer.highlight_end_stack.pop();
                     return self.emit_event(end_byte, Some(HighlightEvent::HighlightEnd));
                 }
+
+                // If an earlier highlight continues in a later included range, and that
+                // part starts before the next capture, then resume the highlight. At the
+                // same position, these parts belong to nodes that enclose the capture.
+                if let Some(pending) = layer.pending_highlights.last()
+                    && pending.range.start == next_start
+                {
+                    let pending = layer.pending_highlights.pop().unwrap();
+                    if pending.range.start < self.byte_range.end {
+                        layer.highlight_end_stack.push(pending.range.end);
+                        return self.emit_event(
+                            pending.range.start,
+                            Some(HighlightEvent::HighlightStart {
+                                highlight: pending.highlight,
+                                pattern_index: pending.pattern_index,
+                            }),
+                        );
+                    }
+                    self.sort_layers();
+                    continue 'main;
+                }
+
+                let (next_match, capture_index) = layer.captures.peek().unwrap();
+                range = next_match.captures[*capture_index].node.byte_range();
             }
             // If there are no more captures, then emit any remaining highlight end events.
             // And if there are none of those, then just advance to the end of the document.
@@ -987,6 +1185,7 @@ where
                             &mut self.injection_callback,
                             config,
                             self.layers[0].depth + 1,
+                            &self.byte_range,
                             ranges,
                         ) {
                             Ok(layers) => {
@@ -994,7 +1193,13 @@ where
                                     self.insert_layer(layer);
                                 }
                             }
-                            Err(e) => return Some(Err(e)),
+                            Err(e) => {
+                                if matches!(e, Error::Cancelled) {
+                                    self.layers.clear();
+                                    self.byte_offset = self.byte_range.end;
+                                }
+                                return Some(Err(e));
+                            }
                         }
                     }
                 }
@@ -1013,9 +1218,16 @@ where
             let mut reference_highlight = None;
             let mut definition_highlight = None;
             while match_.pattern_index < layer.config.highlights_pattern_index {
+                // If the node is foldable, then record its range.
+                if Some(capture.index) == layer.config.fold_capture_index {
+                    let fold_range = capture.node.range();
+                    if self.highlighter.fold_ranges.last() != Some(&fold_range) {
+                        self.highlighter.fold_ranges.push(fold_range);
+                    }
+                }
                 // If the node represents a local scope, push a new local scope onto
                 // the scope stack.
-                if Some(capture.index) == layer.config.local_scope_capture_index {
+                else if Some(capture.index) == layer.config.local_scope_capture_index {
                     definition_highlight = None;
                     let mut scope = LocalScope {
                         inherits: true,
@@ -1093,7 +1305,11 @@ where
                 continue 'main;
             }
 
-            // Otherwise, this capture must represent a highlight.
+            // Otherwise, this capture must represent a highlight. Only its first part is
+            // highlighted now, and any later parts are highlighted when they are reached.
+            let mut parts = split_by_included_ranges(&layer.ranges, range);
+            let range = parts.remove(0);
+
             // If this exact range has already been highlighted by an earlier pattern, or by
             // a different layer, then skip over this one.
             if let Some((last_start, last_end, last_depth)) = self.last_highlight_range
@@ -1139,11 +1355,34 @@ where
             }
 
             // Emit a scope start event and push the node's end position to the stack.
-            if let Some(highlight) = reference_highlight.or(current_highlight) {
+            // Highlights that start after the end of the byte range would be empty, so
+            // they are skipped.
+            if let Some(highlight) = reference_highlight.or(current_highlight)
+                && range.start < self.byte_range.end
+            {
                 self.last_highlight_range = Some((range.start, range.end, layer.depth));
                 layer.highlight_end_stack.push(range.end);
-                return self
-                    .emit_event(range.start, Some(HighlightEvent::HighlightStart(highlight)));
+                if !parts.is_empty() {
+                    layer
+                        .pending_highlights
+                        .extend(parts.into_iter().map(|range| PendingHighlight {
+                            range,
+                            highlight,
+                            pattern_index: match_.pattern_index,
+                        }));
+                    // Keep the next part last. Parts that start at the same position
+                    // are resumed from the outermost one, which ends last.
+                    layer
+                        .pending_highlights
+                        .sort_unstable_by_key(|p| (cmp::Reverse(p.range.start), p.range.end));
+                }
+                return self.emit_event(
+                    range.start,
+                    Some(HighlightEvent::HighlightStart {
+                        highlight,
+                        pattern_index: match_.pattern_index,
+                    }),
+                );
             }
 
             self.sort_layers();
@@ -1192,7 +1431,7 @@ impl HtmlRenderer {
         let mut highlights = Vec::new();
         for event in highlighter {
             match event {
-                Ok(HighlightEvent::HighlightStart(s)) => {
+                Ok(HighlightEvent::HighlightStart { highlight: s, .. }) => {
                     highlights.push(s);
                     self.start_highlight(s, &attribute_callback);
                 }
@@ -1311,6 +1550,115 @@ impl HtmlRenderer {
     }
 }
 
+impl LocalsResolution {
+    /// Get the definition that the reference at the given byte range resolves to.
+    #[must_use]
+    pub fn definition_for(&self, range: ops::Range<usize>) -> Option<&LocalsDefinition> {
+        let reference = self.references.iter().find(|r| r.range == range)?;
+        reference.definition.map(|i| &self.definitions[i])
+    }
+}
+
+/// Resolve each local variable reference in a syntax tree to its definition.
+///
+/// This uses the same captures as the locals query of a [`HighlightConfiguration`]:
+/// `@local.scope`, `@local.definition`, `@local.definition-value`, and
+/// `@local.reference`, along with the `local.scope-inherits` property, and the
+/// same algorithm that the [`Highlighter`] uses to highlight local variables. A
+/// reference resolves to the latest preceding definition with the same name in the
+/// innermost enclosing scope that has one, ignoring definitions whose value contains
+/// the reference. Scopes that don't inherit end the search. A node that is captured
+/// as a definition is not also treated as a reference.
+#[must_use]
+pub fn resolve_locals(query: &Query, tree: &Tree, source: &[u8]) -> LocalsResolution {
+    let scope_capture_index = query.capture_index_for_name("local.scope");
+    let def_capture_index = query.capture_index_for_name("local.definition");
+    let def_value_capture_index = query.capture_index_for_name("local.definition-value");
+    let ref_capture_index = query.capture_index_for_name("local.reference");
+
+    let root = tree.root_node();
+    let mut result = LocalsResolution {
+        scopes: vec![LocalsScope {
+            range: root.byte_range(),
+            parent: None,
+            inherits: false,
+        }],
+        ..Default::default()
+    };
+
+    // The open scopes, each with the indices of its definitions, and the value
+    // range of each definition.
+    let mut scope_stack = vec![(0, Vec::new())];
+    let mut value_ranges = Vec::new();
+    let mut last_def_node = None;
+
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(query, root, source);
+    while let Some((match_, capture_index)) = captures.next() {
+        let capture = match_.captures[*capture_index];
+        let range = capture.node.byte_range();
+
+        // Remove from the scope stack any scopes that have already ended.
+        while scope_stack.len() > 1
+            && range.start > result.scopes[scope_stack.last().unwrap().0].range.end
+        {
+            scope_stack.pop();
+        }
+
+        if Some(capture.index) == scope_capture_index {
+            let mut inherits = true;
+            for prop in query.property_settings(match_.pattern_index) {
+                if prop.key.as_ref() == "local.scope-inherits" {
+                    inherits = prop.value.as_ref().is_none_or(|r| r.as_ref() == "true");
+                }
+            }
+            result.scopes.push(LocalsScope {
+                range,
+                parent: scope_stack.last().map(|(scope, _)| *scope),
+                inherits,
+            });
+            scope_stack.push((result.scopes.len() - 1, Vec::new()));
+        } else if Some(capture.index) == def_capture_index {
+            last_def_node = Some(capture.node);
+            let Ok(name) = str::from_utf8(&source[range.clone()]) else {
+                continue;
+            };
+            let value_range = match_
+                .captures
+                .iter()
+                .find(|c| Some(c.index) == def_value_capture_index)
+                .map_or(0..0, |c| c.node.byte_range());
+            let (scope, defs) = scope_stack.last_mut().unwrap();
+            defs.push(result.definitions.len());
+            result.definitions.push(LocalsDefinition {
+                name: name.to_string(),
+                range,
+                scope: *scope,
+            });
+            value_ranges.push(value_range);
+        } else if Some(capture.index) == ref_capture_index && last_def_node != Some(capture.node) {
+            let Ok(name) = str::from_utf8(&source[range.clone()]) else {
+                continue;
+            };
+            let mut definition = None;
+            for (scope, defs) in scope_stack.iter().rev() {
+                definition = defs.iter().rev().copied().find(|&def| {
+                    result.definitions[def].name == name && range.start >= value_ranges[def].end
+                });
+                if definition.is_some() || !result.scopes[*scope].inherits {
+                    break;
+                }
+            }
+            result.references.push(LocalsReference {
+                name: name.to_string(),
+                range,
+                definition,
+            });
+        }
+    }
+    result
+}
+
 fn injection_for_match<'a>(
     config: &'a HighlightConfiguration,
     parent_name: Option<&'a str>,
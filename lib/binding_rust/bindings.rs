@@ -35,6 +35,11 @@ pub struct TSQueryCursor {
 pub struct TSLookaheadIterator {
     _unused: [u8; 0],
 }
+#[repr(C)]
+#[derive(Debug)]
+pub struct TSLexer {
+    _unused: [u8; 0],
+}
 pub type TSDecodeFunction = ::core::option::Option<
     unsafe extern "C" fn(string: *const u8, length: u32, code_point: *mut i32) -> u32,
 >;
@@ -83,6 +88,10 @@ pub struct TSParseState {
     pub payload: *mut ::core::ffi::c_void,
     pub current_byte_offset: u32,
     pub has_error: bool,
+    pub error_count: u32,
+    pub bytes_read: u32,
+    pub current_range_index: u32,
+    pub step_count: u32,
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -91,6 +100,33 @@ pub struct TSParseOptions {
     pub progress_callback:
         ::core::option::Option<unsafe extern "C" fn(state: *mut TSParseState) -> bool>,
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSAmbiguity {
+    pub symbol: TSSymbol,
+    pub start_byte: u32,
+    pub end_byte: u32,
+    pub chosen_children: *const TSSymbol,
+    pub chosen_child_count: u32,
+    pub rejected_children: *const TSSymbol,
+    pub rejected_child_count: u32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSTableUsage {
+    pub state: TSStateId,
+    pub is_reduction: bool,
+    pub symbol: TSSymbol,
+    pub production_id: u16,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSParsePhaseTimings {
+    pub lexing: u64,
+    pub reduction: u64,
+    pub error_recovery: u64,
+    pub balancing: u64,
+}
 pub const TSLogTypeParse: TSLogType = 0;
 pub const TSLogTypeLex: TSLogType = 1;
 pub type TSLogType = ::core::ffi::c_uint;
@@ -190,6 +226,33 @@ pub struct TSLanguageMetadata {
     pub minor_version: u8,
     pub patch_version: u8,
 }
+#[doc = " The functions that make up an external scanner, with the same signatures\n as the ones that are generated for a grammar's `scanner.c`. See\n [`ts_language_with_external_scanner`]."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSExternalScanner {
+    pub create: ::core::option::Option<unsafe extern "C" fn() -> *mut ::core::ffi::c_void>,
+    pub destroy: ::core::option::Option<unsafe extern "C" fn(payload: *mut ::core::ffi::c_void)>,
+    pub scan: ::core::option::Option<
+        unsafe extern "C" fn(
+            payload: *mut ::core::ffi::c_void,
+            lexer: *mut TSLexer,
+            valid_symbols: *const bool,
+        ) -> bool,
+    >,
+    pub serialize: ::core::option::Option<
+        unsafe extern "C" fn(
+            payload: *mut ::core::ffi::c_void,
+            buffer: *mut ::core::ffi::c_char,
+        ) -> ::core::ffi::c_uint,
+    >,
+    pub deserialize: ::core::option::Option<
+        unsafe extern "C" fn(
+            payload: *mut ::core::ffi::c_void,
+            buffer: *const ::core::ffi::c_char,
+            length: ::core::ffi::c_uint,
+        ),
+    >,
+}
 unsafe extern "C" {
     #[doc = " Create a new parser."]
     pub fn ts_parser_new() -> *mut TSParser;
@@ -218,6 +281,73 @@ unsafe extern "C" {
     #[doc = " Get the ranges of text that the parser will include when parsing.\n\n The returned pointer is owned by the parser. The caller should not free it\n or write to it. The length of the array will be written to the given\n `count` pointer."]
     pub fn ts_parser_included_ranges(self_: *const TSParser, count: *mut u32) -> *const TSRange;
 }
+unsafe extern "C" {
+    #[doc = " Check if the custom decode function stalled during the most recent parse.\n\n A decode function stalls when it reports that it consumed zero bytes from a\n non-empty chunk of text. When this happens, the parser treats the stall as\n the end of the input. If a stall occurred, this function returns `true` and\n writes the byte offset at which decoding stopped to the given `byte_offset`\n pointer. Otherwise, the input ended normally and this function returns\n `false`."]
+    pub fn ts_parser_did_decode_stall(self_: *const TSParser, byte_offset: *mut u32) -> bool;
+}
+unsafe extern "C" {
+    #[doc = " Check whether the parser calls the language's external scanner."]
+    pub fn ts_parser_use_external_scanner(self_: *const TSParser) -> bool;
+}
+unsafe extern "C" {
+    #[doc = " Enable or disable the language's external scanner.\n\n This is a debugging aid for determining whether a problem is caused by a\n grammar or by its external scanner. When the external scanner is disabled,\n the parser never calls its `scan` function, so none of the grammar's external\n tokens can be produced. For most grammars that use external tokens, this\n will cause syntax errors. The external scanner is enabled by default."]
+    pub fn ts_parser_set_use_external_scanner(self_: *mut TSParser, use_external_scanner: bool);
+}
+unsafe extern "C" {
+    #[doc = " Set the maximum number of syntax errors that the parser will recover from\n before halting.\n\n The parser counts each time it has to begin recovering from an error. Once\n that count reaches `max_errors`, the parse is halted in the same way as when\n the progress callback returns `true`: [`ts_parser_parse`] returns `NULL`, and\n the parser keeps its state, so you must call [`ts_parser_reset`] before\n parsing another document. A value of zero, which is the default, means that\n there is no limit."]
+    pub fn ts_parser_set_max_errors(self_: *mut TSParser, max_errors: u32);
+}
+unsafe extern "C" {
+    #[doc = " Set how many parse steps the parser takes between calls to the progress\n callback.\n\n A step is a unit of work like lexing a token or performing a reduction, so\n the number of steps taken for a given input, and the points at which the\n callback is called, are the same on every run. The total number of steps is\n reported in the `step_count` field of [`TSParseState`], which keeps growing\n across a resumed parse and is reset by [`ts_parser_reset`]. A value of zero,\n which is the default, calls the callback every 100 steps."]
+    pub fn ts_parser_set_progress_interval(self_: *mut TSParser, interval: u32);
+}
+unsafe extern "C" {
+    #[doc = " Enable or disable error recovery.\n\n By default, when the parser encounters a token that is not valid, it tries to\n recover by inserting missing tokens, or by returning to an earlier state in\n which the token would be valid, so that the rest of the input can be parsed\n normally. With error recovery disabled, the parser does neither of these: it\n skips every remaining token, and the finished tree's root is a single `ERROR`\n node that contains the nodes parsed before the first error, followed by the\n rest of the input. Valid input is parsed the same way in both modes, so this\n is useful for tools that only process valid files and don't need a detailed\n tree for invalid ones."]
+    pub fn ts_parser_set_error_recovery(self_: *mut TSParser, error_recovery: bool);
+}
+unsafe extern "C" {
+    #[doc = " Count each carriage return that is followed by a newline as zero columns.\n\n By default, a `\\r\\n` line ending is two bytes long, so a node that ends with\n the `\\r`, such as a comment whose pattern includes everything up to the `\\n`,\n ends one column after the end of the line's visible text. Editors that hide\n the `\\r` don't count it, so when this is enabled, the columns of all points\n in the tree are computed the same way. Byte offsets are not affected. The\n points in any edits and included ranges that are given to the parser must then\n be computed the same way too. This is disabled by default."]
+    pub fn ts_parser_set_report_lf_positions(self_: *mut TSParser, report_lf_positions: bool);
+}
+unsafe extern "C" {
+    #[doc = " Set the maximum number of bytes of input that the parser will read.\n\n The parser treats this byte offset as the end of the input, even if the\n input's `read` function would return more text, so the parse always\n finishes. This protects against `read` functions that never signal the end of\n the input. The limit applies to the byte offsets that are requested from the\n input, so it is the same when the parser re-reads text during an incremental\n parse. If the limit falls within a multi-byte character, that character is\n truncated. A value of zero, which is the default, means that there is no\n limit."]
+    pub fn ts_parser_set_max_input_bytes(self_: *mut TSParser, max_input_bytes: u32);
+}
+unsafe extern "C" {
+    #[doc = " Set the largest column that the lexer reports to scanners and lex functions.\n\n Grammars that depend on column values, such as layout-sensitive grammars whose\n external scanners call `get_column`, receive the number of characters between\n the start of the current line and the current position. This number can't\n overflow, since it is never greater than the line's length in bytes, but\n computing it can require re-reading the line from its start, which takes time\n proportional to the length of the line. On pathological input, such as\n minified code on a single line, this makes column-sensitive grammars very\n slow.\n\n When a limit is set, any column greater than it is reported as the limit\n itself, and a line is not re-read when its length in bytes guarantees that the\n column is beyond the limit. A value of zero, which is the default, means that\n there is no limit."]
+    pub fn ts_parser_set_max_column(self_: *mut TSParser, max_column: u32);
+}
+unsafe extern "C" {
+    #[doc = " Set a function that the parser calls whenever it chooses between two\n different trees for the same node.\n\n This happens when a grammar is ambiguous: more than one sequence of children\n can be reduced to the same symbol over the same range of text, and the parser\n has to pick one of them based on dynamic precedence or, failing that, on an\n arbitrary but deterministic comparison. The callback receives the node's\n symbol and byte range, and the symbols of the children of the chosen and\n rejected alternatives. The child arrays are only valid for the duration of\n the call. Choices between alternatives that contain syntax errors are not\n reported. Pass `NULL` for the callback to stop reporting ambiguities."]
+    pub fn ts_parser_set_ambiguity_callback(
+        self_: *mut TSParser,
+        payload: *mut ::core::ffi::c_void,
+        callback: ::core::option::Option<
+            unsafe extern "C" fn(payload: *mut ::core::ffi::c_void, ambiguity: *const TSAmbiguity),
+        >,
+    );
+}
+unsafe extern "C" {
+    #[doc = " Set a function that the parser calls for every parse table action that it\n performs.\n\n The callback receives the parse state in which the action was taken. For\n reductions, it also receives the reduced symbol and the production id of the\n reduction, and `is_reduction` is set to `true`. This makes it possible to\n measure how much of a grammar's parse table is exercised by a set of inputs.\n Pass `NULL` for the callback to stop reporting table usage."]
+    pub fn ts_parser_set_table_usage_callback(
+        self_: *mut TSParser,
+        payload: *mut ::core::ffi::c_void,
+        callback: ::core::option::Option<
+            unsafe extern "C" fn(payload: *mut ::core::ffi::c_void, usage: *const TSTableUsage),
+        >,
+    );
+}
+unsafe extern "C" {
+    #[doc = " Enable or disable profiling of the parser's individual phases.\n\n When profiling is enabled, the parser measures the processor time that it\n spends lexing, performing reductions, recovering from errors, and balancing\n the finished tree. The measurements are reset whenever a new parse begins,\n and they accumulate across resumptions of a parse that was cancelled.\n Profiling is disabled by default, and adds a small overhead to each phase."]
+    pub fn ts_parser_set_phase_profiling(self_: *mut TSParser, enabled: bool);
+}
+unsafe extern "C" {
+    #[doc = " Get the time that the most recent parse spent in each of its phases.\n\n If phase profiling is enabled, this function writes the measurements, in\n microseconds, to the given `timings` pointer and returns `true`. Otherwise,\n it returns `false`. See [`ts_parser_set_phase_profiling`]."]
+    pub fn ts_parser_phase_timings(
+        self_: *const TSParser,
+        timings: *mut TSParsePhaseTimings,
+    ) -> bool;
+}
 unsafe extern "C" {
     #[doc = " Use the parser to parse some source code and create a syntax tree.\n\n If you are parsing this document for the first time, pass `NULL` for the\n `old_tree` parameter. Otherwise, if you have already parsed an earlier\n version of this document and the document has since been edited, pass the\n previous syntax tree so that the unchanged parts of it can be reused.\n This will save time and memory. For this to work correctly, you must have\n already edited the old syntax tree using the [`ts_tree_edit`] function in a\n way that exactly matches the source code changes.\n\n The [`TSInput`] parameter lets you specify how to read the text. It has the\n following three fields:\n 1. [`read`]: A function to retrieve a chunk of text at a given byte offset\n    and (row, column) position. The function should return a pointer to the\n    text and write its length to the [`bytes_read`] pointer. The parser does\n    not take ownership of this buffer; it just borrows it until it has\n    finished reading it. The function should write a zero value to the\n    [`bytes_read`] pointer to indicate the end of the document.\n 2. [`payload`]: An arbitrary pointer that will be passed to each invocation\n    of the [`read`] function.\n 3. [`encoding`]: An indication of how the text is encoded. Either\n    `TSInputEncodingUTF8`, `TSInputEncodingUTF16LE`, `TSInputEncoding16BE`,\n    or `TSInputEncodingCustom`.\n 4. [`decode`]: A function to read one code point from the given input. This\n    function should return the number of bytes consumed and write the code point\n    to the [`code_point`] pointer, or write -1 if the input is invalid.\n\n This function returns a syntax tree on success, and `NULL` on failure. There\n are two possible reasons for failure:\n 1. The parser does not have a language assigned. Check for this using the\n    [`ts_parser_language`] function.\n 2. Parsing was cancelled due to the progress callback returning true. This callback\n    is passed in [`ts_parser_parse_with_options`] inside the [`TSParseOptions`] struct.\n\n [`read`]: TSInput::read\n [`payload`]: TSInput::payload\n [`encoding`]: TSInput::encoding\n [`bytes_read`]: TSInput::read\n [`decode`]: TSInput::decode\n [`code_point`]: TSDecodeFunction::code_point"]
     pub fn ts_parser_parse(
@@ -258,6 +388,14 @@ unsafe extern "C" {
     #[doc = " Instruct the parser to start the next parse from the beginning.\n\n If the parser previously failed because of the progress callback, then\n by default, it will resume where it left off on the next call to\n [`ts_parser_parse`] or other parsing functions. If you don't want to resume,\n and instead intend to use this parser to parse some other document, you must\n call [`ts_parser_reset`] first."]
     pub fn ts_parser_reset(self_: *mut TSParser);
 }
+unsafe extern "C" {
+    #[doc = " Check whether the parser has state left over from a parse that was halted\n by the progress callback.\n\n If this returns `true`, the next call to [`ts_parser_parse`] or other\n parsing functions will resume that parse, unless [`ts_parser_reset`] is\n called first."]
+    pub fn ts_parser_is_resumable(self_: *const TSParser) -> bool;
+}
+unsafe extern "C" {
+    #[doc = " Get the position that a halted parse had reached.\n\n If the parser has state left over from a halted parse, this function writes\n the byte offset that the parser had advanced to when it was halted to the\n given `byte_offset` pointer and returns `true`. Text before this offset has\n already been parsed, and resuming the parse will continue from there.\n Otherwise, this function returns `false`."]
+    pub fn ts_parser_halted_byte_offset(self_: *const TSParser, byte_offset: *mut u32) -> bool;
+}
 unsafe extern "C" {
     #[doc = " Set the logger that a parser should use during parsing.\n\n The parser does not take ownership over the logger payload. If a logger was\n previously assigned, the caller is responsible for releasing any memory\n owned by the previous logger."]
     pub fn ts_parser_set_logger(self_: *mut TSParser, logger: TSLogger);
@@ -274,6 +412,10 @@ unsafe extern "C" {
     #[doc = " Create a shallow copy of the syntax tree. This is very fast.\n\n You need to copy a syntax tree in order to use it on more than one thread at\n a time, as syntax trees are not thread safe."]
     pub fn ts_tree_copy(self_: *const TSTree) -> *mut TSTree;
 }
+unsafe extern "C" {
+    #[doc = " Create a deep copy of the syntax tree. This takes time proportional to the\n number of nodes in the tree.\n\n Unlike the copies made by [`ts_tree_copy`], which share their nodes with the\n original tree, the resulting tree does not share any memory with the\n original."]
+    pub fn ts_tree_deep_copy(self_: *const TSTree) -> *mut TSTree;
+}
 unsafe extern "C" {
     #[doc = " Delete the syntax tree, freeing all of the memory that it used."]
     pub fn ts_tree_delete(self_: *mut TSTree);
@@ -298,6 +440,10 @@ unsafe extern "C" {
     #[doc = " Get the array of included ranges that was used to parse the syntax tree.\n\n The returned pointer must be freed by the caller."]
     pub fn ts_tree_included_ranges(self_: *const TSTree, length: *mut u32) -> *mut TSRange;
 }
+unsafe extern "C" {
+    #[doc = " Get the array of included ranges that was used to parse the syntax tree,\n without copying it.\n\n The returned pointer is owned by the tree, and is valid for as long as the\n tree is neither edited nor deleted."]
+    pub fn ts_tree_included_ranges_view(self_: *const TSTree, length: *mut u32) -> *const TSRange;
+}
 unsafe extern "C" {
     #[doc = " Edit the syntax tree to keep it in sync with source code that has been\n edited.\n\n You must describe the edit both in terms of byte offsets and in terms of\n (row, column) coordinates.\n\n The edit's `start_byte` must be less than or equal to its `old_end_byte`,\n and its `start_point` must be less than or equal to its `old_end_point`."]
     pub fn ts_tree_edit(self_: *mut TSTree, edit: *const TSInputEdit);
@@ -354,6 +500,20 @@ unsafe extern "C" {
     #[doc = " Get an S-expression representing the node as a string.\n\n This string is allocated with `malloc` and the caller is responsible for\n freeing it using `free`."]
     pub fn ts_node_string(self_: TSNode) -> *mut ::core::ffi::c_char;
 }
+unsafe extern "C" {
+    #[doc = " Write an S-expression representing the node, without allocating it as a\n single string.\n\n The S-expression is passed to the `write` function in pieces, which are not\n null-terminated. It is the same as the string returned by [`ts_node_string`]\n once all of the pieces are concatenated. If `write` returns `false`, no more\n pieces are written."]
+    pub fn ts_node_write_string(
+        self_: TSNode,
+        payload: *mut ::core::ffi::c_void,
+        write: ::core::option::Option<
+            unsafe extern "C" fn(
+                payload: *mut ::core::ffi::c_void,
+                text: *const ::core::ffi::c_char,
+                length: u32,
+            ) -> bool,
+        >,
+    );
+}
 unsafe extern "C" {
     #[doc = " Check if the node is null. Functions like [`ts_node_child`] and\n [`ts_node_next_sibling`] will return a null node to indicate that no such node\n was found."]
     pub fn ts_node_is_null(self_: TSNode) -> bool;
@@ -600,6 +760,19 @@ unsafe extern "C" {
     #[doc = " Create a copy of a query."]
     pub fn ts_query_copy(self_: *const TSQuery) -> *mut TSQuery;
 }
+unsafe extern "C" {
+    #[doc = " Serialize a compiled query into a buffer of bytes, which can later be passed\n to [`ts_query_deserialize`] to recreate the query without recompiling it.\n\n The returned buffer is allocated with the library's allocator, and the\n caller is responsible for freeing it. The length of the buffer is written\n to the given `length` pointer. The data is only valid for the same version\n of this library, built for the same platform."]
+    pub fn ts_query_serialize(self_: *const TSQuery, length: *mut u32) -> *mut ::core::ffi::c_char;
+}
+unsafe extern "C" {
+    #[doc = " Recreate a query from a buffer of bytes that was produced by\n [`ts_query_serialize`].\n\n If the data was produced for a different language, this returns `NULL` and\n writes `TSQueryErrorLanguage` to the `error_type` parameter. If the data is\n otherwise invalid, this returns `NULL` and writes `TSQueryErrorSyntax` to\n the `error_type` parameter. The data must have been produced by\n [`ts_query_serialize`]; arbitrary bytes are not fully validated."]
+    pub fn ts_query_deserialize(
+        language: *const TSLanguage,
+        data: *const ::core::ffi::c_char,
+        length: u32,
+        error_type: *mut TSQueryError,
+    ) -> *mut TSQuery;
+}
 unsafe extern "C" {
     #[doc = " Get the number of patterns, captures, or string literals in the query."]
     pub fn ts_query_pattern_count(self_: *const TSQuery) -> u32;
@@ -635,6 +808,12 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn ts_query_is_pattern_guaranteed_at_step(self_: *const TSQuery, byte_offset: u32) -> bool;
 }
+unsafe extern "C" {
+    pub fn ts_query_pattern_depth(self_: *const TSQuery, pattern_index: u32) -> u32;
+}
+unsafe extern "C" {
+    pub fn ts_query_is_pattern_wildcard_rooted(self_: *const TSQuery, pattern_index: u32) -> bool;
+}
 unsafe extern "C" {
     #[doc = " Get the name and length of one of the query's captures, or one of the\n query's string literals. Each capture and string is associated with a\n numeric id based on the order that it appeared in the query's source."]
     pub fn ts_query_capture_name_for_id(
@@ -691,6 +870,15 @@ unsafe extern "C" {
         query_options: *const TSQueryCursorOptions,
     );
 }
+unsafe extern "C" {
+    #[doc = " Start running a single pattern of a given query on a given node.\n\n This behaves like [`ts_query_cursor_exec`], except that the cursor only\n starts matches for the pattern with the given index. The other patterns are\n never attempted, so no work is spent on them."]
+    pub fn ts_query_cursor_exec_for_pattern(
+        self_: *mut TSQueryCursor,
+        query: *const TSQuery,
+        pattern_index: u32,
+        node: TSNode,
+    );
+}
 unsafe extern "C" {
     #[doc = " Manage the maximum number of in-progress matches allowed by this query\n cursor.\n\n Query cursors have an optional maximum capacity for storing lists of\n in-progress captures. If this capacity is exceeded, then the\n earliest-starting match will silently be dropped to make room for further\n matches. This maximum capacity is optional — by default, query cursors allow\n any number of pending matches, dynamically allocating new space for them as\n needed as the query is executed."]
     pub fn ts_query_cursor_did_exceed_match_limit(self_: *const TSQueryCursor) -> bool;
@@ -717,6 +905,14 @@ unsafe extern "C" {
         end_point: TSPoint,
     ) -> bool;
 }
+unsafe extern "C" {
+    #[doc = " Get the range of (row, column) positions in which the query will be executed,\n as set by [`ts_query_cursor_set_point_range`]."]
+    pub fn ts_query_cursor_point_range(
+        self_: *const TSQueryCursor,
+        start_point: *mut TSPoint,
+        end_point: *mut TSPoint,
+    );
+}
 unsafe extern "C" {
     #[doc = " Set the byte range within which all matches must be fully contained.\n\n Set the range of bytes in which matches will be searched for. In contrast to\n `ts_query_cursor_set_byte_range`, this will restrict the query cursor to only return\n matches where _all_ nodes are _fully_ contained within the given range. Both functions\n can be used together, e.g. to search for any matches that intersect line 5000, as\n long as they are fully contained within lines 4500-5500\n\n NOTE: An `end_byte` of zero is interpreted as `UINT32_MAX`, making the range\n unbounded."]
     pub fn ts_query_cursor_set_containing_byte_range(
@@ -765,6 +961,10 @@ unsafe extern "C" {
     #[doc = " Get the number of distinct node types in the language."]
     pub fn ts_language_symbol_count(self_: *const TSLanguage) -> u32;
 }
+unsafe extern "C" {
+    #[doc = " Get the numerical id of the grammar's start rule.\n\n This is the node type of the root node of any tree that is parsed without\n errors, such as `source_file` or `program`. It is found in the parse table,\n and this returns zero if the table has no start rule."]
+    pub fn ts_language_start_symbol(self_: *const TSLanguage) -> TSSymbol;
+}
 unsafe extern "C" {
     #[doc = " Get the number of valid states in this language."]
     pub fn ts_language_state_count(self_: *const TSLanguage) -> u32;
@@ -809,6 +1009,14 @@ unsafe extern "C" {
         length: *mut u32,
     ) -> *const TSSymbol;
 }
+unsafe extern "C" {
+    #[doc = " Get a list of all field ids that can appear on nodes of the given symbol.\n\n The list is computed from the language's parse table, so it includes the\n fields that the node inherits from hidden child rules. The ids are sorted in\n ascending order and the list's length is written to `length`. The caller is\n responsible for freeing the returned array using `free`. Symbols that only\n appear as the target of an alias have no productions of their own, so the\n result for them is empty."]
+    pub fn ts_language_fields_for_symbol(
+        self_: *const TSLanguage,
+        symbol: TSSymbol,
+        length: *mut u32,
+    ) -> *mut TSFieldId;
+}
 unsafe extern "C" {
     #[doc = " Get a node type string for the given numerical id."]
     pub fn ts_language_symbol_name(
@@ -820,6 +1028,18 @@ unsafe extern "C" {
     #[doc = " Check whether the given node type id belongs to named nodes, anonymous nodes,\n or hidden nodes.\n\n See also [`ts_node_is_named`]. Hidden nodes are never returned from the API."]
     pub fn ts_language_symbol_type(self_: *const TSLanguage, symbol: TSSymbol) -> TSSymbolType;
 }
+unsafe extern "C" {
+    #[doc = " Check whether the given node type id is one of the grammar's *extras*, like\n comments, which can appear anywhere in the document.\n\n Extras that are only matched by the lexer and never produce nodes, such as\n whitespace, do not have node type ids. See also [`ts_node_is_extra`]."]
+    pub fn ts_language_symbol_is_extra(self_: *const TSLanguage, symbol: TSSymbol) -> bool;
+}
+unsafe extern "C" {
+    #[doc = " Create a copy of the given language that uses the given functions as its\n external scanner, in place of the scanner that was compiled with it.\n\n The grammar's `externals` must contain exactly `external_token_count`\n tokens, which are the tokens indexed by the `valid_symbols` array that is\n passed to `scan`. This returns `NULL` if the count does not match, or if the\n language was loaded from Wasm.\n\n The copy shares its parse tables with the original language, which must\n stay loaded for as long as the copy is used. Unlike the original, the copy\n is allocated, so it must be freed with [`ts_language_delete`]. Parsers,\n trees and queries keep their own reference to it, so it can be deleted\n while they are still in use."]
+    pub fn ts_language_with_external_scanner(
+        self_: *const TSLanguage,
+        scanner: TSExternalScanner,
+        external_token_count: u32,
+    ) -> *const TSLanguage;
+}
 unsafe extern "C" {
     #[doc = " Get the ABI version number for this language. This version number is used\n to ensure that languages were generated by a compatible version of\n Tree-sitter.\n\n See also [`ts_parser_set_language`]."]
     pub fn ts_language_abi_version(self_: *const TSLanguage) -> u32;
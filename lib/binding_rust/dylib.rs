@@ -0,0 +1,73 @@
+use std::{error, fmt, mem, path::Path};
+
+use libloading::{Library, Symbol};
+
+use crate::{AbiMismatch, Language};
+
+/// An error that occurred in [`Language::load_from_library`].
+#[derive(Debug)]
+pub enum LoadError {
+    /// The dynamic library could not be loaded.
+    Library(libloading::Error),
+    /// The dynamic library does not export the given symbol.
+    Symbol {
+        symbol: String,
+        error: libloading::Error,
+    },
+    /// The language's ABI version is not supported by this library.
+    Abi(AbiMismatch),
+}
+
+impl Language {
+    /// Load a language from a dynamic library, like `libtree-sitter-json.so`.
+    ///
+    /// The `symbol` is the name of the function that returns the language,
+    /// which is `tree_sitter_<name>` for languages generated by the Tree-sitter
+    /// CLI. The language's ABI version is checked with
+    /// [`Language::is_compatible`] before it is returned.
+    ///
+    /// The library is never unloaded, because the returned language, and any
+    /// trees that are parsed with it, refer to its static data.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization code, and the symbol is
+    /// called as an `extern "C" fn() -> *const TSLanguage` without any further
+    /// checks, so the library must be a Tree-sitter grammar.
+    pub unsafe fn load_from_library(path: &Path, symbol: &str) -> Result<Self, LoadError> {
+        let library = unsafe { Library::new(path) }.map_err(LoadError::Library)?;
+        let language = unsafe {
+            let language_fn = library
+                .get::<Symbol<unsafe extern "C" fn() -> Self>>(symbol.as_bytes())
+                .map_err(|error| LoadError::Symbol {
+                    symbol: symbol.to_string(),
+                    error,
+                })?;
+            language_fn()
+        };
+        language.is_compatible().map_err(LoadError::Abi)?;
+        mem::forget(library);
+        Ok(language)
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Library(error) => write!(f, "Failed to load the library: {error}"),
+            Self::Symbol { symbol, error } => {
+                write!(f, "Failed to find the symbol `{symbol}`: {error}")
+            }
+            Self::Abi(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Library(error) | Self::Symbol { error, .. } => Some(error),
+            Self::Abi(error) => Some(error),
+        }
+    }
+}
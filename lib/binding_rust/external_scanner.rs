@@ -0,0 +1,191 @@
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+use core::{
+    ffi::{c_char, c_uint, c_void},
+    slice,
+};
+
+use crate::{Language, ffi};
+
+/// The size of the buffer that is passed to [`ExternalScanner::serialize`].
+pub const SERIALIZATION_BUFFER_SIZE: usize = 1024;
+
+/// The lexer that is passed to [`ExternalScanner::scan`].
+///
+/// This has the same layout as the `TSLexer` struct that external scanners
+/// written in C receive.
+#[repr(C)]
+pub struct ScannerLexer {
+    lookahead: i32,
+    result_symbol: u16,
+    advance: unsafe extern "C" fn(*mut Self, bool),
+    mark_end: unsafe extern "C" fn(*mut Self),
+    get_column: unsafe extern "C" fn(*mut Self) -> u32,
+    is_at_included_range_start: unsafe extern "C" fn(*const Self) -> bool,
+    eof: unsafe extern "C" fn(*const Self) -> bool,
+    log: *const c_void,
+}
+
+impl ScannerLexer {
+    /// Get the current lookahead character.
+    ///
+    /// This is `'\0'` at the end of the input, so use [`eof`](Self::eof) to
+    /// distinguish the end of the input from a null character. It is `None` if
+    /// the input could not be decoded.
+    #[must_use]
+    pub fn lookahead(&self) -> Option<char> {
+        char::from_u32(u32::try_from(self.lookahead).ok()?)
+    }
+
+    /// Advance to the next character. If `skip` is true, the current character
+    /// is treated as whitespace, and is not included in the token.
+    pub fn advance(&mut self, skip: bool) {
+        unsafe { (self.advance)(self, skip) }
+    }
+
+    /// Mark the end of the recognized token. By default, the token ends at the
+    /// current position, so this is only needed when looking ahead past the end
+    /// of the token.
+    pub fn mark_end(&mut self) {
+        unsafe { (self.mark_end)(self) }
+    }
+
+    /// Get the current column, which is computed by counting the characters
+    /// since the start of the line.
+    pub fn column(&mut self) -> u32 {
+        unsafe { (self.get_column)(self) }
+    }
+
+    /// Check whether the lexer is at the start of one of the parser's included
+    /// ranges.
+    #[must_use]
+    pub fn is_at_included_range_start(&self) -> bool {
+        unsafe { (self.is_at_included_range_start)(self) }
+    }
+
+    /// Check whether the lexer is at the end of the input.
+    #[must_use]
+    pub fn eof(&self) -> bool {
+        unsafe { (self.eof)(self) }
+    }
+
+    /// Set the token that was recognized, as an index into the grammar's
+    /// `externals`.
+    pub const fn set_result_symbol(&mut self, index: u16) {
+        self.result_symbol = index;
+    }
+}
+
+/// An external scanner implemented in Rust, which can be used in place of a
+/// grammar's compiled `scanner.c` with [`Language::with_external_scanner`].
+///
+/// Each method corresponds to one of the functions of a C external scanner.
+/// The scanner is created with [`create`](Self::create) for each parser that
+/// uses the language, and dropped in place of the C `destroy` function.
+pub trait ExternalScanner: Sized {
+    /// The number of tokens in the grammar's `externals`. This is the length
+    /// of the `valid_symbols` slice that is passed to [`scan`](Self::scan).
+    const TOKEN_COUNT: usize;
+
+    fn create() -> Self;
+
+    /// Try to recognize one of the tokens whose entry in `valid_symbols` is
+    /// true, returning whether a token was recognized. The token is reported
+    /// with [`ScannerLexer::set_result_symbol`].
+    fn scan(&mut self, lexer: &mut ScannerLexer, valid_symbols: &[bool]) -> bool;
+
+    /// Write the scanner's state into `buffer`, returning the number of bytes
+    /// that were written.
+    fn serialize(&self, buffer: &mut [u8]) -> usize;
+
+    /// Restore the scanner's state from bytes that were written by
+    /// [`serialize`](Self::serialize). The buffer is empty when the scanner
+    /// should be reset to its initial state.
+    fn deserialize(&mut self, buffer: &[u8]);
+}
+
+impl Language {
+    /// Create a copy of this language that uses the given Rust type as its
+    /// external scanner, instead of the scanner that was compiled with it.
+    ///
+    /// This allows prototyping an external scanner without a C toolchain. The
+    /// language must still have been generated from a grammar that declares
+    /// its `externals`, and compiled with a scanner, even a stub one, so that
+    /// it links. Returns `None` if the grammar does not have exactly
+    /// [`ExternalScanner::TOKEN_COUNT`] external tokens, or if the language was
+    /// loaded from Wasm.
+    ///
+    /// The returned language is freed when it is dropped, once no parser,
+    /// tree or query still uses it.
+    ///
+    /// # Safety
+    ///
+    /// The returned language shares its parse tables with this one, so they
+    /// must stay valid for as long as the returned language, or any parser,
+    /// tree or query that uses it, is alive. This always holds for languages
+    /// that are linked into the program. A language that was loaded from a
+    /// dynamic library must not be unloaded until then.
+    #[doc(alias = "ts_language_with_external_scanner")]
+    #[must_use]
+    pub unsafe fn with_external_scanner<S: ExternalScanner>(&self) -> Option<Self> {
+        let scanner = ffi::TSExternalScanner {
+            create: Some(create::<S>),
+            destroy: Some(destroy::<S>),
+            scan: Some(scan::<S>),
+            serialize: Some(serialize::<S>),
+            deserialize: Some(deserialize::<S>),
+        };
+        let ptr = unsafe {
+            ffi::ts_language_with_external_scanner(self.0, scanner, S::TOKEN_COUNT as u32)
+        };
+        (!ptr.is_null()).then_some(Self(ptr))
+    }
+}
+
+unsafe extern "C" fn create<S: ExternalScanner>() -> *mut c_void {
+    Box::into_raw(Box::new(S::create())).cast::<c_void>()
+}
+
+unsafe extern "C" fn destroy<S: ExternalScanner>(payload: *mut c_void) {
+    drop(unsafe { Box::from_raw(payload.cast::<S>()) });
+}
+
+unsafe extern "C" fn scan<S: ExternalScanner>(
+    payload: *mut c_void,
+    lexer: *mut ffi::TSLexer,
+    valid_symbols: *const bool,
+) -> bool {
+    unsafe {
+        let scanner = &mut *payload.cast::<S>();
+        let lexer = &mut *lexer.cast::<ScannerLexer>();
+        let valid_symbols = slice::from_raw_parts(valid_symbols, S::TOKEN_COUNT);
+        scanner.scan(lexer, valid_symbols)
+    }
+}
+
+unsafe extern "C" fn serialize<S: ExternalScanner>(
+    payload: *mut c_void,
+    buffer: *mut c_char,
+) -> c_uint {
+    unsafe {
+        let scanner = &*payload.cast::<S>();
+        let buffer = slice::from_raw_parts_mut(buffer.cast::<u8>(), SERIALIZATION_BUFFER_SIZE);
+        scanner.serialize(buffer).min(SERIALIZATION_BUFFER_SIZE) as c_uint
+    }
+}
+
+unsafe extern "C" fn deserialize<S: ExternalScanner>(
+    payload: *mut c_void,
+    buffer: *const c_char,
+    length: c_uint,
+) {
+    unsafe {
+        let scanner = &mut *payload.cast::<S>();
+        let buffer = if length == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(buffer.cast::<u8>(), length as usize)
+        };
+        scanner.deserialize(buffer);
+    }
+}
@@ -2,13 +2,23 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod external_scanner;
 pub mod ffi;
 mod util;
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 #[cfg(not(feature = "std"))]
-use alloc::{boxed::Box, format, string::String, string::ToString, vec::Vec};
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    string::ToString,
+    vec,
+    vec::Vec,
+};
 use core::{
     ffi::{CStr, c_char, c_void},
     fmt::{self, Write},
@@ -19,17 +29,29 @@ use core::{
     ops::{self, ControlFlow, Deref},
     ptr::{self, NonNull},
     slice, str,
+    time::Duration,
 };
-#[cfg(feature = "std")]
-use std::error;
 #[cfg(all(unix, feature = "std"))]
 use std::os::fd::AsRawFd;
 #[cfg(all(windows, feature = "std"))]
 use std::os::windows::io::AsRawHandle;
+#[cfg(feature = "std")]
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    error,
+    time::Instant,
+};
 
+pub use external_scanner::{ExternalScanner, SERIALIZATION_BUFFER_SIZE, ScannerLexer};
 pub use streaming_iterator::{StreamingIterator, StreamingIteratorMut};
 use tree_sitter_language::LanguageFn;
 
+#[cfg(feature = "dylib")]
+mod dylib;
+#[cfg(feature = "dylib")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dylib")))]
+pub use dylib::LoadError;
 #[cfg(feature = "wasm")]
 mod wasm_language;
 #[cfg(feature = "wasm")]
@@ -70,6 +92,7 @@ pub struct LanguageRef<'a>(*const ffi::TSLanguage, PhantomData<&'a ()>);
 /// be incompatible with existing queries when upgrading between major versions, or minor versions
 /// if it's in zerover.
 #[doc(alias = "TSLanguageMetadata")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct LanguageMetadata {
     pub major_version: u8,
     pub minor_version: u8,
@@ -86,13 +109,35 @@ impl From<ffi::TSLanguageMetadata> for LanguageMetadata {
     }
 }
 
+impl fmt::Display for LanguageMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.major_version, self.minor_version, self.patch_version
+        )
+    }
+}
+
 /// A tree that represents the syntactic structure of a source code file.
+///
+/// Cloning a tree is cheap: the clone shares its nodes with the original, and
+/// only the reference counts of the shared nodes are updated. Editing either
+/// tree with [`Tree::edit`] copies the affected nodes first, so the edit is not
+/// visible through the other tree. Use [`Tree::deep_copy`] when the copy must
+/// not share any memory with the original.
 #[doc(alias = "TSTree")]
 pub struct Tree(NonNull<ffi::TSTree>);
 
 /// A position in a multi-line text document, in terms of rows and columns.
 ///
-/// Rows and columns are zero-based.
+/// Rows and columns are zero-based. A point is displayed as `(row, column)`,
+/// like the positions in the CLI's output, so the first character of a document
+/// is at `(0, 0)`.
+///
+/// Points are ordered by row, and then by column. A point can also be used as
+/// the extent of a span of text, which can be added to another point with `+`
+/// to find where that span ends; see [`Point::saturating_sub`] for the inverse.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Point {
     pub row: usize,
@@ -101,7 +146,15 @@ pub struct Point {
 
 /// A range of positions in a multi-line text document, both in terms of bytes
 /// and of rows and columns.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+///
+/// Ranges are ordered by their start byte, then by their end byte. This is the
+/// order required by [`Parser::set_included_ranges`], so a list of disjoint
+/// ranges can be sorted before it is passed to the parser.
+///
+/// A range is displayed as its byte range, written as `start..end` like the
+/// input to [`parse_byte_range`], followed by its zero-based start and end
+/// points, like `10..42 (0, 10) - (2, 3)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Range {
     pub start_byte: usize,
     pub end_byte: usize,
@@ -109,6 +162,17 @@ pub struct Range {
     pub end_point: Point,
 }
 
+/// A mapping from byte offsets to character offsets within a string, where a
+/// character is a Unicode scalar value.
+///
+/// Build this once for the text that was parsed, and then use it with
+/// [`Node::char_range`] to convert node positions for tools that work in
+/// character offsets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CharIndexMap {
+    char_start_bytes: Vec<usize>,
+}
+
 /// A summary of a change to a text document.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct InputEdit {
@@ -160,6 +224,126 @@ impl InputEdit {
 
         *range = ts_range.into();
     }
+
+    /// Check that this edit is well-formed.
+    ///
+    /// The old and new end of the edit must not come before its start, and each
+    /// end position must be consistent with the corresponding byte offsets: it
+    /// must not come before the start position, it cannot span more rows than
+    /// bytes, and if it is on the same row as the start position, its column
+    /// must differ by the same number of bytes.
+    pub fn validate(&self) -> Result<(), InputEditError> {
+        fn is_consistent(start: (usize, Point), end: (usize, Point)) -> bool {
+            let ((start_byte, start_point), (end_byte, end_point)) = (start, end);
+            let byte_count = end_byte - start_byte;
+            if end_point < start_point {
+                false
+            } else if end_point.row == start_point.row {
+                end_point.column - start_point.column == byte_count
+            } else {
+                end_point.row - start_point.row <= byte_count
+            }
+        }
+
+        let start = (self.start_byte, self.start_position);
+        if self.old_end_byte < self.start_byte {
+            return Err(InputEditError::OldEndBeforeStart);
+        }
+        if self.new_end_byte < self.start_byte {
+            return Err(InputEditError::NewEndBeforeStart);
+        }
+        if !is_consistent(start, (self.old_end_byte, self.old_end_position)) {
+            return Err(InputEditError::InconsistentOldEndPosition);
+        }
+        if !is_consistent(start, (self.new_end_byte, self.new_end_position)) {
+            return Err(InputEditError::InconsistentNewEndPosition);
+        }
+        Ok(())
+    }
+}
+
+/// A change to a text document, described as a replacement of some bytes.
+///
+/// Unlike an [`InputEdit`], an `Edit` does not include row/column positions,
+/// so it can be created without knowing the contents of the document. Use
+/// [`apply_edit`] to apply it to a document and its syntax tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    /// The byte offset at which the edit begins.
+    pub position: usize,
+    /// The number of bytes removed, starting at `position`.
+    pub deleted_length: usize,
+    /// The text inserted at `position`.
+    pub inserted_text: Vec<u8>,
+}
+
+impl Edit {
+    /// Get an edit that undoes this one.
+    ///
+    /// The `source` must be the document *before* this edit is applied, so
+    /// that the deleted text can be restored.
+    pub fn invert(&self, source: &[u8]) -> Result<Self, EditError> {
+        let end = self
+            .position
+            .checked_add(self.deleted_length)
+            .ok_or(EditError {
+                offset: self.position,
+            })?;
+        let removed_content = source
+            .get(self.position..end)
+            .ok_or(EditError { offset: end })?;
+        Ok(Self {
+            position: self.position,
+            deleted_length: self.inserted_text.len(),
+            inserted_text: removed_content.to_vec(),
+        })
+    }
+}
+
+/// Apply an [`Edit`] to a document's source code and to its syntax tree.
+///
+/// This replaces the edited bytes in `source`, and then calls [`Tree::edit`]
+/// with the equivalent [`InputEdit`], whose row/column positions are computed
+/// from the source code. The `InputEdit` is returned, so that it can be used
+/// to update other positions that refer to the document.
+pub fn apply_edit(
+    tree: &mut Tree,
+    source: &mut Vec<u8>,
+    edit: &Edit,
+) -> Result<InputEdit, EditError> {
+    #[expect(
+        clippy::naive_bytecount,
+        reason = "edits are small and infrequent, and this crate has no bytecount dependency"
+    )]
+    fn position_for_offset(source: &[u8], offset: usize) -> Result<Point, EditError> {
+        let text = source.get(..offset).ok_or(EditError { offset })?;
+        let row = text.iter().filter(|&&byte| byte == b'\n').count();
+        let column = text.iter().rev().take_while(|&&byte| byte != b'\n').count();
+        Ok(Point::new(row, column))
+    }
+
+    let start_byte = edit.position;
+    let overflow = || EditError { offset: start_byte };
+    let old_end_byte = start_byte
+        .checked_add(edit.deleted_length)
+        .ok_or_else(overflow)?;
+    let new_end_byte = start_byte
+        .checked_add(edit.inserted_text.len())
+        .ok_or_else(overflow)?;
+    let start_position = position_for_offset(source, start_byte)?;
+    let old_end_position = position_for_offset(source, old_end_byte)?;
+    source.splice(start_byte..old_end_byte, edit.inserted_text.iter().copied());
+    let new_end_position = position_for_offset(source, new_end_byte)?;
+    let edit = InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position,
+        old_end_position,
+        new_end_position,
+    };
+    tree.edit(&edit);
+    Ok(edit)
 }
 
 /// A single node within a syntax [`Tree`].
@@ -168,6 +352,46 @@ impl InputEdit {
 #[repr(transparent)]
 pub struct Node<'tree>(ffi::TSNode, PhantomData<&'tree ()>);
 
+/// Options that control the output of [`Node::to_sexp_with_options`].
+///
+/// The default options produce the same output as [`Node::to_sexp`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SexpOptions {
+    field_names: bool,
+    byte_ranges: bool,
+    point_ranges: bool,
+    anonymous_nodes: bool,
+}
+
+/// A [`Node`] paired with the source code that it was parsed from, for
+/// debugging. See [`Node::debug_with_source`].
+#[derive(Clone, Copy)]
+pub struct NodeWithSource<'tree, 'a> {
+    node: Node<'tree>,
+    source: &'a [u8],
+}
+
+/// A mapping from node kinds to the names that they should be reported as,
+/// for treating several kinds of nodes as one.
+///
+/// Aliases are normally defined in a grammar, but this allows consumers of a
+/// grammar to normalize node kinds, like unifying `line_comment` and
+/// `block_comment` as `comment`, without regenerating it. Kinds that have no
+/// mapping are reported unchanged.
+#[derive(Clone, Debug, Default)]
+pub struct KindMapper {
+    kinds: BTreeMap<String, String>,
+}
+
+/// A [`Node`] whose kind is reported through a [`KindMapper`].
+///
+/// All of the node's other methods are available through [`Deref`].
+#[derive(Clone, Copy)]
+pub struct MappedNode<'tree, 'a> {
+    node: Node<'tree>,
+    mapper: &'a KindMapper,
+}
+
 /// A stateful object that this is used to produce a [`Tree`] based on some
 /// source code.
 #[doc(alias = "TSParser")]
@@ -193,6 +417,94 @@ impl ParseState {
     pub const fn has_error(&self) -> bool {
         unsafe { self.0.as_ref() }.has_error
     }
+
+    /// The number of times that the parser has begun recovering from a syntax
+    /// error so far.
+    ///
+    /// This is the count that is limited by [`ParseOptions::max_errors`]. It
+    /// never decreases during a parse, including one that is resumed after being
+    /// halted, and it starts from zero again after [`Parser::reset`] or
+    /// [`Parser::set_language`].
+    #[must_use]
+    pub const fn error_count(&self) -> usize {
+        unsafe { self.0.as_ref() }.error_count as usize
+    }
+
+    /// The total number of bytes that the input callback has returned to the
+    /// parser so far.
+    ///
+    /// This is the sum of the lengths of all of the chunks that were read, so
+    /// text that is read more than once is counted each time, and a chunk that
+    /// extends beyond what the parser needed is counted in full. When reparsing
+    /// an edited tree, it only includes the text that had to be read again. Like
+    /// [`ParseState::error_count`], it accumulates across a resumed parse.
+    #[must_use]
+    pub const fn bytes_read(&self) -> usize {
+        unsafe { self.0.as_ref() }.bytes_read as usize
+    }
+
+    /// The index of the included range that the parser is currently reading
+    /// from, or `None` if it has reached the end of the last one.
+    ///
+    /// This is an index into the ranges passed to [`Parser::set_included_ranges`],
+    /// or `0` if no included ranges were set. It reflects the lexer's position,
+    /// which can be slightly ahead of [`ParseState::current_byte_offset`], and it
+    /// moves to the next range as soon as the lexer skips over a gap.
+    #[must_use]
+    pub const fn current_range_index(&self) -> Option<usize> {
+        match unsafe { self.0.as_ref() }.current_range_index {
+            u32::MAX => None,
+            index => Some(index as usize),
+        }
+    }
+
+    /// The number of steps that the parser has taken so far.
+    ///
+    /// A step is a unit of work like lexing a token or performing a reduction.
+    /// The count only grows during a parse, including across a resumed parse,
+    /// and it is the same on every run for the same input, so it can be used
+    /// to halt a parse at a reproducible point. See
+    /// [`ParseOptions::progress_every_n_steps`].
+    #[must_use]
+    pub const fn step_count(&self) -> usize {
+        unsafe { self.0.as_ref() }.step_count as usize
+    }
+}
+
+/// A choice that the parser made between two different trees for the same node,
+/// which is passed to the callback given to [`ParseOptions::report_ambiguity`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmbiguityInfo {
+    /// The kind of the node that could be parsed in more than one way.
+    pub kind: &'static str,
+    /// The range of the node in the source code.
+    pub byte_range: ops::Range<usize>,
+    /// The kinds of the children of the alternative that the parser chose.
+    pub chosen_children: Vec<&'static str>,
+    /// The kinds of the children of the alternative that the parser discarded.
+    pub rejected_children: Vec<&'static str>,
+}
+
+/// The parts of a language's parse table that were used while parsing some input,
+/// as returned by [`Parser::parse_coverage`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The ids of the parse states in which the parser performed an action.
+    pub states: BTreeSet<u16>,
+    /// The production ids of the reductions that the parser performed.
+    pub production_ids: BTreeSet<u16>,
+    /// The ids of the symbols that the parser reduced.
+    pub reduced_symbols: BTreeSet<u16>,
+}
+
+impl CoverageReport {
+    /// Add the table usage from another report to this one, for example to measure the
+    /// coverage of a whole corpus of inputs.
+    pub fn merge(&mut self, other: &Self) {
+        self.states.extend(&other.states);
+        self.production_ids.extend(&other.production_ids);
+        self.reduced_symbols.extend(&other.reduced_symbols);
+    }
 }
 
 /// A stateful object that is passed into a [`QueryProgressCallback`]
@@ -209,6 +521,66 @@ impl QueryCursorState {
 #[derive(Default)]
 pub struct ParseOptions<'a> {
     pub progress_callback: Option<ParseProgressCallback<'a>>,
+    pub progress_interval: Option<u32>,
+    pub profile_phases: bool,
+    pub max_errors: Option<u32>,
+    pub error_recovery: Option<bool>,
+    pub report_lf_positions: bool,
+    pub max_input_bytes: Option<usize>,
+    pub max_column: Option<u32>,
+    pub report_ambiguity: Option<AmbiguityCallback<'a>>,
+    pub on_read: Option<ReadCallback<'a>>,
+    #[cfg(feature = "std")]
+    pub deadline: Option<Instant>,
+}
+
+impl Default for SexpOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SexpOptions {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            field_names: true,
+            byte_ranges: false,
+            point_ranges: false,
+            anonymous_nodes: false,
+        }
+    }
+
+    /// Write the name of each node's field before the node, like
+    /// `name: (identifier)`. This is enabled by default.
+    #[must_use]
+    pub const fn field_names(mut self, field_names: bool) -> Self {
+        self.field_names = field_names;
+        self
+    }
+
+    /// Write the byte range of each node after its kind, like `(identifier 4..9)`.
+    #[must_use]
+    pub const fn byte_ranges(mut self, byte_ranges: bool) -> Self {
+        self.byte_ranges = byte_ranges;
+        self
+    }
+
+    /// Write the start and end points of each node after its kind, like
+    /// `(identifier [1, 4] - [1, 9])`, as in the output of `tree-sitter parse`.
+    #[must_use]
+    pub const fn point_ranges(mut self, point_ranges: bool) -> Self {
+        self.point_ranges = point_ranges;
+        self
+    }
+
+    /// Include anonymous nodes, like keywords and punctuation, with their kinds
+    /// in quotes, like `("if")`.
+    #[must_use]
+    pub const fn anonymous_nodes(mut self, anonymous_nodes: bool) -> Self {
+        self.anonymous_nodes = anonymous_nodes;
+        self
+    }
 }
 
 impl<'a> ParseOptions<'a> {
@@ -226,6 +598,149 @@ impl<'a> ParseOptions<'a> {
         self
     }
 
+    /// Call the progress callback once every `steps` parse steps, instead of
+    /// the default of every 100 steps.
+    ///
+    /// A step is a unit of work like lexing a token or performing a reduction,
+    /// so the points at which the callback is called are the same on every run
+    /// for the same input. Halting the parse after a fixed number of steps,
+    /// using [`ParseState::step_count`], is therefore reproducible in a way that
+    /// halting it after a fixed amount of time is not. A value of zero uses the
+    /// default interval.
+    #[must_use]
+    pub const fn progress_every_n_steps(mut self, steps: u32) -> Self {
+        self.progress_interval = Some(steps);
+        self
+    }
+
+    /// Measure the time spent in each phase of the parse.
+    ///
+    /// The measurements can be retrieved with [`Parser::phase_timings`] once
+    /// parsing has finished. This is intended for grammar performance work, and
+    /// adds a small overhead to parsing.
+    #[must_use]
+    pub const fn profile_phases(mut self, enabled: bool) -> Self {
+        self.profile_phases = enabled;
+        self
+    }
+
+    /// Halt the parse once the parser has had to recover from the given number
+    /// of syntax errors.
+    ///
+    /// This is useful for skipping files that are badly broken. A parse that is
+    /// halted this way returns `None`, just like one that was halted by the
+    /// progress callback, so you must call [`Parser::reset`] before using the
+    /// parser for another document. A limit of zero means that there is no
+    /// limit.
+    #[must_use]
+    pub const fn max_errors(mut self, max_errors: u32) -> Self {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    /// Enable or disable error recovery. It is enabled by default.
+    ///
+    /// Normally, the parser recovers from each syntax error by inserting missing
+    /// nodes or by wrapping the invalid text in an `ERROR` node, and then parses
+    /// the rest of the input normally. Without error recovery, the parser gives
+    /// up at the first error: the root of the resulting tree is a single `ERROR`
+    /// node containing the nodes that were parsed before the error, followed by
+    /// the remaining tokens. Valid input produces the same tree either way, so
+    /// disabling recovery avoids its cost for tools that only process valid files.
+    #[must_use]
+    pub const fn error_recovery(mut self, enabled: bool) -> Self {
+        self.error_recovery = Some(enabled);
+        self
+    }
+
+    /// Compute the columns of the tree's points as if each `\r\n` line ending
+    /// were a single `\n`.
+    ///
+    /// Normally, a node that ends with the `\r` of a `\r\n`, like a comment that
+    /// extends to the end of its line, ends one column after the line's visible
+    /// text. With this option, the `\r` does not take up a column, so the points
+    /// match the positions shown by editors that hide it, without any changes to
+    /// the input. Byte offsets are not affected. The points of any [`InputEdit`]s
+    /// and included ranges given to the parser must be computed the same way.
+    #[must_use]
+    pub const fn report_lf_positions(mut self, enabled: bool) -> Self {
+        self.report_lf_positions = enabled;
+        self
+    }
+
+    /// Treat the given byte offset as the end of the input.
+    ///
+    /// The parser stops reading at this offset even if the input callback would
+    /// return more text, so the parse always finishes. This protects against
+    /// callbacks that never return an empty slice. The limit applies to the byte
+    /// offsets that are passed to the callback, so it is the same when the
+    /// parser re-reads text during an incremental parse.
+    #[must_use]
+    pub const fn max_input_bytes(mut self, max_input_bytes: usize) -> Self {
+        self.max_input_bytes = Some(max_input_bytes);
+        self
+    }
+
+    /// Report any column greater than the given one as this column to the grammar's lex
+    /// functions and external scanner.
+    ///
+    /// Columns are only computed for grammars that depend on them, and computing one can
+    /// require re-reading the current line from its start, which is slow on pathological
+    /// input like minified code on a single line. With a limit, column-sensitive grammars
+    /// treat every position past it the same way, and the line is not re-read when it is
+    /// known to be long enough. A limit of zero means that there is no limit.
+    #[must_use]
+    pub const fn max_column(mut self, max_column: u32) -> Self {
+        self.max_column = Some(max_column);
+        self
+    }
+
+    /// Call the given function whenever the parser chooses between two
+    /// different trees for the same node.
+    ///
+    /// This is a debugging aid for grammar authors. When a grammar is
+    /// ambiguous, the parser picks among the valid trees using dynamic
+    /// precedence, or failing that, arbitrarily. Running a corpus through a
+    /// parser with this option reveals the places where that happens, along
+    /// with the children of the competing alternatives. Choices between
+    /// alternatives that contain syntax errors are not reported.
+    #[must_use]
+    pub fn report_ambiguity<F: FnMut(AmbiguityInfo)>(mut self, callback: &'a mut F) -> Self {
+        self.report_ambiguity = Some(callback);
+        self
+    }
+
+    /// Call the given function each time the parser reads text from the input,
+    /// with the offset that was requested and the length of the text that was
+    /// returned.
+    ///
+    /// During an incremental parse, the parser re-reads the text around each
+    /// edit, and may read some text more than once, so this can be used to
+    /// measure how much reading a parse requires. The offset and length are in
+    /// the same units as the ones passed to and returned from the input
+    /// callback: bytes for UTF-8 and custom encodings, and code units for
+    /// UTF-16.
+    #[must_use]
+    pub fn on_read<F: FnMut(usize, usize)>(mut self, callback: &'a mut F) -> Self {
+        self.on_read = Some(callback);
+        self
+    }
+
+    /// Halt the parse once the given instant has passed.
+    ///
+    /// The deadline is checked whenever the progress callback would be called,
+    /// including while the tree is being balanced at the end of the parse, and
+    /// before the progress callback itself, so either of them can halt the
+    /// parse. A parse that is halted this way returns `None`, just like one
+    /// that was halted by the progress callback. Parsing the same document
+    /// again without calling [`Parser::reset`] resumes from where it left off.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub const fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
     /// Create a new `ParseOptions` with a shorter lifetime, borrowing from this one.
     ///
     /// This is useful when you need to reuse parse options multiple times, e.g., calling
@@ -237,7 +752,44 @@ impl<'a> ParseOptions<'a> {
                 Some(cb) => Some(*cb),
                 None => None,
             },
+            progress_interval: self.progress_interval,
+            profile_phases: self.profile_phases,
+            max_errors: self.max_errors,
+            error_recovery: self.error_recovery,
+            report_lf_positions: self.report_lf_positions,
+            max_input_bytes: self.max_input_bytes,
+            max_column: self.max_column,
+            report_ambiguity: match &mut self.report_ambiguity {
+                Some(cb) => Some(*cb),
+                None => None,
+            },
+            on_read: match &mut self.on_read {
+                Some(cb) => Some(*cb),
+                None => None,
+            },
+            #[cfg(feature = "std")]
+            deadline: self.deadline,
+        }
+    }
+
+    /// Get the callback to pass to the parser, which checks the deadline, if
+    /// there is one, before calling the progress callback.
+    fn combined_progress_callback(&mut self) -> Option<BoxedParseProgressCallback<'a>> {
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.deadline {
+            let mut progress_callback = self.progress_callback.take();
+            return Some(Box::new(move |state: &ParseState| {
+                if Instant::now() >= deadline {
+                    return ControlFlow::Break(());
+                }
+                progress_callback
+                    .as_mut()
+                    .map_or(ControlFlow::Continue(()), |callback| callback(state))
+            }));
         }
+        self.progress_callback
+            .take()
+            .map(|callback| Box::new(callback) as BoxedParseProgressCallback<'a>)
     }
 }
 
@@ -306,9 +858,82 @@ type Logger<'a> = Box<dyn FnMut(LogType, &str) + 'a>;
 /// A callback that receives the parse state during parsing.
 type ParseProgressCallback<'a> = &'a mut dyn FnMut(&ParseState) -> ControlFlow<()>;
 
+/// A callback that receives the ambiguities that the parser resolves.
+type AmbiguityCallback<'a> = &'a mut dyn FnMut(AmbiguityInfo);
+
+/// A callback that receives the offset and length of each read from the input.
+type ReadCallback<'a> = &'a mut dyn FnMut(usize, usize);
+
+/// A parse progress callback that the parser owns for the duration of a parse.
+type BoxedParseProgressCallback<'a> = Box<dyn FnMut(&ParseState) -> ControlFlow<()> + 'a>;
+
+/// The payload passed on every call to a parse function's `read` C function.
+/// It contains three things:
+/// 1. A reference to the rust `callback`.
+/// 2. The text that was returned from the previous call to `callback`. This allows the
+///    callback to return owned values like vectors.
+/// 3. The optional callback that is notified of every read.
+type ReadPayload<'a, 'b, F, T> = (&'a mut F, Option<T>, Option<ReadCallback<'b>>);
+
+/// A C function that Tree-sitter calls to read a chunk of input.
+type ReadFunction = unsafe extern "C" fn(*mut c_void, u32, ffi::TSPoint, *mut u32) -> *const c_char;
+
+// This C function is passed to Tree-sitter as the input callback for UTF-8 text.
+unsafe extern "C" fn read_utf8<T: AsRef<[u8]>, F: FnMut(usize, Point) -> T>(
+    payload: *mut c_void,
+    byte_offset: u32,
+    position: ffi::TSPoint,
+    bytes_read: *mut u32,
+) -> *const c_char {
+    unsafe {
+        let (callback, text, on_read) = payload.cast::<ReadPayload<F, T>>().as_mut().unwrap();
+        *text = Some(callback(byte_offset as usize, position.into()));
+        let slice = text.as_ref().unwrap().as_ref();
+        if let Some(on_read) = on_read {
+            on_read(byte_offset as usize, slice.len());
+        }
+        *bytes_read = slice.len() as u32;
+        slice.as_ptr().cast::<c_char>()
+    }
+}
+
+/// The callbacks and flags of a [`ParseOptions`] that apply to a single parse
+/// rather than being stored on the parser.
+struct ParseCallbacks<'a> {
+    progress: Option<BoxedParseProgressCallback<'a>>,
+    report_ambiguity: Option<AmbiguityCallback<'a>>,
+    on_read: Option<ReadCallback<'a>>,
+}
+
 /// A callback that receives the query state during query execution.
 type QueryProgressCallback<'a> = &'a mut dyn FnMut(&QueryCursorState) -> ControlFlow<()>;
 
+/// The reason that the parser stopped reading input during a parse with a
+/// custom [`Decode`] implementation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeStop {
+    /// The input callback returned an empty slice, marking the end of the document.
+    Eof,
+    /// The decoder reported that it consumed zero bytes from a non-empty slice
+    /// at the given byte offset. The parser treats this as the end of the input.
+    Stall(usize),
+}
+
+/// The processor time that a parse spent in each of its phases.
+///
+/// See [`ParseOptions::profile_phases`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParsePhaseTimings {
+    /// Time spent running the lexer, including external scanners.
+    pub lexing: Duration,
+    /// Time spent performing reductions.
+    pub reduction: Duration,
+    /// Time spent recovering from syntax errors.
+    pub error_recovery: Duration,
+    /// Time spent balancing the finished tree.
+    pub balancing: Duration,
+}
+
 pub trait Decode {
     /// A callback that decodes the next code point from the input slice. It should return the code
     /// point, and how many bytes were decoded.
@@ -359,6 +984,27 @@ impl From<ffi::TSQuantifier> for CaptureQuantifier {
     }
 }
 
+/// A summary of the features of a [`Query`] that affect how expensive it is to
+/// execute. See [`Query::estimated_cost`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryCost {
+    /// The number of patterns in the query.
+    pub pattern_count: usize,
+    /// The number of patterns that do not have a single root node.
+    pub non_rooted_pattern_count: usize,
+    /// The number of patterns that can begin with a wildcard node, and so must
+    /// be tried at every node in the tree. A pattern like `(_ (identifier))`
+    /// is not counted, because it is only tried where its child matches.
+    pub wildcard_pattern_count: usize,
+    /// The total number of predicates in all of the patterns.
+    pub predicate_count: usize,
+    /// The number of `#match?` and `#not-match?` predicates, each of which runs
+    /// a regex on the text of a capture.
+    pub regex_count: usize,
+    /// The largest number of levels of nested nodes in any pattern.
+    pub max_depth: usize,
+}
+
 /// A stateful object for executing a [`Query`] on a syntax [`Tree`].
 #[doc(alias = "TSQueryCursor")]
 pub struct QueryCursor {
@@ -421,6 +1067,17 @@ pub struct QueryCaptures<'query, 'tree, T: TextProvider<I>, I: AsRef<[u8]>> {
     _phantom: PhantomData<(&'tree (), I)>,
 }
 
+/// A source of the text of the nodes that are captured by a query.
+///
+/// [`QueryCursor::matches`] and [`QueryCursor::captures`] accept any
+/// `TextProvider`, so the source code does not need to be stored in one
+/// contiguous buffer. The provider is only asked for a node's text when a
+/// text predicate like `#eq?` or `#match?` needs to be evaluated, and it may
+/// return the text in several chunks, which are only concatenated if a
+/// predicate needs to compare more than one chunk.
+///
+/// This is implemented for `&[u8]`, for closures that take a [`Node`] and
+/// return an iterator of chunks, and for [`ChunkedTextProvider`].
 pub trait TextProvider<I>
 where
     I: AsRef<[u8]>,
@@ -429,6 +1086,30 @@ where
     fn text(&mut self, node: Node) -> Self::I;
 }
 
+/// A document whose text is stored in multiple chunks, such as a rope.
+///
+/// Implementing this trait allows a document to be parsed with
+/// [`Parser::parse_with_provider`], and to be used when evaluating query
+/// predicates via [`ChunkedTextProvider`].
+pub trait ChunkedText {
+    /// Get the text starting at the given byte offset, up to the end of the
+    /// chunk that contains it.
+    ///
+    /// The returned slice must not be empty unless `byte` is at or past the end
+    /// of the document.
+    fn chunk_at(&self, byte: usize) -> &[u8];
+}
+
+/// A [`TextProvider`] that reads the text of nodes from a [`ChunkedText`].
+#[derive(Clone, Copy)]
+pub struct ChunkedTextProvider<'a, P: ?Sized>(pub &'a P);
+
+/// An iterator over the chunks of a [`ChunkedText`] within a byte range.
+pub struct ChunkedTextChunks<'a, P: ?Sized> {
+    text: &'a P,
+    range: ops::Range<usize>,
+}
+
 /// A particular [`Node`] that has been captured with a particular name within a
 /// [`Query`].
 #[derive(Clone, Copy, Debug)]
@@ -450,7 +1131,94 @@ pub enum LanguageError {
 
 /// An error that occurred in [`Parser::set_included_ranges`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct IncludedRangesError(pub usize);
+pub struct IncludedRangesError {
+    /// The index of the first invalid range.
+    pub index: usize,
+    pub kind: RangeErrorKind,
+}
+
+/// An error that occurred in [`Parser::configure`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParserError {
+    Language(LanguageError),
+    IncludedRanges(IncludedRangesError),
+}
+
+/// The reason that a range passed to [`Parser::set_included_ranges`] was invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeErrorKind {
+    /// The range starts before the previous range starts.
+    NotOrdered,
+    /// The range ends before it starts.
+    EndBeforeStart,
+    /// The range starts before the previous range ends.
+    Overlapping,
+}
+
+/// An error returned by [`Language::is_compatible`] when a language's ABI
+/// version is outside of the range supported by this library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AbiMismatch {
+    /// The ABI version of the language.
+    pub version: usize,
+    /// The earliest supported ABI version, [`MIN_COMPATIBLE_LANGUAGE_VERSION`].
+    pub min_version: usize,
+    /// The latest supported ABI version, [`LANGUAGE_VERSION`].
+    pub max_version: usize,
+}
+
+/// An error that occurred in [`apply_edit`] or [`Edit::invert`], because the
+/// edit refers to an offset past the end of the source code.
+///
+/// If the end of the edit overflows a `usize`, the offset is the edit's position.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EditError {
+    pub offset: usize,
+}
+
+/// An error that occurred in [`InputEdit::validate`] or [`Tree::try_edit`],
+/// because the edit is malformed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEditError {
+    /// The edit's `old_end_byte` is less than its `start_byte`.
+    OldEndBeforeStart,
+    /// The edit's `new_end_byte` is less than its `start_byte`.
+    NewEndBeforeStart,
+    /// The edit's `old_end_position` does not agree with its byte offsets.
+    InconsistentOldEndPosition,
+    /// The edit's `new_end_position` does not agree with its byte offsets.
+    InconsistentNewEndPosition,
+}
+
+/// An error that occurred when parsing a [`Point`] or a byte range from a
+/// string. Each variant holds the string that could not be parsed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PositionParseError {
+    /// The string was not of the form `row,column`.
+    Point(String),
+    /// The string was not of the form `start..end`, with `start <= end`.
+    ByteRange(String),
+}
+
+/// An error that occurred in [`Parser::parse_with_verification`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum IncrementalParseError {
+    /// One of the parses did not produce a tree, because the parser has no
+    /// language, or because the parse was halted.
+    NoTree,
+    /// The incrementally-parsed tree is not structurally equal to the tree
+    /// that was parsed from scratch.
+    Mismatch {
+        /// The S-expression of the incrementally-parsed tree.
+        incremental: String,
+        /// The S-expression of the tree that was parsed from scratch.
+        fresh: String,
+        /// A line diff of the two S-expressions, with lines only in the
+        /// incremental tree prefixed by `-` and lines only in the fresh tree
+        /// prefixed by `+`.
+        diff: String,
+    },
+}
 
 /// An error that occurred when trying to create a [`Query`].
 #[derive(Debug, PartialEq, Eq)]
@@ -515,6 +1283,24 @@ impl Language {
         unsafe { ffi::ts_language_abi_version(self.0) as usize }
     }
 
+    /// Check whether this language's ABI version is supported by this library.
+    ///
+    /// This is the same check that [`Parser::set_language`] performs, but it
+    /// reports the supported range along with the language's version, which
+    /// is useful when loading languages dynamically.
+    pub fn is_compatible(&self) -> Result<(), AbiMismatch> {
+        let version = self.abi_version();
+        if (MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version) {
+            Ok(())
+        } else {
+            Err(AbiMismatch {
+                version,
+                min_version: MIN_COMPATIBLE_LANGUAGE_VERSION,
+                max_version: LANGUAGE_VERSION,
+            })
+        }
+    }
+
     /// Get the metadata for this language. This information is generated by the
     /// CLI, and relies on the language author providing the correct metadata in
     /// the language's `tree-sitter.json` file.
@@ -536,6 +1322,20 @@ impl Language {
         unsafe { ffi::ts_language_symbol_count(self.0) as usize }
     }
 
+    /// Get the node kind of the grammar's start rule, which is the kind of the root node of any
+    /// tree that is parsed without errors, such as `source_file` or `program`.
+    ///
+    /// The start rule is found in the parse table. Returns an empty string if
+    /// the table does not have one.
+    #[doc(alias = "ts_language_start_symbol")]
+    #[must_use]
+    pub fn root_kind(&self) -> &'static str {
+        match unsafe { ffi::ts_language_start_symbol(self.0) } {
+            0 => "",
+            symbol => self.node_kind_for_id(symbol).unwrap_or(""),
+        }
+    }
+
     /// Get the number of valid states in this language.
     #[doc(alias = "ts_language_state_count")]
     #[must_use]
@@ -573,6 +1373,32 @@ impl Language {
         }
     }
 
+    /// Get the ids of all of the fields that can appear on nodes of the given
+    /// kind, sorted in ascending order.
+    ///
+    /// The fields are computed from the parse table, so fields that a node
+    /// inherits from hidden rules are included. Kinds that only exist as the
+    /// target of an alias have no productions of their own and yield an empty
+    /// list.
+    #[doc(alias = "ts_language_fields_for_symbol")]
+    #[must_use]
+    pub fn fields_for_kind(&self, kind_id: u16) -> Vec<u16> {
+        unsafe {
+            let mut length = 0u32;
+            let ptr = ffi::ts_language_fields_for_symbol(
+                self.0,
+                kind_id,
+                core::ptr::addr_of_mut!(length),
+            );
+            if ptr.is_null() {
+                return Vec::new();
+            }
+            let result = slice::from_raw_parts(ptr, length as usize).to_vec();
+            ts_free(ptr.cast::<c_void>());
+            result
+        }
+    }
+
     /// Get the name of the node kind for the given numerical id.
     #[doc(alias = "ts_language_symbol_name")]
     #[must_use]
@@ -615,6 +1441,39 @@ impl Language {
         unsafe { ffi::ts_language_symbol_type(self.0, id) == ffi::TSSymbolTypeSupertype }
     }
 
+    /// Check if the node type for the given numerical id is one of the grammar's
+    /// extras, like comments, which can appear anywhere in the document.
+    #[doc(alias = "ts_language_symbol_is_extra")]
+    #[must_use]
+    pub fn node_kind_is_extra(&self, id: u16) -> bool {
+        unsafe { ffi::ts_language_symbol_is_extra(self.0, id) }
+    }
+
+    /// Get the numerical ids of all of the node types that are extras in this
+    /// language.
+    ///
+    /// Extras that are only matched by the lexer and never produce nodes, such
+    /// as whitespace, are not included.
+    #[must_use]
+    pub fn extra_symbols(&self) -> Vec<u16> {
+        (0..self.node_kind_count() as u16)
+            .filter(|id| self.node_kind_is_extra(*id))
+            .collect()
+    }
+
+    /// Iterate over every node type in this language, yielding its numerical
+    /// id, its name, and whether it is named.
+    ///
+    /// Names are not unique on their own: a named node type and an anonymous
+    /// one can share a name, so lookup tables built from this should be keyed
+    /// on both the name and the `is_named` flag.
+    pub fn kind_ids(&self) -> impl Iterator<Item = (u16, &'static str, bool)> + '_ {
+        (0..self.node_kind_count() as u16).filter_map(|id| {
+            self.node_kind_for_id(id)
+                .map(|name| (id, name, self.node_kind_is_named(id)))
+        })
+    }
+
     /// Get the number of distinct field names in this language.
     #[doc(alias = "ts_language_field_count")]
     #[must_use]
@@ -728,13 +1587,14 @@ impl Parser {
     /// Returns a Result indicating whether or not the language was successfully
     /// assigned. True means assignment succeeded. False means there was a
     /// version mismatch: the language was generated with an incompatible
-    /// version of the Tree-sitter CLI. Check the language's version using
-    /// [`Language::version`] and compare it to this library's
-    /// [`LANGUAGE_VERSION`] and [`MIN_COMPATIBLE_LANGUAGE_VERSION`] constants.
+    /// version of the Tree-sitter CLI. Use [`Language::is_compatible`] to check
+    /// a language's version against this library's [`LANGUAGE_VERSION`] and
+    /// [`MIN_COMPATIBLE_LANGUAGE_VERSION`] constants.
     #[doc(alias = "ts_parser_set_language")]
     pub fn set_language(&mut self, language: &Language) -> Result<(), LanguageError> {
-        let version = language.abi_version();
-        if (MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version) {
+        if let Err(error) = language.is_compatible() {
+            Err(LanguageError::Version(error.version))
+        } else {
             #[cfg_attr(
                 not(feature = "wasm"),
                 expect(unused_variables, reason = "only used when wasm feature is enabled")
@@ -745,8 +1605,6 @@ impl Parser {
                 return Err(LanguageError::Wasm);
             }
             Ok(())
-        } else {
-            Err(LanguageError::Version(version))
         }
     }
 
@@ -758,6 +1616,25 @@ impl Parser {
         (!ptr.is_null()).then_some(LanguageRef(ptr, PhantomData))
     }
 
+    /// Check whether the parser calls the language's external scanner.
+    #[doc(alias = "ts_parser_use_external_scanner")]
+    #[must_use]
+    pub fn use_external_scanner(&self) -> bool {
+        unsafe { ffi::ts_parser_use_external_scanner(self.0.as_ptr()) }
+    }
+
+    /// Enable or disable the language's external scanner.
+    ///
+    /// This is a debugging aid for determining whether a problem is caused by a
+    /// grammar or by its external scanner. When the external scanner is disabled,
+    /// its `scan` function is never called, so none of the grammar's external
+    /// tokens can be produced. For most grammars that use external tokens, this
+    /// will cause syntax errors. The external scanner is enabled by default.
+    #[doc(alias = "ts_parser_set_use_external_scanner")]
+    pub fn set_use_external_scanner(&mut self, use_external_scanner: bool) {
+        unsafe { ffi::ts_parser_set_use_external_scanner(self.0.as_ptr(), use_external_scanner) }
+    }
+
     /// Get the parser's current logger.
     #[doc(alias = "ts_parser_logger")]
     #[must_use]
@@ -841,6 +1718,181 @@ impl Parser {
         }
     }
 
+    /// Apply the parser-wide settings of the given options, and take out the
+    /// callbacks that only apply to the upcoming parse.
+    fn apply_parse_options<'a>(
+        &mut self,
+        options: &mut Option<ParseOptions<'a>>,
+    ) -> ParseCallbacks<'a> {
+        let profile_phases = options.as_ref().is_some_and(|o| o.profile_phases);
+        let max_errors = options.as_ref().and_then(|o| o.max_errors).unwrap_or(0);
+        let progress_interval = options
+            .as_ref()
+            .and_then(|o| o.progress_interval)
+            .unwrap_or(0);
+        let error_recovery = options
+            .as_ref()
+            .and_then(|o| o.error_recovery)
+            .unwrap_or(true);
+        let max_input_bytes = options
+            .as_ref()
+            .and_then(|o| o.max_input_bytes)
+            .map_or(0, |max| u32::try_from(max).unwrap_or(u32::MAX));
+        let max_column = options.as_ref().and_then(|o| o.max_column).unwrap_or(0);
+        let report_lf_positions = options.as_ref().is_some_and(|o| o.report_lf_positions);
+        unsafe {
+            ffi::ts_parser_set_phase_profiling(self.0.as_ptr(), profile_phases);
+            ffi::ts_parser_set_max_errors(self.0.as_ptr(), max_errors);
+            ffi::ts_parser_set_progress_interval(self.0.as_ptr(), progress_interval);
+            ffi::ts_parser_set_error_recovery(self.0.as_ptr(), error_recovery);
+            ffi::ts_parser_set_max_input_bytes(self.0.as_ptr(), max_input_bytes);
+            ffi::ts_parser_set_max_column(self.0.as_ptr(), max_column);
+            ffi::ts_parser_set_report_lf_positions(self.0.as_ptr(), report_lf_positions);
+        }
+
+        options.as_mut().map_or(
+            ParseCallbacks {
+                progress: None,
+                report_ambiguity: None,
+                on_read: None,
+            },
+            |options| ParseCallbacks {
+                progress: options.combined_progress_callback(),
+                report_ambiguity: options.report_ambiguity.take(),
+                on_read: options.on_read.take(),
+            },
+        )
+    }
+
+    /// Parse input that is read through the given C function, which receives
+    /// a pointer to a [`ReadPayload`] wrapping `callback`.
+    ///
+    /// The resulting tree is passed to `finish`, along with the input, so that
+    /// the same input can be parsed again.
+    #[expect(clippy::too_many_arguments, reason = "private helper")]
+    fn parse_input<T, F, R>(
+        &mut self,
+        callback: &mut F,
+        old_tree: Option<&Tree>,
+        mut options: Option<ParseOptions>,
+        read: ReadFunction,
+        encoding: ffi::TSInputEncoding,
+        decode: ffi::TSDecodeFunction,
+        finish: impl FnOnce(&mut Self, Option<Tree>, ffi::TSInput) -> R,
+    ) -> R {
+        // This C function is passed to Tree-sitter as the progress callback.
+        unsafe extern "C" fn progress(state: *mut ffi::TSParseState) -> bool {
+            unsafe {
+                let callback = (*state)
+                    .payload
+                    .cast::<ParseProgressCallback>()
+                    .as_mut()
+                    .unwrap();
+                match callback(&ParseState::from_raw(state)) {
+                    ControlFlow::Continue(()) => false,
+                    ControlFlow::Break(()) => true,
+                }
+            }
+        }
+
+        let ParseCallbacks {
+            progress: mut progress_callback,
+            report_ambiguity,
+            on_read,
+        } = self.apply_parse_options(&mut options);
+
+        let mut callback_ptr: ParseProgressCallback;
+        let parse_options = if let Some(progress_callback) = progress_callback.as_mut() {
+            callback_ptr = &mut **progress_callback;
+            ffi::TSParseOptions {
+                payload: ptr::addr_of_mut!(callback_ptr).cast::<c_void>(),
+                progress_callback: Some(progress),
+            }
+        } else {
+            ffi::TSParseOptions {
+                payload: ptr::null_mut(),
+                progress_callback: None,
+            }
+        };
+
+        let mut payload: ReadPayload<F, T> = (callback, None, on_read);
+        let c_input = ffi::TSInput {
+            payload: ptr::addr_of_mut!(payload).cast::<c_void>(),
+            read: Some(read),
+            encoding,
+            decode,
+        };
+
+        let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
+        let tree = self.with_ambiguity_callback(report_ambiguity, |parser| unsafe {
+            let c_new_tree = ffi::ts_parser_parse_with_options(
+                parser.0.as_ptr(),
+                c_old_tree,
+                copy_input(&c_input),
+                parse_options,
+            );
+
+            NonNull::new(c_new_tree).map(Tree)
+        });
+
+        // The read callback only observes the parse that was asked for, not
+        // any later parse of the same input.
+        unsafe { (*c_input.payload.cast::<ReadPayload<F, T>>()).2 = None };
+        finish(self, tree, c_input)
+    }
+
+    /// Run the given parse with an ambiguity callback installed, if there is one.
+    fn with_ambiguity_callback<R>(
+        &mut self,
+        callback: Option<AmbiguityCallback>,
+        parse: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        type Payload<'a> = (AmbiguityCallback<'a>, *const ffi::TSLanguage);
+
+        // This C function is passed to Tree-sitter as the ambiguity callback.
+        unsafe extern "C" fn report(payload: *mut c_void, ambiguity: *const ffi::TSAmbiguity) {
+            unsafe {
+                let (callback, language) = payload.cast::<Payload>().as_mut().unwrap();
+                let language = LanguageRef(*language, PhantomData);
+                let ambiguity = &*ambiguity;
+                let kinds = |ptr: *const u16, len: u32| -> Vec<&'static str> {
+                    if len == 0 {
+                        return Vec::new();
+                    }
+                    slice::from_raw_parts(ptr, len as usize)
+                        .iter()
+                        .map(|id| language.node_kind_for_id(*id).unwrap_or(""))
+                        .collect()
+                };
+                callback(AmbiguityInfo {
+                    kind: language.node_kind_for_id(ambiguity.symbol).unwrap_or(""),
+                    byte_range: ambiguity.start_byte as usize..ambiguity.end_byte as usize,
+                    chosen_children: kinds(ambiguity.chosen_children, ambiguity.chosen_child_count),
+                    rejected_children: kinds(
+                        ambiguity.rejected_children,
+                        ambiguity.rejected_child_count,
+                    ),
+                });
+            }
+        }
+
+        let Some(callback) = callback else {
+            return parse(self);
+        };
+        let language = unsafe { ffi::ts_parser_language(self.0.as_ptr()) };
+        let mut payload: Payload = (callback, language);
+        unsafe {
+            ffi::ts_parser_set_ambiguity_callback(
+                self.0.as_ptr(),
+                ptr::addr_of_mut!(payload).cast::<c_void>(),
+                Some(report),
+            );
+        }
+        let result = parse(self);
+        unsafe { ffi::ts_parser_set_ambiguity_callback(self.0.as_ptr(), ptr::null_mut(), None) };
+        result
+    }
+
     /// Stop the parser from printing debugging graphs while parsing.
     #[doc(alias = "ts_parser_print_dot_graphs")]
     #[cfg(not(target_os = "wasi"))]
@@ -858,6 +1910,11 @@ impl Parser {
     ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
     ///   the new text using [`Tree::edit`].
     ///
+    /// The length of the input is taken from the slice, so the text may contain null bytes. A
+    /// null byte is never treated as the end of the input; like any other unexpected character,
+    /// it produces an error node and parsing continues after it. The same holds for all of the
+    /// slice-based and callback-based parse methods.
+    ///
     /// Returns a [`Tree`] if parsing succeeded, or `None` if:
     ///  * The parser has not yet had a language assigned with [`Parser::set_language`]
     #[doc(alias = "ts_parser_parse")]
@@ -877,6 +1934,86 @@ impl Parser {
         )
     }
 
+    /// Parse a document that is stored as a list of lines.
+    ///
+    /// The lines are parsed as if they were joined with single `\n` characters, without a
+    /// trailing newline after the last line. The lines themselves must not contain line
+    /// terminators.
+    ///
+    /// # Arguments:
+    /// * `lines` The UTF8-encoded lines of the document.
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    pub fn parse_lines(&mut self, lines: &[&str], old_tree: Option<&Tree>) -> Option<Tree> {
+        self.parse_with_options(
+            &mut |_, position| {
+                let Some(line) = lines.get(position.row) else {
+                    return &[][..];
+                };
+                if position.column < line.len() {
+                    &line.as_bytes()[position.column..]
+                } else if position.row + 1 < lines.len() {
+                    b"\n"
+                } else {
+                    &[]
+                }
+            },
+            old_tree,
+            None,
+        )
+    }
+
+    /// Parse a slice of UTF8 text and report which parts of the language's parse table were
+    /// used, without keeping the resulting syntax tree.
+    ///
+    /// This is useful for measuring how much of a grammar is exercised by a corpus of test
+    /// inputs. Reports for several inputs can be combined with [`CoverageReport::merge`].
+    ///
+    /// Returns `None` under the same conditions as [`Parser::parse`].
+    #[doc(alias = "ts_parser_set_table_usage_callback")]
+    pub fn parse_coverage(&mut self, text: impl AsRef<[u8]>) -> Option<CoverageReport> {
+        // This C function is passed to Tree-sitter as the table usage callback.
+        unsafe extern "C" fn record(payload: *mut c_void, usage: *const ffi::TSTableUsage) {
+            unsafe {
+                let report = payload.cast::<CoverageReport>().as_mut().unwrap();
+                let usage = &*usage;
+                report.states.insert(usage.state);
+                if usage.is_reduction {
+                    report.production_ids.insert(usage.production_id);
+                    report.reduced_symbols.insert(usage.symbol);
+                }
+            }
+        }
+
+        let mut report = CoverageReport::default();
+        unsafe {
+            ffi::ts_parser_set_table_usage_callback(
+                self.0.as_ptr(),
+                ptr::addr_of_mut!(report).cast::<c_void>(),
+                Some(record),
+            );
+        }
+        let tree = self.parse(text, None);
+        unsafe { ffi::ts_parser_set_table_usage_callback(self.0.as_ptr(), ptr::null_mut(), None) };
+        tree.map(|_| report)
+    }
+
+    /// Parse a document that is stored in chunks, such as a rope.
+    ///
+    /// # Arguments:
+    /// * `text` The UTF8-encoded text to parse. See [`ChunkedText`].
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    pub fn parse_with_provider<P: ChunkedText + ?Sized>(
+        &mut self,
+        text: &P,
+        old_tree: Option<&Tree>,
+    ) -> Option<Tree> {
+        self.parse_with_options(&mut |i, _| text.chunk_at(i), old_tree, None)
+    }
+
     /// Parse text provided in chunks by a callback.
     ///
     /// # Arguments:
@@ -894,88 +2031,71 @@ impl Parser {
         old_tree: Option<&Tree>,
         options: Option<ParseOptions>,
     ) -> Option<Tree> {
-        type Payload<'a, F, T> = (&'a mut F, Option<T>);
-
-        // This C function is passed to Tree-sitter as the progress callback.
-        unsafe extern "C" fn progress(state: *mut ffi::TSParseState) -> bool {
-            unsafe {
-                let callback = (*state)
-                    .payload
-                    .cast::<ParseProgressCallback>()
-                    .as_mut()
-                    .unwrap();
-                match callback(&ParseState::from_raw(state)) {
-                    ControlFlow::Continue(()) => false,
-                    ControlFlow::Break(()) => true,
-                }
-            }
-        }
-
-        // This C function is passed to Tree-sitter as the input callback.
-        unsafe extern "C" fn read<T: AsRef<[u8]>, F: FnMut(usize, Point) -> T>(
-            payload: *mut c_void,
-            byte_offset: u32,
-            position: ffi::TSPoint,
-            bytes_read: *mut u32,
-        ) -> *const c_char {
-            unsafe {
-                let (callback, text) = payload.cast::<Payload<F, T>>().as_mut().unwrap();
-                *text = Some(callback(byte_offset as usize, position.into()));
-                let slice = text.as_ref().unwrap().as_ref();
-                *bytes_read = slice.len() as u32;
-                slice.as_ptr().cast::<c_char>()
-            }
-        }
-
-        let empty_options = ffi::TSParseOptions {
-            payload: ptr::null_mut(),
-            progress_callback: None,
-        };
+        self.parse_input::<T, F, _>(
+            callback,
+            old_tree,
+            options,
+            read_utf8::<T, F>,
+            ffi::TSInputEncodingUTF8,
+            None,
+            |_, tree, _| tree,
+        )
+    }
 
-        let mut callback_ptr;
-        let parse_options = if let Some(options) = options {
-            if let Some(cb) = options.progress_callback {
-                callback_ptr = cb;
-                ffi::TSParseOptions {
-                    payload: core::ptr::addr_of_mut!(callback_ptr).cast::<c_void>(),
-                    progress_callback: Some(progress),
+    /// Parse text provided in chunks by a callback, reusing `old_tree`, and
+    /// check that the result is structurally equal to a tree parsed from
+    /// scratch.
+    ///
+    /// This turns silent corruption of reused subtrees into a loud failure,
+    /// which makes it useful when fuzzing or in CI. It more than doubles the
+    /// cost of the parse. The `callback` is invoked again for the second parse,
+    /// but the progress and read callbacks in `options` are not.
+    ///
+    /// The arguments are the same as for [`parse_with_options`](Parser::parse_with_options).
+    /// Returns an [`IncrementalParseError::Mismatch`] with a diff of the two
+    /// trees' S-expressions if they differ.
+    pub fn parse_with_verification<T: AsRef<[u8]>, F: FnMut(usize, Point) -> T>(
+        &mut self,
+        callback: &mut F,
+        old_tree: &Tree,
+        options: Option<ParseOptions>,
+    ) -> Result<Tree, IncrementalParseError> {
+        self.parse_input::<T, F, _>(
+            callback,
+            Some(old_tree),
+            options,
+            read_utf8::<T, F>,
+            ffi::TSInputEncodingUTF8,
+            None,
+            |parser, tree, input| {
+                let tree = tree.ok_or(IncrementalParseError::NoTree)?;
+                let c_fresh_tree =
+                    unsafe { ffi::ts_parser_parse(parser.0.as_ptr(), ptr::null(), input) };
+                let fresh_tree = NonNull::new(c_fresh_tree)
+                    .map(Tree)
+                    .ok_or(IncrementalParseError::NoTree)?;
+                let incremental = tree.root_node().to_sexp();
+                let fresh = fresh_tree.root_node().to_sexp();
+                if incremental == fresh {
+                    Ok(tree)
+                } else {
+                    Err(IncrementalParseError::Mismatch {
+                        diff: sexp_diff(&incremental, &fresh),
+                        incremental,
+                        fresh,
+                    })
                 }
-            } else {
-                empty_options
-            }
-        } else {
-            empty_options
-        };
-
-        // A pointer to this payload is passed on every call to the `read` C function.
-        // The payload contains two things:
-        // 1. A reference to the rust `callback`.
-        // 2. The text that was returned from the previous call to `callback`. This allows the
-        //    callback to return owned values like vectors.
-        let mut payload: Payload<F, T> = (callback, None);
-
-        let c_input = ffi::TSInput {
-            payload: ptr::addr_of_mut!(payload).cast::<c_void>(),
-            read: Some(read::<T, F>),
-            encoding: ffi::TSInputEncodingUTF8,
-            decode: None,
-        };
-
-        let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
-        unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
-            );
-
-            NonNull::new(c_new_tree).map(Tree)
-        }
+            },
+        )
     }
 
     /// Parse a slice of UTF16 little-endian text.
     ///
+    /// The byte offsets and columns in the resulting tree, as well as those in
+    /// any [`InputEdit`]s applied to it, are measured in bytes, which is two per
+    /// UTF-16 code unit. Use [`Node::utf16_code_unit_start`] and
+    /// [`Node::utf16_code_unit_end`] to get offsets in code units.
+    ///
     /// # Arguments:
     /// * `text` The UTF16-encoded text to parse.
     /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
@@ -986,18 +2106,28 @@ impl Parser {
         input: impl AsRef<[u16]>,
         old_tree: Option<&Tree>,
     ) -> Option<Tree> {
-        let code_points = input.as_ref();
-        let len = code_points.len();
+        self.parse_utf16_le_slice(input.as_ref(), old_tree, None)
+    }
+
+    /// Parse a slice of UTF16 little-endian text, with options.
+    ///
+    /// # Arguments:
+    /// * `text` The UTF16-encoded text to parse.
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    /// * `options` Options for parsing the text. This can be used to set a progress callback.
+    pub fn parse_utf16_le_slice(
+        &mut self,
+        text: &[u16],
+        old_tree: Option<&Tree>,
+        options: Option<ParseOptions>,
+    ) -> Option<Tree> {
+        let len = text.len();
         self.parse_utf16_le_with_options(
-            &mut |i, _| {
-                if i < len {
-                    &code_points[i..]
-                } else {
-                    Default::default()
-                }
-            },
+            &mut |i, _| if i < len { &text[i..] } else { &[] },
             old_tree,
-            None,
+            options,
         )
     }
 
@@ -1018,22 +2148,6 @@ impl Parser {
         old_tree: Option<&Tree>,
         options: Option<ParseOptions>,
     ) -> Option<Tree> {
-        type Payload<'a, F, T> = (&'a mut F, Option<T>);
-
-        unsafe extern "C" fn progress(state: *mut ffi::TSParseState) -> bool {
-            unsafe {
-                let callback = (*state)
-                    .payload
-                    .cast::<ParseProgressCallback>()
-                    .as_mut()
-                    .unwrap();
-                match callback(&ParseState::from_raw(state)) {
-                    ControlFlow::Continue(()) => false,
-                    ControlFlow::Break(()) => true,
-                }
-            }
-        }
-
         // This C function is passed to Tree-sitter as the input callback.
         unsafe extern "C" fn read<T: AsRef<[u16]>, F: FnMut(usize, Point) -> T>(
             payload: *mut c_void,
@@ -1042,7 +2156,8 @@ impl Parser {
             bytes_read: *mut u32,
         ) -> *const c_char {
             unsafe {
-                let (callback, text) = payload.cast::<Payload<F, T>>().as_mut().unwrap();
+                let (callback, text, on_read) =
+                    payload.cast::<ReadPayload<F, T>>().as_mut().unwrap();
                 *text = Some(callback(
                     (byte_offset / 2) as usize,
                     Point {
@@ -1051,56 +2166,23 @@ impl Parser {
                     },
                 ));
                 let slice = text.as_ref().unwrap().as_ref();
+                if let Some(on_read) = on_read {
+                    on_read((byte_offset / 2) as usize, slice.len());
+                }
                 *bytes_read = slice.len() as u32 * 2;
                 slice.as_ptr().cast::<c_char>()
             }
         }
 
-        let empty_options = ffi::TSParseOptions {
-            payload: ptr::null_mut(),
-            progress_callback: None,
-        };
-
-        let mut callback_ptr;
-        let parse_options = if let Some(options) = options {
-            if let Some(cb) = options.progress_callback {
-                callback_ptr = cb;
-                ffi::TSParseOptions {
-                    payload: core::ptr::addr_of_mut!(callback_ptr).cast::<c_void>(),
-                    progress_callback: Some(progress),
-                }
-            } else {
-                empty_options
-            }
-        } else {
-            empty_options
-        };
-
-        // A pointer to this payload is passed on every call to the `read` C function.
-        // The payload contains two things:
-        // 1. A reference to the rust `callback`.
-        // 2. The text that was returned from the previous call to `callback`. This allows the
-        //    callback to return owned values like vectors.
-        let mut payload: Payload<F, T> = (callback, None);
-
-        let c_input = ffi::TSInput {
-            payload: core::ptr::addr_of_mut!(payload).cast::<c_void>(),
-            read: Some(read::<T, F>),
-            encoding: ffi::TSInputEncodingUTF16LE,
-            decode: None,
-        };
-
-        let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
-        unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
-            );
-
-            NonNull::new(c_new_tree).map(Tree)
-        }
+        self.parse_input::<T, F, _>(
+            callback,
+            old_tree,
+            options,
+            read::<T, F>,
+            ffi::TSInputEncodingUTF16LE,
+            None,
+            |_, tree, _| tree,
+        )
     }
 
     /// Parse a slice of UTF16 big-endian text.
@@ -1115,12 +2197,28 @@ impl Parser {
         input: impl AsRef<[u16]>,
         old_tree: Option<&Tree>,
     ) -> Option<Tree> {
-        let code_points = input.as_ref();
-        let len = code_points.len();
+        self.parse_utf16_be_slice(input.as_ref(), old_tree, None)
+    }
+
+    /// Parse a slice of UTF16 big-endian text, with options.
+    ///
+    /// # Arguments:
+    /// * `text` The UTF16-encoded text to parse.
+    /// * `old_tree` A previous syntax tree parsed from the same document. If the text of the
+    ///   document has changed since `old_tree` was created, then you must edit `old_tree` to match
+    ///   the new text using [`Tree::edit`].
+    /// * `options` Options for parsing the text. This can be used to set a progress callback.
+    pub fn parse_utf16_be_slice(
+        &mut self,
+        text: &[u16],
+        old_tree: Option<&Tree>,
+        options: Option<ParseOptions>,
+    ) -> Option<Tree> {
+        let len = text.len();
         self.parse_utf16_be_with_options(
-            &mut |i, _| if i < len { &code_points[i..] } else { &[] },
+            &mut |i, _| if i < len { &text[i..] } else { &[] },
             old_tree,
-            None,
+            options,
         )
     }
 
@@ -1141,23 +2239,6 @@ impl Parser {
         old_tree: Option<&Tree>,
         options: Option<ParseOptions>,
     ) -> Option<Tree> {
-        type Payload<'a, F, T> = (&'a mut F, Option<T>);
-
-        // This C function is passed to Tree-sitter as the progress callback.
-        unsafe extern "C" fn progress(state: *mut ffi::TSParseState) -> bool {
-            unsafe {
-                let callback = (*state)
-                    .payload
-                    .cast::<ParseProgressCallback>()
-                    .as_mut()
-                    .unwrap();
-                match callback(&ParseState::from_raw(state)) {
-                    ControlFlow::Continue(()) => false,
-                    ControlFlow::Break(()) => true,
-                }
-            }
-        }
-
         // This C function is passed to Tree-sitter as the input callback.
         unsafe extern "C" fn read<T: AsRef<[u16]>, F: FnMut(usize, Point) -> T>(
             payload: *mut c_void,
@@ -1166,7 +2247,8 @@ impl Parser {
             bytes_read: *mut u32,
         ) -> *const c_char {
             unsafe {
-                let (callback, text) = payload.cast::<Payload<F, T>>().as_mut().unwrap();
+                let (callback, text, on_read) =
+                    payload.cast::<ReadPayload<F, T>>().as_mut().unwrap();
                 *text = Some(callback(
                     (byte_offset / 2) as usize,
                     Point {
@@ -1175,56 +2257,23 @@ impl Parser {
                     },
                 ));
                 let slice = text.as_ref().unwrap().as_ref();
+                if let Some(on_read) = on_read {
+                    on_read((byte_offset / 2) as usize, slice.len());
+                }
                 *bytes_read = slice.len() as u32 * 2;
                 slice.as_ptr().cast::<c_char>()
             }
         }
 
-        let empty_options = ffi::TSParseOptions {
-            payload: ptr::null_mut(),
-            progress_callback: None,
-        };
-
-        let mut callback_ptr;
-        let parse_options = if let Some(options) = options {
-            if let Some(cb) = options.progress_callback {
-                callback_ptr = cb;
-                ffi::TSParseOptions {
-                    payload: core::ptr::addr_of_mut!(callback_ptr).cast::<c_void>(),
-                    progress_callback: Some(progress),
-                }
-            } else {
-                empty_options
-            }
-        } else {
-            empty_options
-        };
-
-        // A pointer to this payload is passed on every call to the `read` C function.
-        // The payload contains two things:
-        // 1. A reference to the rust `callback`.
-        // 2. The text that was returned from the previous call to `callback`. This allows the
-        //    callback to return owned values like vectors.
-        let mut payload: Payload<F, T> = (callback, None);
-
-        let c_input = ffi::TSInput {
-            payload: core::ptr::addr_of_mut!(payload).cast::<c_void>(),
-            read: Some(read::<T, F>),
-            encoding: ffi::TSInputEncodingUTF16BE,
-            decode: None,
-        };
-
-        let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
-        unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
-            );
-
-            NonNull::new(c_new_tree).map(Tree)
-        }
+        self.parse_input::<T, F, _>(
+            callback,
+            old_tree,
+            options,
+            read::<T, F>,
+            ffi::TSInputEncodingUTF16BE,
+            None,
+            |_, tree, _| tree,
+        )
     }
 
     /// Parse text provided in chunks by a callback using a custom encoding.
@@ -1243,28 +2292,15 @@ impl Parser {
     /// [`Decode`] trait. This trait has a single method, [`decode`](Decode::decode), which takes a
     /// slice of bytes and returns a tuple of the code point and the number of bytes consumed.
     /// The `decode` method should return `-1` for the code point if decoding fails.
+    ///
+    /// If the `decode` method consumes zero bytes from a non-empty slice, parsing stops at that
+    /// position. Use [`Parser::decode_stop`] afterward to find out where this happened.
     pub fn parse_custom_encoding<D: Decode, T: AsRef<[u8]>, F: FnMut(usize, Point) -> T>(
         &mut self,
         callback: &mut F,
         old_tree: Option<&Tree>,
         options: Option<ParseOptions>,
     ) -> Option<Tree> {
-        type Payload<'a, F, T> = (&'a mut F, Option<T>);
-
-        unsafe extern "C" fn progress(state: *mut ffi::TSParseState) -> bool {
-            unsafe {
-                let callback = (*state)
-                    .payload
-                    .cast::<ParseProgressCallback>()
-                    .as_mut()
-                    .unwrap();
-                match callback(&ParseState::from_raw(state)) {
-                    ControlFlow::Continue(()) => false,
-                    ControlFlow::Break(()) => true,
-                }
-            }
-        }
-
         // At compile time, create a C-compatible callback that calls the custom `decode` method.
         unsafe extern "C" fn decode_fn<D: Decode>(
             data: *const u8,
@@ -1288,62 +2324,69 @@ impl Parser {
             bytes_read: *mut u32,
         ) -> *const c_char {
             unsafe {
-                let (callback, text) = payload.cast::<Payload<F, T>>().as_mut().unwrap();
+                let (callback, text, on_read) =
+                    payload.cast::<ReadPayload<F, T>>().as_mut().unwrap();
                 *text = Some(callback(byte_offset as usize, position.into()));
                 let slice = text.as_ref().unwrap().as_ref();
+                if let Some(on_read) = on_read {
+                    on_read(byte_offset as usize, slice.len());
+                }
                 *bytes_read = slice.len() as u32;
                 slice.as_ptr().cast::<c_char>()
             }
         }
 
-        let empty_options = ffi::TSParseOptions {
-            payload: ptr::null_mut(),
-            progress_callback: None,
-        };
-
-        let mut callback_ptr;
-        let parse_options = if let Some(options) = options {
-            if let Some(cb) = options.progress_callback {
-                callback_ptr = cb;
-                ffi::TSParseOptions {
-                    payload: core::ptr::addr_of_mut!(callback_ptr).cast::<c_void>(),
-                    progress_callback: Some(progress),
-                }
-            } else {
-                empty_options
-            }
-        } else {
-            empty_options
-        };
-
-        // A pointer to this payload is passed on every call to the `read` C function.
-        // The payload contains two things:
-        // 1. A reference to the rust `callback`.
-        // 2. The text that was returned from the previous call to `callback`. This allows the
-        //    callback to return owned values like vectors.
-        let mut payload: Payload<F, T> = (callback, None);
-
-        let c_input = ffi::TSInput {
-            payload: core::ptr::addr_of_mut!(payload).cast::<c_void>(),
-            read: Some(read::<T, F>),
-            encoding: ffi::TSInputEncodingCustom,
+        self.parse_input::<T, F, _>(
+            callback,
+            old_tree,
+            options,
+            read::<T, F>,
+            ffi::TSInputEncodingCustom,
             // Use this custom decode callback
-            decode: Some(decode_fn::<D>),
-        };
-
-        let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
-        unsafe {
-            let c_new_tree = ffi::ts_parser_parse_with_options(
-                self.0.as_ptr(),
-                c_old_tree,
-                c_input,
-                parse_options,
-            );
+            Some(decode_fn::<D>),
+            |_, tree, _| tree,
+        )
+    }
 
-            NonNull::new(c_new_tree).map(Tree)
+    /// Get the reason that the parser stopped reading input during the most
+    /// recent parse.
+    ///
+    /// This is mostly useful for diagnosing bugs in [`Decode`] implementations
+    /// used with [`Parser::parse_custom_encoding`]. A decoder that consumes zero
+    /// bytes from a non-empty slice of input cannot make progress, so the parser
+    /// stops reading at that offset and reports a [`DecodeStop::Stall`].
+    #[doc(alias = "ts_parser_did_decode_stall")]
+    #[must_use]
+    pub fn decode_stop(&self) -> DecodeStop {
+        let mut byte_offset = 0u32;
+        if unsafe {
+            ffi::ts_parser_did_decode_stall(self.0.as_ptr(), core::ptr::addr_of_mut!(byte_offset))
+        } {
+            DecodeStop::Stall(byte_offset as usize)
+        } else {
+            DecodeStop::Eof
         }
     }
 
+    /// Get the time that the most recent parse spent in each of its phases.
+    ///
+    /// Returns `None` unless the parse was performed with
+    /// [`ParseOptions::profile_phases`] enabled.
+    #[doc(alias = "ts_parser_phase_timings")]
+    #[must_use]
+    pub fn phase_timings(&self) -> Option<ParsePhaseTimings> {
+        let mut timings = MaybeUninit::<ffi::TSParsePhaseTimings>::uninit();
+        unsafe { ffi::ts_parser_phase_timings(self.0.as_ptr(), timings.as_mut_ptr()) }.then(|| {
+            let timings = unsafe { timings.assume_init() };
+            ParsePhaseTimings {
+                lexing: Duration::from_micros(timings.lexing),
+                reduction: Duration::from_micros(timings.reduction),
+                error_recovery: Duration::from_micros(timings.error_recovery),
+                balancing: Duration::from_micros(timings.balancing),
+            }
+        })
+    }
+
     /// Instruct the parser to start the next parse from the beginning.
     ///
     /// If the parser previously failed because of a callback, then by default,
@@ -1355,6 +2398,65 @@ impl Parser {
         unsafe { ffi::ts_parser_reset(self.0.as_ptr()) }
     }
 
+    /// Check whether the parser has state left over from a parse that was
+    /// halted by a progress callback.
+    ///
+    /// If this returns `true`, the next call to [`parse`](Parser::parse) or
+    /// other parsing functions will resume that parse instead of starting a
+    /// new one. Call [`discard_state`](Parser::discard_state) to start fresh.
+    #[doc(alias = "ts_parser_is_resumable")]
+    #[must_use]
+    pub fn is_resumable(&self) -> bool {
+        unsafe { ffi::ts_parser_is_resumable(self.0.as_ptr()) }
+    }
+
+    /// Get the byte offset that a halted parse had reached.
+    ///
+    /// A parse that is halted by a progress callback does not produce a tree,
+    /// so this position is recorded on the parser instead. It is the same as the
+    /// last [`ParseState::current_byte_offset`] that was reported. Returns `None`
+    /// if the parser has no state left over from a halted parse.
+    #[doc(alias = "ts_parser_halted_byte_offset")]
+    #[must_use]
+    pub fn parsed_through_byte(&self) -> Option<usize> {
+        let mut byte_offset = 0u32;
+        unsafe {
+            ffi::ts_parser_halted_byte_offset(self.0.as_ptr(), core::ptr::addr_of_mut!(byte_offset))
+        }
+        .then_some(byte_offset as usize)
+    }
+
+    /// Discard any state left over from a halted parse, so that the next parse
+    /// starts from the beginning.
+    ///
+    /// This is equivalent to [`reset`](Parser::reset).
+    #[doc(alias = "ts_parser_reset")]
+    pub fn discard_state(&mut self) {
+        self.reset();
+    }
+
+    /// Return the parser to the configuration of a new parser.
+    ///
+    /// Unlike [`reset`](Parser::reset), which only discards the state of a
+    /// halted parse, this also removes the parser's language, logger, and
+    /// debugging graph output, includes the entire document again, and
+    /// re-enables the external scanner. This is useful for reusing a parser,
+    /// for example from a pool, for an unrelated document in another language.
+    /// A wasm store that was given to the parser with `set_wasm_store` is kept.
+    pub fn clear(&mut self) {
+        #[cfg(feature = "std")]
+        #[cfg(not(target_os = "wasi"))]
+        {
+            self.stop_printing_dot_graphs();
+        }
+        self.set_logger(None);
+        unsafe {
+            ffi::ts_parser_set_language(self.0.as_ptr(), ptr::null());
+            ffi::ts_parser_set_included_ranges(self.0.as_ptr(), ptr::null(), 0);
+        }
+        self.set_use_external_scanner(true);
+    }
+
     /// Set the ranges of text that the parser should include when parsing.
     ///
     /// By default, the parser will always include entire documents. This
@@ -1370,8 +2472,8 @@ impl Parser {
     ///     ranges[i].end_byte <= ranges[i + 1].start_byte
     /// ```
     /// If this requirement is not satisfied, method will return
-    /// [`IncludedRangesError`] error with an offset in the passed ranges
-    /// slice pointing to a first incorrect range.
+    /// [`IncludedRangesError`] error with the index of the offending range
+    /// and the reason that it was rejected. See also [`validate_included_ranges`].
     #[doc(alias = "ts_parser_set_included_ranges")]
     pub fn set_included_ranges(&mut self, ranges: &[Range]) -> Result<(), IncludedRangesError> {
         let ts_ranges = ranges.iter().copied().map(Into::into).collect::<Vec<_>>();
@@ -1386,15 +2488,68 @@ impl Parser {
         if result {
             Ok(())
         } else {
-            let mut prev_end_byte = 0;
-            for (i, range) in ranges.iter().enumerate() {
-                if range.start_byte < prev_end_byte || range.end_byte < range.start_byte {
-                    return Err(IncludedRangesError(i));
+            validate_included_ranges(ranges)?;
+            Err(IncludedRangesError {
+                index: 0,
+                kind: RangeErrorKind::NotOrdered,
+            })
+        }
+    }
+
+    /// Set the ranges of text that the parser should include when parsing, in
+    /// any order.
+    ///
+    /// This is like [`set_included_ranges`](Parser::set_included_ranges), but the
+    /// ranges are sorted by their position in the document first, so ranges that
+    /// were collected from several sources can be passed as they are. Ranges that
+    /// touch, where one ends at the byte where the next one starts, are merged into
+    /// a single range. Ranges that overlap are still rejected, with an
+    /// [`IncludedRangesError`] whose index refers to the position of the offending
+    /// range in the given slice.
+    pub fn set_included_ranges_sorted(
+        &mut self,
+        ranges: &[Range],
+    ) -> Result<(), IncludedRangesError> {
+        let mut sorted = ranges.iter().copied().enumerate().collect::<Vec<_>>();
+        sorted.sort_unstable_by_key(|(_, range)| *range);
+
+        let mut merged = Vec::<Range>::with_capacity(sorted.len());
+        for (index, range) in sorted {
+            if range.end_byte < range.start_byte {
+                return Err(IncludedRangesError {
+                    index,
+                    kind: RangeErrorKind::EndBeforeStart,
+                });
+            }
+            match merged.last_mut() {
+                Some(previous) if range.start_byte < previous.end_byte => {
+                    return Err(IncludedRangesError {
+                        index,
+                        kind: RangeErrorKind::Overlapping,
+                    });
+                }
+                Some(previous) if range.start_byte == previous.end_byte => {
+                    previous.end_byte = range.end_byte;
+                    previous.end_point = range.end_point;
                 }
-                prev_end_byte = range.end_byte;
+                _ => merged.push(range),
             }
-            Err(IncludedRangesError(0))
         }
+        self.set_included_ranges(&merged)
+    }
+
+    /// Set both the language and the included ranges that the parser should
+    /// use, as when parsing an injected language.
+    ///
+    /// The ranges are validated before anything is changed, so if either the
+    /// language or the ranges are rejected, the parser keeps its previous
+    /// configuration. See [`Parser::set_language`] and
+    /// [`Parser::set_included_ranges`].
+    pub fn configure(&mut self, language: &Language, ranges: &[Range]) -> Result<(), ParserError> {
+        validate_included_ranges(ranges)?;
+        self.set_language(language)?;
+        self.set_included_ranges(ranges)?;
+        Ok(())
     }
 
     /// Get the ranges of text that the parser will include when parsing.
@@ -1411,6 +2566,32 @@ impl Parser {
     }
 }
 
+/// Check that the given ranges could be passed to [`Parser::set_included_ranges`].
+///
+/// The ranges must be ordered from earliest to latest in the document, and they
+/// must not overlap. This performs the same checks as `set_included_ranges`,
+/// without needing a parser, so that ranges computed from several sources can
+/// be validated and fixed before parsing.
+pub fn validate_included_ranges(ranges: &[Range]) -> Result<(), IncludedRangesError> {
+    let mut prev_start_byte = 0;
+    let mut prev_end_byte = 0;
+    for (index, range) in ranges.iter().enumerate() {
+        let kind = if range.end_byte < range.start_byte {
+            RangeErrorKind::EndBeforeStart
+        } else if range.start_byte < prev_start_byte {
+            RangeErrorKind::NotOrdered
+        } else if range.start_byte < prev_end_byte {
+            RangeErrorKind::Overlapping
+        } else {
+            prev_start_byte = range.start_byte;
+            prev_end_byte = range.end_byte;
+            continue;
+        };
+        return Err(IncludedRangesError { index, kind });
+    }
+    Ok(())
+}
+
 impl Drop for Parser {
     fn drop(&mut self) {
         #[cfg(feature = "std")]
@@ -1428,27 +2609,122 @@ unsafe extern "C" {
     fn _open_osfhandle(osfhandle: isize, flags: core::ffi::c_int) -> core::ffi::c_int;
 }
 
-impl Tree {
-    /// Get the root node of the syntax tree.
-    #[doc(alias = "ts_tree_root_node")]
+impl Tree {
+    /// Get the root node of the syntax tree.
+    ///
+    /// A tree always has a root node, even when the text is empty or contains
+    /// only whitespace. If the grammar's start rule accepts empty text, the root
+    /// node for such text has no children and no errors, and it starts and ends
+    /// at the end of the text.
+    #[doc(alias = "ts_tree_root_node")]
+    #[must_use]
+    pub fn root_node(&self) -> Node {
+        Node::new(unsafe { ffi::ts_tree_root_node(self.0.as_ptr()) }).unwrap()
+    }
+
+    /// Get the root node of the syntax tree, but with its position shifted
+    /// forward by the given offset.
+    #[doc(alias = "ts_tree_root_node_with_offset")]
+    #[must_use]
+    pub fn root_node_with_offset(&self, offset_bytes: usize, offset_extent: Point) -> Node {
+        Node::new(unsafe {
+            ffi::ts_tree_root_node_with_offset(
+                self.0.as_ptr(),
+                offset_bytes as u32,
+                offset_extent.into(),
+            )
+        })
+        .unwrap()
+    }
+
+    /// Get the number of nodes in the tree.
+    ///
+    /// This counts every node that can be reached with a [`TreeCursor`], including
+    /// the root, anonymous nodes, `ERROR` nodes and `MISSING` nodes. It doesn't
+    /// count hidden nodes, which don't appear in the tree. The parser tracks this
+    /// number as it builds the tree, so this takes constant time.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.root_node().descendant_count()
+    }
+
+    /// Get the number of named nodes in the tree.
+    ///
+    /// This counts the same nodes as [`Tree::node_count`], except for anonymous
+    /// ones. `ERROR` nodes are named, and `MISSING` nodes are counted if the
+    /// missing node is named. Unlike [`Tree::node_count`], this is not tracked by
+    /// the parser, so it takes time proportional to the size of the tree.
+    #[must_use]
+    pub fn named_node_count(&self) -> usize {
+        let mut count = 0;
+        let mut cursor = self.walk();
+        'outer: loop {
+            if cursor.node().is_named() {
+                count += 1;
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+        count
+    }
+
+    /// Find the node of the given kind that starts at the given byte offset.
+    ///
+    /// Unlike [`Node::id`], a node's start byte and kind stay the same when the
+    /// same text is parsed again, so they can be stored to find the corresponding
+    /// node in a later tree. If several nested nodes of the same kind start at the
+    /// offset, the innermost one is returned.
     #[must_use]
-    pub fn root_node(&self) -> Node {
-        Node::new(unsafe { ffi::ts_tree_root_node(self.0.as_ptr()) }).unwrap()
+    pub fn node_at(&self, byte_offset: usize, kind: &str) -> Option<Node> {
+        let mut node = self
+            .root_node()
+            .descendant_for_byte_range(byte_offset, byte_offset)?;
+        loop {
+            if node.start_byte() == byte_offset && node.kind() == kind {
+                return Some(node);
+            }
+            if node.start_byte() < byte_offset {
+                return None;
+            }
+            node = node.parent()?;
+        }
     }
 
-    /// Get the root node of the syntax tree, but with its position shifted
-    /// forward by the given offset.
-    #[doc(alias = "ts_tree_root_node_with_offset")]
+    /// Rebuild the given source text by concatenating the text of the tree's leaf
+    /// nodes, in order.
+    ///
+    /// The bytes between consecutive leaves, such as whitespace or text outside of
+    /// the tree's included ranges, are copied from `source` as they are. If the
+    /// tree is a complete parse of `source`, the result is equal to `source`, so
+    /// any difference indicates that the leaves' byte ranges are inconsistent with
+    /// the text, for example because the tree was edited but not reparsed.
     #[must_use]
-    pub fn root_node_with_offset(&self, offset_bytes: usize, offset_extent: Point) -> Node {
-        Node::new(unsafe {
-            ffi::ts_tree_root_node_with_offset(
-                self.0.as_ptr(),
-                offset_bytes as u32,
-                offset_extent.into(),
-            )
-        })
-        .unwrap()
+    pub fn reconstruct_source(&self, source: &[u8]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(source.len());
+        let mut cursor = self.walk();
+        let mut end_byte = 0;
+        'outer: loop {
+            if !cursor.goto_first_child() {
+                let node = cursor.node();
+                let (start, end) = (node.start_byte(), node.end_byte());
+                result.extend_from_slice(source.get(end_byte..start).unwrap_or_default());
+                result.extend_from_slice(source.get(start..end).unwrap_or_default());
+                end_byte = end_byte.max(end);
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        break 'outer;
+                    }
+                }
+            }
+        }
+        result.extend_from_slice(source.get(end_byte..).unwrap_or_default());
+        result
     }
 
     /// Get the language that was used to parse the syntax tree.
@@ -1461,23 +2737,132 @@ impl Tree {
         )
     }
 
+    /// Create a deep copy of the syntax tree, which does not share any nodes
+    /// with this one.
+    ///
+    /// This takes time proportional to the number of nodes in the tree. In most
+    /// cases, [`Tree::clone`] is sufficient, as edits to a clone never affect the
+    /// tree that it was cloned from.
+    #[doc(alias = "ts_tree_deep_copy")]
+    #[must_use]
+    pub fn deep_copy(&self) -> Self {
+        unsafe {
+            Self(NonNull::new_unchecked(ffi::ts_tree_deep_copy(
+                self.0.as_ptr(),
+            )))
+        }
+    }
+
     /// Edit the syntax tree to keep it in sync with source code that has been
     /// edited.
     ///
     /// You must describe the edit both in terms of byte offsets and in terms of
     /// row/column coordinates.
+    ///
+    /// The edit is not checked, and a malformed edit leaves the tree in a state
+    /// that causes incorrect incremental parses. Use [`Tree::try_edit`] to check
+    /// the edit first.
     #[doc(alias = "ts_tree_edit")]
     pub fn edit(&mut self, edit: &InputEdit) {
         let edit = edit.into();
         unsafe { ffi::ts_tree_edit(self.0.as_ptr(), &raw const edit) };
     }
 
+    /// Edit the syntax tree like [`Tree::edit`], after checking that the edit is
+    /// well-formed with [`InputEdit::validate`].
+    ///
+    /// If the edit is malformed, the tree is left unchanged.
+    pub fn try_edit(&mut self, edit: &InputEdit) -> Result<(), InputEditError> {
+        edit.validate()?;
+        self.edit(edit);
+        Ok(())
+    }
+
     /// Create a new [`TreeCursor`] starting from the root of the tree.
     #[must_use]
     pub fn walk(&self) -> TreeCursor {
         self.root_node().walk()
     }
 
+    /// Iterate over the kind id and range of every named node in the tree, in
+    /// document order, starting with the root node.
+    ///
+    /// This is a lightweight traversal for building flat summaries of large
+    /// documents, like outlines or minimaps. It uses a single [`TreeCursor`]
+    /// internally, so the only allocation is the cursor's stack, rather than
+    /// one per node.
+    pub fn named_node_summaries(&self) -> impl Iterator<Item = (u16, Range)> + '_ {
+        let mut cursor = self.walk();
+        let mut done = false;
+        iter::from_fn(move || {
+            while !done {
+                let node = cursor.node();
+                if !cursor.goto_first_child() {
+                    while !cursor.goto_next_sibling() {
+                        if !cursor.goto_parent() {
+                            done = true;
+                            break;
+                        }
+                    }
+                }
+                if node.is_named() {
+                    return Some((node.kind_id(), node.range()));
+                }
+            }
+            None
+        })
+    }
+
+    /// Get the ranges of the syntax tree that are marked as changed by calls to
+    /// [`Tree::edit`] since it was parsed.
+    ///
+    /// Each range covers the smallest nodes that were affected by an edit, and
+    /// adjacent or overlapping ranges are merged. This can be used to decide
+    /// whether reparsing is worthwhile before a new tree exists. Once the tree
+    /// has been reparsed, use [`Tree::changed_ranges`] instead.
+    pub fn edited_ranges(&self) -> impl Iterator<Item = Range> + use<> {
+        let mut ranges = Vec::<Range>::new();
+        let mut cursor = self.walk();
+        if cursor.node().has_changes() {
+            'outer: loop {
+                if cursor.goto_first_child() {
+                    loop {
+                        if cursor.node().has_changes() {
+                            continue 'outer;
+                        }
+                        if !cursor.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                    cursor.goto_parent();
+                }
+
+                let range = cursor.node().range();
+                match ranges.last_mut() {
+                    Some(last) if last.end_byte >= range.start_byte => {
+                        if range.end_byte > last.end_byte {
+                            last.end_byte = range.end_byte;
+                            last.end_point = range.end_point;
+                        }
+                    }
+                    _ => ranges.push(range),
+                }
+
+                loop {
+                    while cursor.goto_next_sibling() {
+                        if cursor.node().has_changes() {
+                            continue 'outer;
+                        }
+                    }
+                    if !cursor.goto_parent() {
+                        break 'outer;
+                    }
+                }
+            }
+        }
+        ranges.into_iter()
+    }
+
     /// Compare this old edited syntax tree to a new syntax tree representing
     /// the same document, returning a sequence of ranges whose syntactic
     /// structure has changed.
@@ -1487,6 +2872,10 @@ impl Tree {
     /// call this method right after calling one of the [`Parser::parse`]
     /// functions. Call it on the old tree that was passed to parse, and
     /// pass the new tree that was returned from `parse`.
+    ///
+    /// Each returned [`Range`] carries both byte offsets and row/column
+    /// [`Point`]s, where columns are measured in bytes. If you need offsets in
+    /// UTF-16 code units instead, see [`Tree::changed_ranges_utf16`].
     #[doc(alias = "ts_tree_get_changed_ranges")]
     #[must_use]
     pub fn changed_ranges(&self, other: &Self) -> impl ExactSizeIterator<Item = Range> {
@@ -1501,6 +2890,88 @@ impl Tree {
         }
     }
 
+    /// Like [`Tree::changed_ranges`], but with offsets measured in UTF-16 code
+    /// units of the given UTF-8 `source`, which must be the text that the new
+    /// tree was parsed from.
+    ///
+    /// In the returned ranges, `start_byte` and `end_byte` are offsets in UTF-16
+    /// code units from the beginning of the document, and the `column` of each
+    /// point is the number of UTF-16 code units from the start of its row. This
+    /// matches the positions used by editors and protocols like LSP. Invalid
+    /// UTF-8 sequences count as one code unit per replacement character.
+    #[must_use]
+    pub fn changed_ranges_utf16(&self, other: &Self, source: &[u8]) -> Vec<Range> {
+        let mut byte = 0;
+        let mut code_units = 0;
+        let mut to_utf16 = |offset: usize, point: Point| {
+            let offset = offset.min(source.len());
+            if offset < byte {
+                byte = 0;
+                code_units = 0;
+            }
+            code_units += utf16_len(&source[byte..offset]);
+            byte = offset;
+            let row_start = offset.saturating_sub(point.column);
+            (
+                code_units,
+                Point::new(point.row, utf16_len(&source[row_start..offset])),
+            )
+        };
+        self.changed_ranges(other)
+            .map(|range| {
+                let (start_byte, start_point) = to_utf16(range.start_byte, range.start_point);
+                let (end_byte, end_point) = to_utf16(range.end_byte, range.end_point);
+                Range {
+                    start_byte,
+                    end_byte,
+                    start_point,
+                    end_point,
+                }
+            })
+            .collect()
+    }
+
+    /// Get the smallest set of nodes in this syntax tree that cover the ranges
+    /// whose syntactic structure changed since the given old tree.
+    ///
+    /// The `old` tree must be the one that was edited and passed to the parse
+    /// that produced this tree; see [`Tree::changed_ranges`]. Every returned node
+    /// lies within a changed range, or is a leaf that overlaps one, and the nodes
+    /// are returned in document order without overlapping each other. This is
+    /// useful for re-running node-oriented analyses only on the parts of a
+    /// document that were reparsed differently.
+    pub fn changed_subtrees<'tree>(
+        &'tree self,
+        old: &Self,
+    ) -> impl Iterator<Item = Node<'tree>> + use<'tree> {
+        let ranges = old.changed_ranges(self).collect::<Vec<_>>();
+        let mut nodes = Vec::new();
+        let mut cursor = self.walk();
+        'outer: loop {
+            let node = cursor.node();
+            let (start, end) = (node.start_byte(), node.end_byte());
+            let mut overlapping = ranges
+                .iter()
+                .filter(|range| range.start_byte < end && start < range.end_byte)
+                .peekable();
+            if overlapping.peek().is_some() {
+                if node.child_count() == 0
+                    || overlapping.any(|range| range.start_byte <= start && end <= range.end_byte)
+                {
+                    nodes.push(node);
+                } else if cursor.goto_first_child() {
+                    continue;
+                }
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+        nodes.into_iter()
+    }
+
     /// Get the included ranges that were used to parse the syntax tree.
     #[doc(alias = "ts_tree_included_ranges")]
     #[must_use]
@@ -1515,6 +2986,40 @@ impl Tree {
         }
     }
 
+    /// Get the number of included ranges that contributed content to the
+    /// syntax tree.
+    ///
+    /// A range contributes if any token in the tree overlaps it. Ranges that
+    /// were empty, that lay outside of the input, or that only contained
+    /// whitespace are not counted. This distinguishes a range that produced no
+    /// content, like an empty interpolation, from one that parsed successfully.
+    #[must_use]
+    pub fn used_range_count(&self) -> usize {
+        let ranges = self.included_ranges();
+        let mut used = 0;
+        let mut index = 0;
+        let mut cursor = self.walk();
+        'outer: loop {
+            while cursor.goto_first_child() {}
+            let node = cursor.node();
+            if node.start_byte() < node.end_byte() {
+                while index < ranges.len() && ranges[index].end_byte <= node.start_byte() {
+                    index += 1;
+                }
+                while index < ranges.len() && ranges[index].start_byte < node.end_byte() {
+                    used += 1;
+                    index += 1;
+                }
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+        used
+    }
+
     /// Print a graph of the tree to the given file descriptor.
     /// The graph is formatted in the DOT language. You may want to pipe this
     /// graph directly to a `dot(1)` process in order to generate SVG
@@ -1556,6 +3061,8 @@ impl Drop for Tree {
 }
 
 impl Clone for Tree {
+    /// Create a shallow copy of the syntax tree. This is very fast.
+    #[doc(alias = "ts_tree_copy")]
     fn clone(&self) -> Self {
         unsafe { Self(NonNull::new_unchecked(ffi::ts_tree_copy(self.0.as_ptr()))) }
     }
@@ -1576,6 +3083,9 @@ impl<'tree> Node<'tree> {
     /// - A node not marked as having changes does not guarantee it was reused.
     ///
     /// - If a node is marked as having changed in the old tree, it will not be reused.
+    ///
+    /// To find a node again in a tree that may have been parsed from scratch, use
+    /// [`Tree::node_at`] with the node's start byte and kind instead.
     #[must_use]
     pub fn id(&self) -> usize {
         self.0.id as usize
@@ -1632,10 +3142,26 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_is_named(self.0) }
     }
 
+    /// Get the literal text that this node represents, if it is *anonymous*.
+    ///
+    /// Anonymous nodes correspond to string literals in the grammar, like
+    /// keywords and punctuation, so their text is known from the grammar
+    /// without reading the source code. Returns `None` for named nodes.
+    #[must_use]
+    pub fn anonymous_text(&self) -> Option<&'static str> {
+        (!self.is_named()).then(|| self.kind())
+    }
+
     /// Check if this node is *extra*.
     ///
     /// Extra nodes represent things like comments, which are not required by the
     /// grammar, but can appear anywhere.
+    ///
+    /// Extra nodes are children of the smallest node that encloses them, in their
+    /// position in the document, so they can be found with the usual navigation
+    /// methods. Named extras, like comments, are also included when navigating
+    /// over named children. Whitespace is not represented by nodes, but can be
+    /// recovered from the gaps between the byte ranges of adjacent nodes.
     #[doc(alias = "ts_node_is_extra")]
     #[must_use]
     pub fn is_extra(&self) -> bool {
@@ -1651,6 +3177,11 @@ impl<'tree> Node<'tree> {
 
     /// Check if this node represents a syntax error or contains any syntax
     /// errors anywhere within it.
+    ///
+    /// This reflects the node's entire subtree, and is answered in constant
+    /// time from state stored on the node, so it can be used to skip over
+    /// error-free subtrees cheaply. Use [`Node::is_error`] to check whether
+    /// this node itself is an `ERROR` node.
     #[doc(alias = "ts_node_has_error")]
     #[must_use]
     pub fn has_error(&self) -> bool {
@@ -1660,7 +3191,8 @@ impl<'tree> Node<'tree> {
     /// Check if this node represents a syntax error.
     ///
     /// Syntax errors represent parts of the code that could not be incorporated
-    /// into a valid syntax tree.
+    /// into a valid syntax tree. Unlike [`Node::has_error`], this is only true
+    /// for the `ERROR` node itself, not for its ancestors.
     #[doc(alias = "ts_node_is_error")]
     #[must_use]
     pub fn is_error(&self) -> bool {
@@ -1692,6 +3224,10 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the byte offset where this node starts.
+    ///
+    /// Offsets are always measured in bytes, regardless of the encoding of the
+    /// text that was parsed. For a tree parsed from UTF-16 text, this is twice
+    /// the offset in code units; see [`Node::utf16_code_unit_start`].
     #[doc(alias = "ts_node_start_byte")]
     #[must_use]
     pub fn start_byte(&self) -> usize {
@@ -1711,6 +3247,33 @@ impl<'tree> Node<'tree> {
         self.start_byte()..self.end_byte()
     }
 
+    /// Get the offset in UTF-16 code units where this node starts.
+    ///
+    /// This is only meaningful for trees that were parsed from UTF-16 text, like
+    /// with [`Parser::parse_utf16_le`], in which case it can be used to index
+    /// directly into the slice of `u16`s that was parsed.
+    #[must_use]
+    pub fn utf16_code_unit_start(&self) -> usize {
+        self.start_byte() / 2
+    }
+
+    /// Get the offset in UTF-16 code units where this node ends.
+    ///
+    /// See [`Node::utf16_code_unit_start`].
+    #[must_use]
+    pub fn utf16_code_unit_end(&self) -> usize {
+        self.end_byte() / 2
+    }
+
+    /// Get the range of source code that this node represents, in terms of
+    /// characters rather than bytes.
+    ///
+    /// The `index_map` must have been built from the text that was parsed.
+    #[must_use]
+    pub fn char_range(&self, index_map: &CharIndexMap) -> core::ops::Range<usize> {
+        index_map.char_index(self.start_byte())..index_map.char_index(self.end_byte())
+    }
+
     /// Get the range of source code that this node represents, both in terms of
     /// raw bytes and of row/column coordinates.
     #[must_use]
@@ -1807,6 +3370,9 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the field name of this node's child at the given index.
+    ///
+    /// The index counts all of the node's children, including anonymous ones,
+    /// as in [`Node::child`]. Returns `None` if the child is not in a field.
     #[doc(alias = "ts_node_field_name_for_child")]
     #[must_use]
     pub fn field_name_for_child(&self, child_index: u32) -> Option<&'static str> {
@@ -1817,6 +3383,11 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the field name of this node's named child at the given index.
+    ///
+    /// The index only counts named children, as in [`Node::named_child`], so
+    /// this can be used while iterating over [`Node::named_children`] without
+    /// converting the index. Returns `None` if the child is not in a field.
+    #[doc(alias = "ts_node_field_name_for_named_child")]
     #[must_use]
     pub fn field_name_for_named_child(&self, named_child_index: u32) -> Option<&'static str> {
         unsafe {
@@ -1834,6 +3405,19 @@ impl<'tree> Node<'tree> {
     ///
     /// If you're walking the tree recursively, you may want to use the
     /// [`TreeCursor`] APIs directly instead.
+    ///
+    /// All of the node iteration helpers that take a `&mut TreeCursor` reset
+    /// it to the node they're called on, so one cursor can be shared by any
+    /// number of calls on nodes from the same tree:
+    /// ```ignore
+    /// let mut cursor = tree.walk();
+    /// for item in tree.root_node().named_children(&mut tree.walk()) {
+    ///     for child in item.children(&mut cursor) {
+    ///         // ...
+    ///     }
+    ///     let breadcrumbs = item.path_to_root(&mut cursor);
+    /// }
+    /// ```
     pub fn children<'cursor>(
         &self,
         cursor: &'cursor mut TreeCursor<'tree>,
@@ -1936,6 +3520,49 @@ impl<'tree> Node<'tree> {
         Self::new(unsafe { ffi::ts_node_parent(self.0) })
     }
 
+    /// Iterate over this node's ancestors, starting with its immediate parent
+    /// and ending with the root node. The node itself is not included.
+    ///
+    /// The ancestors are computed lazily, so combining this with methods like
+    /// [`Iterator::find`] only walks as far up the tree as necessary.
+    pub fn ancestors(&self) -> impl Iterator<Item = Node<'tree>> + use<'tree> {
+        iter::successors(self.parent(), Node::parent)
+    }
+
+    /// Get the kinds of this node and of its named ancestors, along with the
+    /// field name under which each one appears in its parent, starting with
+    /// this node and ending with the root.
+    ///
+    /// This is the chain that is typically shown as a breadcrumb, like
+    /// `source_file > mod_item > function_item > block`. Anonymous ancestors
+    /// are skipped, but field names are still reported for their children.
+    ///
+    /// The given cursor is reset and used to walk down from the root. See
+    /// [`Node::children`] for how to reuse a cursor across calls.
+    #[must_use]
+    pub fn path_to_root(
+        &self,
+        cursor: &mut TreeCursor<'tree>,
+    ) -> Vec<(Option<&'static str>, &'static str)> {
+        let root = Self::new(unsafe { ffi::ts_tree_root_node(self.0.tree) }).unwrap();
+        cursor.reset(root);
+        let mut path = Vec::from([(None, root.kind())]);
+        while cursor.node() != *self {
+            let Some(target) = cursor.node().child_with_descendant(*self) else {
+                break;
+            };
+            cursor.goto_first_child_for_byte(target.start_byte());
+            while cursor.node() != target {
+                cursor.goto_next_sibling();
+            }
+            if target.is_named() || target == *self {
+                path.push((cursor.field_name(), target.kind()));
+            }
+        }
+        path.reverse();
+        path
+    }
+
     /// Get the node that contains `descendant`.
     ///
     /// Note that this can return `descendant` itself.
@@ -1974,6 +3601,12 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get this node's first child that contains or starts after the given byte offset.
+    ///
+    /// This is the first child whose end byte is greater than `byte`, so a child
+    /// that ends exactly at `byte` is skipped in favor of the one after it. It
+    /// can be used to step toward a position without a [`TreeCursor`], in
+    /// contrast to [`Node::child_with_descendant`], which requires the target
+    /// node to already be known.
     #[doc(alias = "ts_node_first_child_for_byte")]
     #[must_use]
     pub fn first_child_for_byte(&self, byte: usize) -> Option<Self> {
@@ -1981,7 +3614,9 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get this node's first named child that contains or starts after the given byte offset.
-    #[doc(alias = "ts_node_first_named_child_for_point")]
+    ///
+    /// See [`Node::first_child_for_byte`].
+    #[doc(alias = "ts_node_first_named_child_for_byte")]
     #[must_use]
     pub fn first_named_child_for_byte(&self, byte: usize) -> Option<Self> {
         Self::new(unsafe { ffi::ts_node_first_named_child_for_byte(self.0, byte as u32) })
@@ -2004,6 +3639,10 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the smallest named node within this node that spans the given byte range.
+    ///
+    /// Unlike [`descendant_for_byte_range`](Node::descendant_for_byte_range),
+    /// this skips over anonymous tokens like keywords and punctuation, so it is
+    /// suited for finding the symbol under an editor's cursor.
     #[doc(alias = "ts_node_named_descendant_for_byte_range")]
     #[must_use]
     pub fn named_descendant_for_byte_range(&self, start: usize, end: usize) -> Option<Self> {
@@ -2013,6 +3652,12 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the smallest node within this node that spans the given point range.
+    ///
+    /// Only this node's subtree is searched, so this can be called on an
+    /// enclosing node that is already known, rather than on the root node. The
+    /// points are in the same coordinates as this node's positions, which
+    /// include any offset given to [`Tree::root_node_with_offset`]. If none of
+    /// this node's descendants spans the range, this node itself is returned.
     #[doc(alias = "ts_node_descendant_for_point_range")]
     #[must_use]
     pub fn descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
@@ -2022,6 +3667,8 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the smallest named node within this node that spans the given point range.
+    ///
+    /// See [`descendant_for_point_range`](Node::descendant_for_point_range).
     #[doc(alias = "ts_node_named_descendant_for_point_range")]
     #[must_use]
     pub fn named_descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
@@ -2034,19 +3681,165 @@ impl<'tree> Node<'tree> {
     #[doc(alias = "ts_node_string")]
     #[must_use]
     pub fn to_sexp(&self) -> String {
-        let c_string = unsafe { ffi::ts_node_string(self.0) };
-        let result = unsafe { CStr::from_ptr(c_string) }
-            .to_str()
-            .unwrap()
-            .to_string();
-        unsafe { ts_free(c_string.cast::<c_void>()) };
+        let mut result = String::new();
+        self.write_sexp(&mut result).unwrap();
+        result
+    }
+
+    /// Get an S-expression representing the node, with the given options.
+    ///
+    /// With the default [`SexpOptions`], this is the same as
+    /// [`to_sexp`](Node::to_sexp). The other options add detail for debugging,
+    /// like the position of each node, or remove the field names.
+    #[must_use]
+    pub fn to_sexp_with_options(&self, options: SexpOptions) -> String {
+        if options == SexpOptions::default() {
+            return self.to_sexp();
+        }
+
+        let mut result = String::new();
+        let mut cursor = self.walk();
+        let mut did_visit_children = false;
+        loop {
+            let node = cursor.node();
+            let is_shown = cursor.depth() == 0
+                || node.is_named()
+                || node.is_missing()
+                || options.anonymous_nodes;
+            if did_visit_children {
+                if is_shown {
+                    result.push(')');
+                }
+                if cursor.goto_next_sibling() {
+                    did_visit_children = false;
+                } else if !cursor.goto_parent() {
+                    break;
+                }
+                continue;
+            }
+
+            if is_shown {
+                if !result.is_empty() {
+                    result.push(' ');
+                }
+                if let Some(field_name) = cursor.field_name().filter(|_| options.field_names) {
+                    write!(result, "{field_name}: ").unwrap();
+                }
+                let kind = node.kind();
+                if node.is_missing() {
+                    if node.is_named() {
+                        write!(result, "(MISSING {kind}").unwrap();
+                    } else {
+                        write!(result, "(MISSING \"{kind}\"").unwrap();
+                    }
+                } else if node.is_error()
+                    && node.child_count() == 0
+                    && !node.byte_range().is_empty()
+                {
+                    // Only the C library knows which character was unexpected, so
+                    // reuse its output for this leaf, without the closing paren.
+                    let sexp = node.to_sexp();
+                    result.push_str(sexp.strip_suffix(')').unwrap_or(&sexp));
+                } else if node.is_named() {
+                    write!(result, "({kind}").unwrap();
+                } else {
+                    write!(result, "(\"{kind}\"").unwrap();
+                }
+                if options.byte_ranges {
+                    write!(result, " {}..{}", node.start_byte(), node.end_byte()).unwrap();
+                }
+                if options.point_ranges {
+                    let (start, end) = (node.start_position(), node.end_position());
+                    write!(
+                        result,
+                        " [{}, {}] - [{}, {}]",
+                        start.row, start.column, end.row, end.column
+                    )
+                    .unwrap();
+                }
+            }
+            did_visit_children = !cursor.goto_first_child();
+        }
         result
     }
 
+    /// Write an S-expression representing the node to the given writer.
+    ///
+    /// The output is the same as [`to_sexp`](Node::to_sexp), but it is written
+    /// in small pieces as the tree is traversed, so the whole S-expression for a
+    /// large tree never needs to be held in memory. If the writer returns an
+    /// error, writing stops and the error is returned.
+    #[doc(alias = "ts_node_write_string")]
+    pub fn write_sexp<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        unsafe extern "C" fn write<W: fmt::Write>(
+            payload: *mut c_void,
+            text: *const c_char,
+            length: u32,
+        ) -> bool {
+            let (writer, result) = unsafe { &mut *payload.cast::<(&mut W, fmt::Result)>() };
+            let text = unsafe { slice::from_raw_parts(text.cast::<u8>(), length as usize) };
+            *result = str::from_utf8(text)
+                .map_err(|_| fmt::Error)
+                .and_then(|text| writer.write_str(text));
+            result.is_ok()
+        }
+
+        let mut state = (writer, Ok(()));
+        unsafe {
+            ffi::ts_node_write_string(
+                self.0,
+                ptr::addr_of_mut!(state).cast::<c_void>(),
+                Some(write::<W>),
+            );
+        }
+        state.1
+    }
+
     pub fn utf8_text<'a>(&self, source: &'a [u8]) -> Result<&'a str, str::Utf8Error> {
         str::from_utf8(&source[self.start_byte()..self.end_byte()])
     }
 
+    /// Get the text of this node, leaving out any text that lies outside of the
+    /// tree's [included ranges](Tree::included_ranges).
+    ///
+    /// When a tree is parsed with multiple included ranges, a node can span the
+    /// gaps between them, and [`utf8_text`](Node::utf8_text) would return the
+    /// excluded text as well. This concatenates only the included parts of the
+    /// node's text. If the node lies within a single included range, its text is
+    /// borrowed from `source`, and nothing is allocated.
+    pub fn utf8_text_ranges<'a>(&self, source: &'a [u8]) -> Result<Cow<'a, str>, str::Utf8Error> {
+        let (start, end) = (self.start_byte(), self.end_byte());
+        let mut count = 0u32;
+        let ptr = unsafe {
+            ffi::ts_tree_included_ranges_view(self.0.tree, core::ptr::addr_of_mut!(count))
+        };
+        let pieces = unsafe { slice::from_raw_parts(ptr, count as usize) }
+            .iter()
+            .map(|range| (range.start_byte as usize).max(start)..(range.end_byte as usize).min(end))
+            .filter(|piece| piece.start < piece.end);
+        if start == end || pieces.clone().next() == Some(start..end) {
+            return self.utf8_text(source).map(Cow::Borrowed);
+        }
+        let mut text = Vec::with_capacity(end - start);
+        for piece in pieces {
+            text.extend_from_slice(&source[piece]);
+        }
+        String::from_utf8(text)
+            .map(Cow::Owned)
+            .map_err(|error| error.utf8_error())
+    }
+
+    /// Get a value whose [`Debug`](fmt::Debug) output is the same as this
+    /// node's, followed by a short preview of the node's text in the given
+    /// source code.
+    #[must_use]
+    pub const fn debug_with_source<'a>(&self, source: &'a [u8]) -> NodeWithSource<'tree, 'a> {
+        NodeWithSource {
+            node: *self,
+            source,
+        }
+    }
+
     #[must_use]
     pub fn utf16_text<'a>(&self, source: &'a [u16]) -> &'a [u16] {
         &source[self.start_byte() / 2..self.end_byte() / 2]
@@ -2096,16 +3889,91 @@ impl hash::Hash for Node<'_> {
 
 impl fmt::Debug for Node<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let start = self.start_position();
+        let end = self.end_position();
         write!(
             f,
-            "{{Node {} {} - {}}}",
+            "{} @ {}..{} ({}:{}-{}:{})",
             self.kind(),
-            self.start_position(),
-            self.end_position()
+            self.start_byte(),
+            self.end_byte(),
+            start.row,
+            start.column,
+            end.row,
+            end.column
         )
     }
 }
 
+impl KindMapper {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report nodes of the given kind as `mapped_kind`.
+    pub fn map(&mut self, kind: &str, mapped_kind: &str) -> &mut Self {
+        self.kinds.insert(kind.to_string(), mapped_kind.to_string());
+        self
+    }
+
+    /// Get the mapped kind of the given node.
+    #[must_use]
+    pub fn kind(&self, node: &Node) -> &str {
+        let kind = node.kind();
+        self.kinds.get(kind).map_or(kind, String::as_str)
+    }
+
+    /// Wrap the given node so that its kind is reported through this mapper.
+    #[must_use]
+    pub const fn apply<'tree>(&self, node: Node<'tree>) -> MappedNode<'tree, '_> {
+        MappedNode { node, mapper: self }
+    }
+}
+
+impl<'tree> MappedNode<'tree, '_> {
+    /// Get this node's kind, after applying the mapping.
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        self.mapper.kind(&self.node)
+    }
+
+    /// Get the underlying node.
+    #[must_use]
+    pub const fn node(&self) -> Node<'tree> {
+        self.node
+    }
+}
+
+impl<'tree> Deref for MappedNode<'tree, '_> {
+    type Target = Node<'tree>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl fmt::Debug for MappedNode<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {:?}", self.kind(), self.node)
+    }
+}
+
+impl fmt::Debug for NodeWithSource<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const PREVIEW_LENGTH: usize = 32;
+
+        let range = self.node.start_byte().min(self.source.len())
+            ..self.node.end_byte().min(self.source.len());
+        let text = String::from_utf8_lossy(&self.source[range]);
+        let mut preview = text.chars().take(PREVIEW_LENGTH).collect::<String>();
+        if text.chars().nth(PREVIEW_LENGTH).is_some() {
+            preview.push('…');
+        }
+        write!(f, "{:?} {preview:?}", self.node)
+    }
+}
+
 impl fmt::Display for Node<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let sexp = self.to_sexp();
@@ -2495,6 +4363,70 @@ impl Query {
         })
     }
 
+    /// Serialize this compiled query into a buffer of bytes.
+    ///
+    /// The bytes can be passed to [`Query::from_bytes`] to recreate the query
+    /// without recompiling it from source, which is useful for reducing the
+    /// startup cost of large queries. The data is only valid for the same
+    /// version of this library, built for the same platform.
+    #[doc(alias = "ts_query_serialize")]
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut length = 0u32;
+        unsafe {
+            let ptr = ffi::ts_query_serialize(self.ptr.as_ptr(), core::ptr::addr_of_mut!(length));
+            let result = slice::from_raw_parts(ptr.cast::<u8>(), length as usize).to_vec();
+            ts_free(ptr.cast::<c_void>());
+            result
+        }
+    }
+
+    /// Recreate a query from bytes that were produced by [`Query::to_bytes`].
+    ///
+    /// Returns a [`QueryError`] with kind [`QueryErrorKind::Language`] if the
+    /// query was compiled for a different language, or with kind
+    /// [`QueryErrorKind::Syntax`] if the data is malformed. Every index in the
+    /// data is checked against the language and the query's own tables, so
+    /// arbitrary bytes are rejected rather than producing an invalid query.
+    #[doc(alias = "ts_query_deserialize")]
+    pub fn from_bytes(language: &Language, bytes: &[u8]) -> Result<Self, QueryError> {
+        let mut error_type: ffi::TSQueryError = ffi::TSQueryErrorSyntax;
+        let ptr = match u32::try_from(bytes.len()) {
+            Ok(length) => unsafe {
+                ffi::ts_query_deserialize(
+                    language.0,
+                    bytes.as_ptr().cast::<c_char>(),
+                    length,
+                    core::ptr::addr_of_mut!(error_type),
+                )
+            },
+            Err(_) => ptr::null_mut(),
+        };
+
+        if ptr.is_null() {
+            let (message, kind) = if error_type == ffi::TSQueryErrorLanguage {
+                (
+                    "Serialized query was compiled for a different language".to_string(),
+                    QueryErrorKind::Language,
+                )
+            } else {
+                (
+                    "Malformed serialized query".to_string(),
+                    QueryErrorKind::Syntax,
+                )
+            };
+            return Err(QueryError {
+                row: 0,
+                column: 0,
+                offset: 0,
+                message,
+                kind,
+            });
+        }
+
+        unsafe { Self::from_raw_parts(ptr, "") }
+    }
+
     #[doc(hidden)]
     unsafe fn from_raw_parts(ptr: *mut ffi::TSQuery, source: &str) -> Result<Self, QueryError> {
         let ptr = {
@@ -2951,6 +4883,59 @@ impl Query {
         }
     }
 
+    /// Check whether a match satisfies the text predicates of its pattern,
+    /// like `#eq?` and `#match?`, using text from `text_provider`.
+    ///
+    /// This is the same check that [`QueryCursor::matches`] and
+    /// [`QueryCursor::captures`] perform, but it can be run on a match that
+    /// is held separately from the iteration, for example to filter matches
+    /// again after the source text has changed.
+    #[must_use]
+    pub fn match_satisfies_predicates<T: TextProvider<I>, I: AsRef<[u8]>>(
+        &self,
+        query_match: &QueryMatch,
+        mut text_provider: T,
+    ) -> bool {
+        query_match.satisfies_text_predicates(
+            self,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut text_provider,
+        )
+    }
+
+    /// Estimate how expensive this query is to execute.
+    ///
+    /// This is a static analysis of the query's patterns, which can be used to
+    /// reject pathological queries, such as ones from untrusted sources, before
+    /// running them.
+    #[must_use]
+    pub fn estimated_cost(&self) -> QueryCost {
+        let mut cost = QueryCost {
+            pattern_count: self.pattern_count(),
+            ..QueryCost::default()
+        };
+        for i in 0..cost.pattern_count {
+            if !self.is_pattern_rooted(i) {
+                cost.non_rooted_pattern_count += 1;
+            }
+            if unsafe { ffi::ts_query_is_pattern_wildcard_rooted(self.ptr.as_ptr(), i as u32) } {
+                cost.wildcard_pattern_count += 1;
+            }
+            cost.predicate_count += self.text_predicates[i].len()
+                + self.property_predicates[i].len()
+                + self.property_settings[i].len()
+                + self.general_predicates[i].len();
+            cost.regex_count += self.text_predicates[i]
+                .iter()
+                .filter(|p| matches!(p, TextPredicateCapture::MatchString(..)))
+                .count();
+            let depth = unsafe { ffi::ts_query_pattern_depth(self.ptr.as_ptr(), i as u32) };
+            cost.max_depth = cost.max_depth.max(depth as usize);
+        }
+        cost
+    }
+
     fn parse_property(
         row: usize,
         function_name: &str,
@@ -3060,6 +5045,13 @@ impl QueryCursor {
     /// one match may contain captures that appear *before* some of the
     /// captures from a previous match.
     ///
+    /// When a [`Query`] is created, its patterns are indexed by the kind of the
+    /// node at which they start, so as the cursor walks the tree, each pattern
+    /// is only attempted at nodes of that kind. Large queries therefore don't
+    /// need to be split up or pre-filtered by node kind. To limit the work that
+    /// is done on a large file, restrict the cursor to the relevant part of it
+    /// with [`QueryCursor::set_byte_range`] instead.
+    ///
     /// Iterating over a `QueryMatches` object requires the `StreamingIterator`
     /// or `StreamingIteratorMut` trait to be in scope. This can be done via
     /// `use tree_sitter::StreamingIterator` or `use tree_sitter::StreamingIteratorMut`
@@ -3084,6 +5076,83 @@ impl QueryCursor {
         }
     }
 
+    /// Call the given function for each match, in the order that the matches
+    /// were found.
+    ///
+    /// This is equivalent to iterating over [`QueryCursor::matches`], but can be
+    /// more convenient when the matches only need to be visited once. Returning
+    /// [`ControlFlow::Break`] from the callback stops the search early, and is
+    /// reflected in this method's return value.
+    pub fn for_each_match<T, I, F>(
+        &mut self,
+        query: &Query,
+        node: Node,
+        text_provider: T,
+        mut callback: F,
+    ) -> ControlFlow<()>
+    where
+        T: TextProvider<I>,
+        I: AsRef<[u8]>,
+        F: FnMut(&QueryMatch) -> ControlFlow<()>,
+    {
+        let mut matches = self.matches(query, node, text_provider);
+        while let Some(m) = matches.next() {
+            callback(m)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Iterate over the matches of a single pattern in the order that they were found.
+    ///
+    /// This is equivalent to filtering the results of [`QueryCursor::matches`] by
+    /// [`QueryMatch::pattern_index`], but the other patterns in the query are never
+    /// attempted, so no work is spent on them. This is useful for running one part of a
+    /// large, combined query, such as only its folds.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `pattern_index` is greater than or equal to the query's
+    /// pattern count.
+    #[doc(alias = "ts_query_cursor_exec_for_pattern")]
+    pub fn matches_for_pattern<
+        'query,
+        'cursor: 'query,
+        'tree,
+        T: TextProvider<I>,
+        I: AsRef<[u8]>,
+    >(
+        &'cursor mut self,
+        query: &'query Query,
+        pattern_index: usize,
+        node: Node<'tree>,
+        text_provider: T,
+    ) -> QueryMatches<'query, 'tree, T, I> {
+        assert!(
+            pattern_index < query.pattern_count(),
+            "Pattern index is {pattern_index} but the pattern count is {}",
+            query.pattern_count(),
+        );
+        let ptr = self.ptr.as_ptr();
+        unsafe {
+            ffi::ts_query_cursor_exec_for_pattern(
+                ptr,
+                query.ptr.as_ptr(),
+                pattern_index as u32,
+                node.0,
+            );
+        }
+        QueryMatches {
+            ptr,
+            query,
+            text_provider,
+            buffer1: Vec::default(),
+            buffer2: Vec::default(),
+            current_match: None,
+            _options: None,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Iterate over all of the matches in the order that they were found, with options.
     ///
     /// Each match contains the index of the pattern that matched, and a list of
@@ -3152,6 +5221,13 @@ impl QueryCursor {
     /// This is useful if you don't care about which pattern matched, and just
     /// want a single, ordered sequence of captures.
     ///
+    /// The positions of the captured nodes are based on the position of the
+    /// given `node`. When querying a tree that was parsed from a fragment of a
+    /// larger document, such as an injected language, pass the root node from
+    /// [`Tree::root_node_with_offset`] to get captures whose positions are
+    /// relative to the whole document. The `text_provider` must then provide
+    /// the text of the whole document, too.
+    ///
     /// Iterating over a `QueryCaptures` object requires the `StreamingIterator`
     /// or `StreamingIteratorMut` trait to be in scope. This can be done via
     /// `use tree_sitter::StreamingIterator` or `use tree_sitter::StreamingIteratorMut`
@@ -3237,6 +5313,87 @@ impl QueryCursor {
         }
     }
 
+    /// Get the captures that intersect a window of rows and columns, in the
+    /// order that they appear, along with their ranges clipped to the window.
+    ///
+    /// This packages the operation performed by providers of LSP semantic
+    /// tokens. The cursor is restricted to the window while the query runs,
+    /// and its previous point range is restored afterward.
+    ///
+    /// Like all of the points in this library, the columns of `start` and `end`
+    /// are byte offsets within their rows. LSP positions count UTF-16 code
+    /// units by default, so they must be converted before calling this, and the
+    /// clipped ranges must be converted back.
+    pub fn captures_in_point_range<'tree>(
+        &mut self,
+        query: &Query,
+        node: Node<'tree>,
+        start: Point,
+        end: Point,
+        source: &[u8],
+    ) -> Vec<(QueryCapture<'tree>, Range)> {
+        let mut previous_start = ffi::TSPoint { row: 0, column: 0 };
+        let mut previous_end = ffi::TSPoint { row: 0, column: 0 };
+        unsafe {
+            ffi::ts_query_cursor_point_range(
+                self.ptr.as_ptr(),
+                ptr::addr_of_mut!(previous_start),
+                ptr::addr_of_mut!(previous_end),
+            );
+        }
+
+        let start_byte = byte_offset_for_point(source, start);
+        let end_byte = byte_offset_for_point(source, end);
+        let mut result = Vec::new();
+        let mut captures = self
+            .set_point_range(start..end)
+            .captures(query, node, source);
+        while let Some((m, i)) = captures.next() {
+            let capture = m.captures[*i];
+            let mut range = capture.node.range();
+            if range.end_point <= start || range.start_point >= end {
+                continue;
+            }
+            if range.start_point < start {
+                range.start_point = start;
+                range.start_byte = start_byte;
+            }
+            if range.end_point > end {
+                range.end_point = end;
+                range.end_byte = end_byte;
+            }
+            result.push((capture, range));
+        }
+        drop(captures);
+
+        unsafe {
+            ffi::ts_query_cursor_set_point_range(self.ptr.as_ptr(), previous_start, previous_end);
+        }
+        result
+    }
+
+    /// Get all of the individual captures in the order that they appear, each
+    /// paired with its capture index and the index of the pattern that
+    /// produced it.
+    ///
+    /// This is a convenience over [`captures`](QueryCursor::captures) for
+    /// callers that resolve overlapping captures by pattern priority, such as
+    /// highlighters where later patterns take precedence over earlier ones.
+    pub fn captures_with_pattern<'tree, T: TextProvider<I>, I: AsRef<[u8]>>(
+        &mut self,
+        query: &Query,
+        node: Node<'tree>,
+        text_provider: T,
+    ) -> Vec<(Node<'tree>, u32, usize)> {
+        let mut result = Vec::new();
+        let mut captures = self.captures(query, node, text_provider);
+        while let Some((m, i)) = captures.next() {
+            let capture = m.captures[*i];
+            result.push((capture.node, capture.index, m.pattern_index));
+        }
+        result
+    }
+
     /// Set the range in which the query will be executed, in terms of byte
     /// offsets.
     #[doc(alias = "ts_query_cursor_set_byte_range")]
@@ -3414,6 +5571,7 @@ impl<'tree> QueryMatch<'_, 'tree> {
                 TextPredicateCapture::EqCapture(i, j, is_positive, match_all_nodes) => {
                     let mut nodes_1 = self.nodes_for_capture_index(*i).peekable();
                     let mut nodes_2 = self.nodes_for_capture_index(*j).peekable();
+                    let is_empty = nodes_1.peek().is_none() || nodes_2.peek().is_none();
                     while nodes_1.peek().is_some() && nodes_2.peek().is_some() {
                         let node1 = nodes_1.next().unwrap();
                         let node2 = nodes_2.next().unwrap();
@@ -3429,10 +5587,14 @@ impl<'tree> QueryMatch<'_, 'tree> {
                             return true;
                         }
                     }
-                    nodes_1.next().is_none() && nodes_2.next().is_none()
+                    // An `any-` predicate fails if none of the nodes satisfied it.
+                    nodes_1.next().is_none()
+                        && nodes_2.next().is_none()
+                        && (*match_all_nodes || is_empty)
                 }
                 TextPredicateCapture::EqString(i, s, is_positive, match_all_nodes) => {
-                    let nodes = self.nodes_for_capture_index(*i);
+                    let mut nodes = self.nodes_for_capture_index(*i).peekable();
+                    let is_empty = nodes.peek().is_none();
                     for node in nodes {
                         let mut text = text_provider.text(node);
                         let text = node_text1.get_text(&mut text);
@@ -3444,10 +5606,11 @@ impl<'tree> QueryMatch<'_, 'tree> {
                             return true;
                         }
                     }
-                    true
+                    *match_all_nodes || is_empty
                 }
                 TextPredicateCapture::MatchString(i, r, is_positive, match_all_nodes) => {
-                    let nodes = self.nodes_for_capture_index(*i);
+                    let mut nodes = self.nodes_for_capture_index(*i).peekable();
+                    let is_empty = nodes.peek().is_none();
                     for node in nodes {
                         let mut text = text_provider.text(node);
                         let text = node_text1.get_text(&mut text);
@@ -3459,7 +5622,7 @@ impl<'tree> QueryMatch<'_, 'tree> {
                             return true;
                         }
                     }
-                    true
+                    *match_all_nodes || is_empty
                 }
                 TextPredicateCapture::AnyString(i, v, is_positive) => {
                     let nodes = self.nodes_for_capture_index(*i);
@@ -3633,6 +5796,34 @@ impl<'a> TextProvider<&'a [u8]> for &'a [u8] {
     }
 }
 
+impl<'a, P: ChunkedText + ?Sized> TextProvider<&'a [u8]> for ChunkedTextProvider<'a, P> {
+    type I = ChunkedTextChunks<'a, P>;
+
+    fn text(&mut self, node: Node) -> Self::I {
+        ChunkedTextChunks {
+            text: self.0,
+            range: node.byte_range(),
+        }
+    }
+}
+
+impl<'a, P: ChunkedText + ?Sized> Iterator for ChunkedTextChunks<'a, P> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.range.is_empty() {
+            return None;
+        }
+        let chunk = self.text.chunk_at(self.range.start);
+        if chunk.is_empty() {
+            return None;
+        }
+        let len = chunk.len().min(self.range.len());
+        self.range.start += len;
+        Some(&chunk[..len])
+    }
+}
+
 impl PartialEq for Query {
     fn eq(&self, other: &Self) -> bool {
         self.ptr == other.ptr
@@ -3656,6 +5847,89 @@ impl Point {
     pub const fn new(row: usize, column: usize) -> Self {
         Self { row, column }
     }
+
+    /// Get the extent of the text between `other` and this point, such that
+    /// adding it to `other` gives this point again.
+    ///
+    /// If this point is on a later row than `other`, the result keeps this
+    /// point's column. If they are on the same row, the result is the difference
+    /// of their columns, and if this point comes before `other`, the result is
+    /// clamped to `(0, 0)`.
+    #[must_use]
+    pub const fn saturating_sub(self, other: Self) -> Self {
+        if self.row > other.row {
+            Self::new(self.row - other.row, self.column)
+        } else if self.row == other.row {
+            Self::new(0, self.column.saturating_sub(other.column))
+        } else {
+            Self::new(0, 0)
+        }
+    }
+}
+
+impl ops::Add for Point {
+    type Output = Self;
+
+    /// Advance this point by the extent of a span of text.
+    ///
+    /// If the extent spans multiple rows, the result is on a later row and has
+    /// the extent's column, since the column restarts at each line break.
+    /// Otherwise, the extent's column is added to this point's column. This is
+    /// not a field-wise sum, so it is not commutative.
+    fn add(self, extent: Self) -> Self {
+        if extent.row > 0 {
+            Self::new(self.row + extent.row, extent.column)
+        } else {
+            Self::new(self.row, self.column + extent.column)
+        }
+    }
+}
+
+impl Range {
+    /// Check if the given byte offset lies within this range.
+    ///
+    /// Ranges are half-open, so the end byte is not contained, and an empty
+    /// range contains nothing.
+    #[must_use]
+    pub const fn contains_byte(&self, byte: usize) -> bool {
+        self.start_byte <= byte && byte < self.end_byte
+    }
+
+    /// Check if the given point lies within this range.
+    ///
+    /// Points are compared by row and then by column. As with
+    /// [`Range::contains_byte`], the end point is not contained.
+    #[must_use]
+    pub fn contains_point(&self, point: Point) -> bool {
+        self.start_point <= point && point < self.end_point
+    }
+
+    /// Check if this range and the given range have any bytes in common.
+    ///
+    /// Ranges that only touch at their boundaries do not intersect, and an
+    /// empty range intersects nothing.
+    #[must_use]
+    pub const fn intersects(&self, other: &Self) -> bool {
+        self.start_byte < other.end_byte
+            && other.start_byte < self.end_byte
+            && self.start_byte < self.end_byte
+            && other.start_byte < other.end_byte
+    }
+
+    /// Get the range that is covered by both this range and the given range,
+    /// or `None` if they don't [intersect](Range::intersects).
+    ///
+    /// The start byte and start point are the later of the two ranges' starts,
+    /// and the end byte and end point are the earlier of their ends.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.intersects(other).then(|| Self {
+            start_byte: self.start_byte.max(other.start_byte),
+            end_byte: self.end_byte.min(other.end_byte),
+            start_point: self.start_point.max(other.start_point),
+            end_point: self.end_point.min(other.end_point),
+        })
+    }
 }
 
 impl fmt::Display for Point {
@@ -3664,6 +5938,67 @@ impl fmt::Display for Point {
     }
 }
 
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}..{} {} - {}",
+            self.start_byte, self.end_byte, self.start_point, self.end_point
+        )
+    }
+}
+
+impl CharIndexMap {
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        Self {
+            char_start_bytes: text.char_indices().map(|(i, _)| i).collect(),
+        }
+    }
+
+    /// Get the character offset for the given byte offset.
+    ///
+    /// This is the number of characters that start before the byte offset, so
+    /// an offset within a multi-byte character is rounded up to the next
+    /// character, and offsets past the end of the text map to its length.
+    #[must_use]
+    pub fn char_index(&self, byte_offset: usize) -> usize {
+        self.char_start_bytes
+            .partition_point(|start| *start < byte_offset)
+    }
+}
+
+/// Parse a point written as `row,column`, like `"3,12"`. Whitespace around
+/// either number is ignored.
+impl str::FromStr for Point {
+    type Err = PositionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || PositionParseError::Point(s.to_string());
+        let (row, column) = s.split_once(',').ok_or_else(error)?;
+        Ok(Self {
+            row: row.trim().parse().map_err(|_| error())?,
+            column: column.trim().parse().map_err(|_| error())?,
+        })
+    }
+}
+
+/// Parse a byte range written as `start..end`, like `"10..42"`, in the form
+/// accepted by [`QueryCursor::set_byte_range`].
+///
+/// Whitespace around either number is ignored. The end must not be less than
+/// the start.
+pub fn parse_byte_range(s: &str) -> Result<ops::Range<usize>, PositionParseError> {
+    let error = || PositionParseError::ByteRange(s.to_string());
+    let (start, end) = s.split_once("..").ok_or_else(error)?;
+    let start = start.trim().parse().map_err(|_| error())?;
+    let end = end.trim().parse().map_err(|_| error())?;
+    if end < start {
+        return Err(error());
+    }
+    Ok(start..end)
+}
+
 impl From<Point> for ffi::TSPoint {
     fn from(val: Point) -> Self {
         Self {
@@ -3717,6 +6052,12 @@ impl From<&InputEdit> for ffi::TSInputEdit {
     }
 }
 
+fn utf16_len(bytes: &[u8]) -> usize {
+    LossyUtf8::new(bytes)
+        .map(|chunk| chunk.encode_utf16().count())
+        .sum()
+}
+
 impl<'a> LossyUtf8<'a> {
     #[must_use]
     pub const fn new(bytes: &'a [u8]) -> Self {
@@ -3764,6 +6105,69 @@ impl<'a> Iterator for LossyUtf8<'a> {
     }
 }
 
+const fn copy_input(input: &ffi::TSInput) -> ffi::TSInput {
+    ffi::TSInput {
+        payload: input.payload,
+        read: input.read,
+        encoding: input.encoding,
+        decode: input.decode,
+    }
+}
+
+/// Render a line diff of two S-expressions, with lines only in `old` prefixed
+/// by `-` and lines only in `new` prefixed by `+`.
+fn sexp_diff(old: &str, new: &str) -> String {
+    let old = format_sexp(old, 0);
+    let new = format_sexp(new, 0);
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    // The length of the longest common subsequence of the lines after each
+    // pair of positions, so that lines which were only shifted by an insertion
+    // or deletion are not reported as changed.
+    let width = new_lines.len() + 1;
+    let mut lcs = vec![0_usize; (old_lines.len() + 1) * width];
+    for i in (0..old_lines.len()).rev() {
+        for j in (0..new_lines.len()).rev() {
+            lcs[i * width + j] = if old_lines[i] == new_lines[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut result = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_lines.len() || j < new_lines.len() {
+        if i < old_lines.len() && j < new_lines.len() && old_lines[i] == new_lines[j] {
+            writeln!(result, "  {}", old_lines[i]).unwrap();
+            i += 1;
+            j += 1;
+        } else if j == new_lines.len()
+            || (i < old_lines.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            writeln!(result, "- {}", old_lines[i]).unwrap();
+            i += 1;
+        } else {
+            writeln!(result, "+ {}", new_lines[j]).unwrap();
+            j += 1;
+        }
+    }
+    result
+}
+
+fn byte_offset_for_point(text: &[u8], point: Point) -> usize {
+    let mut offset = 0;
+    for _ in 0..point.row {
+        match text[offset..].iter().position(|b| *b == b'\n') {
+            Some(i) => offset += i + 1,
+            None => return text.len(),
+        }
+    }
+    (offset + point.column).min(text.len())
+}
+
 #[must_use]
 const fn predicate_error(row: usize, message: String) -> QueryError {
     QueryError {
@@ -3777,7 +6181,97 @@ const fn predicate_error(row: usize, message: String) -> QueryError {
 
 impl fmt::Display for IncludedRangesError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Incorrect range by index: {}", self.0)
+        let reason = match self.kind {
+            RangeErrorKind::NotOrdered => "starts before the previous range",
+            RangeErrorKind::EndBeforeStart => "ends before it starts",
+            RangeErrorKind::Overlapping => "overlaps the previous range",
+        };
+        write!(
+            f,
+            "Incorrect range at index {}: the range {reason}",
+            self.index
+        )
+    }
+}
+
+impl fmt::Display for PositionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Point(input) => {
+                write!(f, "Invalid point {input:?}, expected `row,column`")
+            }
+            Self::ByteRange(input) => {
+                write!(
+                    f,
+                    "Invalid byte range {input:?}, expected `start..end` with start <= end"
+                )
+            }
+        }
+    }
+}
+
+impl From<LanguageError> for ParserError {
+    fn from(error: LanguageError) -> Self {
+        Self::Language(error)
+    }
+}
+
+impl From<IncludedRangesError> for ParserError {
+    fn from(error: IncludedRangesError) -> Self {
+        Self::IncludedRanges(error)
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Language(error) => write!(f, "{error}"),
+            Self::IncludedRanges(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl fmt::Display for AbiMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Incompatible language version {}. Expected minimum {}, maximum {}",
+            self.version, self.min_version, self.max_version
+        )
+    }
+}
+
+impl fmt::Display for IncrementalParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoTree => write!(f, "The parse did not produce a tree"),
+            Self::Mismatch { diff, .. } => write!(
+                f,
+                "Incremental parse does not match a parse from scratch:\n{diff}"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Failed to address an offset: {}", self.offset)
+    }
+}
+
+impl fmt::Display for InputEditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            Self::OldEndBeforeStart => "the old end byte is before the start byte",
+            Self::NewEndBeforeStart => "the new end byte is before the start byte",
+            Self::InconsistentOldEndPosition => {
+                "the old end position is inconsistent with the byte offsets"
+            }
+            Self::InconsistentNewEndPosition => {
+                "the new end position is inconsistent with the byte offsets"
+            }
+        };
+        write!(f, "Invalid edit: {reason}")
     }
 }
 
@@ -3992,9 +6486,34 @@ pub unsafe fn set_allocator(allocator: Option<Allocator>) {
 impl error::Error for IncludedRangesError {}
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for EditError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for IncrementalParseError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for InputEditError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for AbiMismatch {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for PositionParseError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 impl error::Error for LanguageError {}
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for ParserError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Language(error) => Some(error),
+            Self::IncludedRanges(error) => Some(error),
+        }
+    }
+}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 impl error::Error for QueryError {}
 
 unsafe impl Send for Language {}
@@ -47,6 +47,7 @@ typedef struct TSTree TSTree;
 typedef struct TSQuery TSQuery;
 typedef struct TSQueryCursor TSQueryCursor;
 typedef struct TSLookaheadIterator TSLookaheadIterator;
+typedef struct TSLexer TSLexer;
 
 // This function signature reads one code point from the given string,
 // returning the number of bytes consumed. It should write the code point
@@ -97,6 +98,10 @@ typedef struct TSParseState {
   void *payload;
   uint32_t current_byte_offset;
   bool has_error;
+  uint32_t error_count;
+  uint32_t bytes_read;
+  uint32_t current_range_index;
+  uint32_t step_count;
 } TSParseState;
 
 typedef struct TSParseOptions {
@@ -104,6 +109,30 @@ typedef struct TSParseOptions {
   bool (*progress_callback)(TSParseState *state);
 } TSParseOptions;
 
+typedef struct TSAmbiguity {
+  TSSymbol symbol;
+  uint32_t start_byte;
+  uint32_t end_byte;
+  const TSSymbol *chosen_children;
+  uint32_t chosen_child_count;
+  const TSSymbol *rejected_children;
+  uint32_t rejected_child_count;
+} TSAmbiguity;
+
+typedef struct TSTableUsage {
+  TSStateId state;
+  bool is_reduction;
+  TSSymbol symbol;
+  uint16_t production_id;
+} TSTableUsage;
+
+typedef struct TSParsePhaseTimings {
+  uint64_t lexing;
+  uint64_t reduction;
+  uint64_t error_recovery;
+  uint64_t balancing;
+} TSParsePhaseTimings;
+
 typedef enum TSLogType {
   TSLogTypeParse,
   TSLogTypeLex,
@@ -207,6 +236,19 @@ typedef struct TSLanguageMetadata {
   uint8_t patch_version;
 } TSLanguageMetadata;
 
+/**
+ * The functions that make up an external scanner, with the same signatures
+ * as the ones that are generated for a grammar's `scanner.c`. See
+ * [`ts_language_with_external_scanner`].
+ */
+typedef struct TSExternalScanner {
+  void *(*create)(void);
+  void (*destroy)(void *payload);
+  bool (*scan)(void *payload, TSLexer *lexer, const bool *valid_symbols);
+  unsigned (*serialize)(void *payload, char *buffer);
+  void (*deserialize)(void *payload, const char *buffer, unsigned length);
+} TSExternalScanner;
+
 /********************/
 /* Section - Parser */
 /********************/
@@ -278,6 +320,176 @@ const TSRange *ts_parser_included_ranges(
   uint32_t *count
 );
 
+/**
+ * Check if the custom decode function stalled during the most recent parse.
+ *
+ * A decode function stalls when it reports that it consumed zero bytes from a
+ * non-empty chunk of text. When this happens, the parser treats the stall as
+ * the end of the input. If a stall occurred, this function returns `true` and
+ * writes the byte offset at which decoding stopped to the given `byte_offset`
+ * pointer. Otherwise, the input ended normally and this function returns
+ * `false`.
+ */
+bool ts_parser_did_decode_stall(const TSParser *self, uint32_t *byte_offset);
+
+/**
+ * Check whether the parser calls the language's external scanner.
+ */
+bool ts_parser_use_external_scanner(const TSParser *self);
+
+/**
+ * Enable or disable the language's external scanner.
+ *
+ * This is a debugging aid for determining whether a problem is caused by a
+ * grammar or by its external scanner. When the external scanner is disabled,
+ * the parser never calls its `scan` function, so none of the grammar's external
+ * tokens can be produced. For most grammars that use external tokens, this
+ * will cause syntax errors. The external scanner is enabled by default.
+ */
+void ts_parser_set_use_external_scanner(TSParser *self, bool use_external_scanner);
+
+/**
+ * Set the maximum number of syntax errors that the parser will recover from
+ * before halting.
+ *
+ * The parser counts each time it has to begin recovering from an error. Once
+ * that count reaches `max_errors`, the parse is halted in the same way as when
+ * the progress callback returns `true`: [`ts_parser_parse`] returns `NULL`, and
+ * the parser keeps its state, so you must call [`ts_parser_reset`] before
+ * parsing another document. A value of zero, which is the default, means that
+ * there is no limit.
+ */
+void ts_parser_set_max_errors(TSParser *self, uint32_t max_errors);
+
+/**
+ * Set how many parse steps the parser takes between calls to the progress
+ * callback.
+ *
+ * A step is a unit of work like lexing a token or performing a reduction, so
+ * the number of steps taken for a given input, and the points at which the
+ * callback is called, are the same on every run. The total number of steps is
+ * reported in the `step_count` field of [`TSParseState`], which keeps growing
+ * across a resumed parse and is reset by [`ts_parser_reset`]. A value of zero,
+ * which is the default, calls the callback every 100 steps.
+ */
+void ts_parser_set_progress_interval(TSParser *self, uint32_t interval);
+
+/**
+ * Enable or disable error recovery.
+ *
+ * By default, when the parser encounters a token that is not valid, it tries to
+ * recover by inserting missing tokens, or by returning to an earlier state in
+ * which the token would be valid, so that the rest of the input can be parsed
+ * normally. With error recovery disabled, the parser does neither of these: it
+ * skips every remaining token, and the finished tree's root is a single `ERROR`
+ * node that contains the nodes parsed before the first error, followed by the
+ * rest of the input. Valid input is parsed the same way in both modes, so this
+ * is useful for tools that only process valid files and don't need a detailed
+ * tree for invalid ones.
+ */
+void ts_parser_set_error_recovery(TSParser *self, bool error_recovery);
+
+/**
+ * Count each carriage return that is followed by a newline as zero columns.
+ *
+ * By default, a `\r\n` line ending is two bytes long, so a node that ends with
+ * the `\r`, such as a comment whose pattern includes everything up to the `\n`,
+ * ends one column after the end of the line's visible text. Editors that hide
+ * the `\r` don't count it, so when this is enabled, the columns of all points
+ * in the tree are computed the same way. Byte offsets are not affected. The
+ * points in any edits and included ranges that are given to the parser must then
+ * be computed the same way too. This is disabled by default.
+ */
+void ts_parser_set_report_lf_positions(TSParser *self, bool report_lf_positions);
+
+/**
+ * Set the maximum number of bytes of input that the parser will read.
+ *
+ * The parser treats this byte offset as the end of the input, even if the
+ * input's `read` function would return more text, so the parse always
+ * finishes. This protects against `read` functions that never signal the end of
+ * the input. The limit applies to the byte offsets that are requested from the
+ * input, so it is the same when the parser re-reads text during an incremental
+ * parse. If the limit falls within a multi-byte character, that character is
+ * truncated. A value of zero, which is the default, means that there is no
+ * limit.
+ */
+void ts_parser_set_max_input_bytes(TSParser *self, uint32_t max_input_bytes);
+
+/**
+ * Set the largest column that the lexer reports to scanners and lex functions.
+ *
+ * Grammars that depend on column values, such as layout-sensitive grammars whose
+ * external scanners call `get_column`, receive the number of characters between
+ * the start of the current line and the current position. This number can't
+ * overflow, since it is never greater than the line's length in bytes, but
+ * computing it can require re-reading the line from its start, which takes time
+ * proportional to the length of the line. On pathological input, such as
+ * minified code on a single line, this makes column-sensitive grammars very
+ * slow.
+ *
+ * When a limit is set, any column greater than it is reported as the limit
+ * itself, and a line is not re-read when its length in bytes guarantees that the
+ * column is beyond the limit. A value of zero, which is the default, means that
+ * there is no limit.
+ */
+void ts_parser_set_max_column(TSParser *self, uint32_t max_column);
+
+/**
+ * Set a function that the parser calls whenever it chooses between two
+ * different trees for the same node.
+ *
+ * This happens when a grammar is ambiguous: more than one sequence of children
+ * can be reduced to the same symbol over the same range of text, and the parser
+ * has to pick one of them based on dynamic precedence or, failing that, on an
+ * arbitrary but deterministic comparison. The callback receives the node's
+ * symbol and byte range, and the symbols of the children of the chosen and
+ * rejected alternatives. The child arrays are only valid for the duration of
+ * the call. Choices between alternatives that contain syntax errors are not
+ * reported. Pass `NULL` for the callback to stop reporting ambiguities.
+ */
+void ts_parser_set_ambiguity_callback(
+  TSParser *self,
+  void *payload,
+  void (*callback)(void *payload, const TSAmbiguity *ambiguity)
+);
+
+/**
+ * Set a function that the parser calls for every parse table action that it
+ * performs.
+ *
+ * The callback receives the parse state in which the action was taken. For
+ * reductions, it also receives the reduced symbol and the production id of the
+ * reduction, and `is_reduction` is set to `true`. This makes it possible to
+ * measure how much of a grammar's parse table is exercised by a set of inputs.
+ * Pass `NULL` for the callback to stop reporting table usage.
+ */
+void ts_parser_set_table_usage_callback(
+  TSParser *self,
+  void *payload,
+  void (*callback)(void *payload, const TSTableUsage *usage)
+);
+
+/**
+ * Enable or disable profiling of the parser's individual phases.
+ *
+ * When profiling is enabled, the parser measures the processor time that it
+ * spends lexing, performing reductions, recovering from errors, and balancing
+ * the finished tree. The measurements are reset whenever a new parse begins,
+ * and they accumulate across resumptions of a parse that was cancelled.
+ * Profiling is disabled by default, and adds a small overhead to each phase.
+ */
+void ts_parser_set_phase_profiling(TSParser *self, bool enabled);
+
+/**
+ * Get the time that the most recent parse spent in each of its phases.
+ *
+ * If phase profiling is enabled, this function writes the measurements, in
+ * microseconds, to the given `timings` pointer and returns `true`. Otherwise,
+ * it returns `false`. See [`ts_parser_set_phase_profiling`].
+ */
+bool ts_parser_phase_timings(const TSParser *self, TSParsePhaseTimings *timings);
+
 /**
  * Use the parser to parse some source code and create a syntax tree.
  *
@@ -378,6 +590,27 @@ TSTree *ts_parser_parse_string_encoding(
  */
 void ts_parser_reset(TSParser *self);
 
+/**
+ * Check whether the parser has state left over from a parse that was halted
+ * by the progress callback.
+ *
+ * If this returns `true`, the next call to [`ts_parser_parse`] or other
+ * parsing functions will resume that parse, unless [`ts_parser_reset`] is
+ * called first.
+ */
+bool ts_parser_is_resumable(const TSParser *self);
+
+/**
+ * Get the position that a halted parse had reached.
+ *
+ * If the parser has state left over from a halted parse, this function writes
+ * the byte offset that the parser had advanced to when it was halted to the
+ * given `byte_offset` pointer and returns `true`. Text before this offset has
+ * already been parsed, and resuming the parse will continue from there.
+ * Otherwise, this function returns `false`.
+ */
+bool ts_parser_halted_byte_offset(const TSParser *self, uint32_t *byte_offset);
+
 /**
  * Set the logger that a parser should use during parsing.
  *
@@ -412,6 +645,16 @@ void ts_parser_print_dot_graphs(TSParser *self, int fd);
  */
 TSTree *ts_tree_copy(const TSTree *self);
 
+/**
+ * Create a deep copy of the syntax tree. This takes time proportional to the
+ * number of nodes in the tree.
+ *
+ * Unlike the copies made by [`ts_tree_copy`], which share their nodes with the
+ * original tree, the resulting tree does not share any memory with the
+ * original.
+ */
+TSTree *ts_tree_deep_copy(const TSTree *self);
+
 /**
  * Delete the syntax tree, freeing all of the memory that it used.
  */
@@ -444,6 +687,15 @@ const TSLanguage *ts_tree_language(const TSTree *self);
  */
 TSRange *ts_tree_included_ranges(const TSTree *self, uint32_t *length);
 
+/**
+ * Get the array of included ranges that was used to parse the syntax tree,
+ * without copying it.
+ *
+ * The returned pointer is owned by the tree, and is valid for as long as the
+ * tree is neither edited nor deleted.
+ */
+const TSRange *ts_tree_included_ranges_view(const TSTree *self, uint32_t *length);
+
 /**
  * Edit the syntax tree to keep it in sync with source code that has been
  * edited.
@@ -548,6 +800,21 @@ TSPoint ts_node_end_point(TSNode self);
  */
 char *ts_node_string(TSNode self);
 
+/**
+ * Write an S-expression representing the node, without allocating it as a
+ * single string.
+ *
+ * The S-expression is passed to the `write` function in pieces, which are not
+ * null-terminated. It is the same as the string returned by [`ts_node_string`]
+ * once all of the pieces are concatenated. If `write` returns `false`, no more
+ * pieces are written.
+ */
+void ts_node_write_string(
+  TSNode self,
+  void *payload,
+  bool (*write)(void *payload, const char *text, uint32_t length)
+);
+
 /**
  * Check if the node is null. Functions like [`ts_node_child`] and
  * [`ts_node_next_sibling`] will return a null node to indicate that no such node
@@ -928,6 +1195,35 @@ void ts_query_delete(TSQuery *self);
  */
 TSQuery *ts_query_copy(const TSQuery *self);
 
+/**
+ * Serialize a compiled query into a buffer of bytes, which can later be passed
+ * to [`ts_query_deserialize`] to recreate the query without recompiling it.
+ *
+ * The returned buffer is allocated with the library's allocator, and the
+ * caller is responsible for freeing it. The length of the buffer is written
+ * to the given `length` pointer. The data is only valid for the same version
+ * of this library, built for the same platform.
+ */
+char *ts_query_serialize(const TSQuery *self, uint32_t *length);
+
+/**
+ * Recreate a query from a buffer of bytes that was produced by
+ * [`ts_query_serialize`].
+ *
+ * If the data was produced for a different language, this returns `NULL` and
+ * writes `TSQueryErrorLanguage` to the `error_type` parameter. If the data is
+ * otherwise invalid, this returns `NULL` and writes `TSQueryErrorSyntax` to
+ * the `error_type` parameter. Every symbol, field, capture, and step that the
+ * data refers to is checked, so arbitrary bytes never produce a query that
+ * reads out of bounds.
+ */
+TSQuery *ts_query_deserialize(
+  const TSLanguage *language,
+  const char *data,
+  uint32_t length,
+  TSQueryError *error_type
+);
+
 /**
  * Get the number of patterns, captures, or string literals in the query.
  */
@@ -994,6 +1290,18 @@ bool ts_query_is_pattern_non_local(const TSQuery *self, uint32_t pattern_index);
  */
 bool ts_query_is_pattern_guaranteed_at_step(const TSQuery *self, uint32_t byte_offset);
 
+/*
+ * Get the number of levels of nested nodes in the given pattern. A pattern
+ * that consists of a single node has a depth of one.
+ */
+uint32_t ts_query_pattern_depth(const TSQuery *self, uint32_t pattern_index);
+
+/*
+ * Check if the given pattern can begin with a wildcard node, such as `(_)`
+ * or `_`. These patterns must be tried at every node in the syntax tree.
+ */
+bool ts_query_is_pattern_wildcard_rooted(const TSQuery *self, uint32_t pattern_index);
+
 /**
  * Get the name and length of one of the query's captures, or one of the
  * query's string literals. Each capture and string is associated with a
@@ -1082,6 +1390,20 @@ void ts_query_cursor_exec_with_options(
   const TSQueryCursorOptions *query_options
 );
 
+/**
+ * Start running a single pattern of a given query on a given node.
+ *
+ * This behaves like [`ts_query_cursor_exec`], except that the cursor only
+ * starts matches for the pattern with the given index. The other patterns are
+ * never attempted, so no work is spent on them.
+ */
+void ts_query_cursor_exec_for_pattern(
+  TSQueryCursor *self,
+  const TSQuery *query,
+  uint32_t pattern_index,
+  TSNode node
+);
+
 /**
  * Manage the maximum number of in-progress matches allowed by this query
  * cursor.
@@ -1137,6 +1459,16 @@ bool ts_query_cursor_set_byte_range(TSQueryCursor *self, uint32_t start_byte, ui
  */
 bool ts_query_cursor_set_point_range(TSQueryCursor *self, TSPoint start_point, TSPoint end_point);
 
+/**
+ * Get the range of (row, column) positions in which the query will be executed,
+ * as set by [`ts_query_cursor_set_point_range`].
+ */
+void ts_query_cursor_point_range(
+  const TSQueryCursor *self,
+  TSPoint *start_point,
+  TSPoint *end_point
+);
+
 /**
  * Set the byte range within which all matches must be fully contained.
  *
@@ -1227,6 +1559,15 @@ uint32_t ts_language_symbol_count(const TSLanguage *self);
 */
 uint32_t ts_language_state_count(const TSLanguage *self);
 
+/**
+ * Get the numerical id of the grammar's start rule.
+ *
+ * This is the node type of the root node of any tree that is parsed without
+ * errors, such as `source_file` or `program`. It is found in the parse table,
+ * and this returns zero if the table has no start rule.
+ */
+TSSymbol ts_language_start_symbol(const TSLanguage *self);
+
 /**
  * Get the numerical id for the given node type string.
  */
@@ -1268,6 +1609,22 @@ const TSSymbol *ts_language_subtypes(
   uint32_t *length
 );
 
+/**
+ * Get a list of all field ids that can appear on nodes of the given symbol.
+ *
+ * The list is computed from the language's parse table, so it includes the
+ * fields that the node inherits from hidden child rules. The ids are sorted in
+ * ascending order and the list's length is written to `length`. The caller is
+ * responsible for freeing the returned array using `free`. Symbols that only
+ * appear as the target of an alias have no productions of their own, so the
+ * result for them is empty.
+ */
+TSFieldId *ts_language_fields_for_symbol(
+  const TSLanguage *self,
+  TSSymbol symbol,
+  uint32_t *length
+);
+
 /**
  * Get a node type string for the given numerical id.
  */
@@ -1281,6 +1638,36 @@ const char *ts_language_symbol_name(const TSLanguage *self, TSSymbol symbol);
  */
 TSSymbolType ts_language_symbol_type(const TSLanguage *self, TSSymbol symbol);
 
+/**
+ * Check whether the given node type id is one of the grammar's *extras*, like
+ * comments, which can appear anywhere in the document.
+ *
+ * Extras that are only matched by the lexer and never produce nodes, such as
+ * whitespace, do not have node type ids. See also [`ts_node_is_extra`].
+ */
+bool ts_language_symbol_is_extra(const TSLanguage *self, TSSymbol symbol);
+
+/**
+ * Create a copy of the given language that uses the given functions as its
+ * external scanner, in place of the scanner that was compiled with it.
+ *
+ * The grammar's `externals` must contain exactly `external_token_count`
+ * tokens, which are the tokens indexed by the `valid_symbols` array that is
+ * passed to `scan`. This returns `NULL` if the count does not match, or if the
+ * language was loaded from Wasm.
+ *
+ * The copy shares its parse tables with the original language, which must
+ * stay loaded for as long as the copy is used. Unlike the original, the copy
+ * is allocated, so it must be freed with [`ts_language_delete`]. Parsers,
+ * trees and queries keep their own reference to it, so it can be deleted
+ * while they are still in use.
+ */
+const TSLanguage *ts_language_with_external_scanner(
+  const TSLanguage *self,
+  TSExternalScanner scanner,
+  uint32_t external_token_count
+);
+
 /**
  * Get the ABI version number for this language. This version number is used
  * to ensure that languages were generated by a compatible version of
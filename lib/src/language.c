@@ -1,11 +1,38 @@
+#include "./alloc.h"
+#include "./atomic.h"
 #include "./language.h"
 #include "./wasm_store.h"
 #include "tree_sitter/api.h"
+#include <stddef.h>
 #include <string.h>
 
+// A language that was created by `ts_language_with_external_scanner`. Unlike
+// the languages that are compiled into a program, it is allocated, so it is
+// reference counted and freed when the last reference is deleted.
+typedef struct {
+  TSLanguage language;
+  const TSLanguage *original;
+  volatile uint32_t ref_count;
+} ScannerLanguage;
+
+// The copy's `keyword_lex_fn` is replaced with this function, which is how the
+// copy is recognized. The parser calls the original language's keyword lex
+// function instead, using `ts_language_call_keyword_lex_fn`.
+static bool ts_language__scanner_copy_keyword_lex_fn(TSLexer *lexer, TSStateId state) {
+  (void)lexer;
+  (void)state;
+  return false;
+}
+
+static inline bool ts_language__is_scanner_copy(const TSLanguage *self) {
+  return self->keyword_lex_fn == ts_language__scanner_copy_keyword_lex_fn;
+}
+
 const TSLanguage *ts_language_copy(const TSLanguage *self) {
   if (self && ts_language_is_wasm(self)) {
     ts_wasm_language_retain(self);
+  } else if (self && ts_language__is_scanner_copy(self)) {
+    atomic_inc(&((ScannerLanguage *)self)->ref_count);
   }
   return self;
 }
@@ -13,9 +40,22 @@ const TSLanguage *ts_language_copy(const TSLanguage *self) {
 void ts_language_delete(const TSLanguage *self) {
   if (self && ts_language_is_wasm(self)) {
     ts_wasm_language_release(self);
+  } else if (self && ts_language__is_scanner_copy(self)) {
+    ScannerLanguage *copy = (ScannerLanguage *)self;
+    if (atomic_dec(&copy->ref_count) == 0) {
+      ts_language_delete(copy->original);
+      ts_free(copy);
+    }
   }
 }
 
+bool ts_language_call_keyword_lex_fn(const TSLanguage *self, TSLexer *lexer, TSStateId state) {
+  if (ts_language__is_scanner_copy(self)) {
+    self = ((const ScannerLanguage *)self)->original;
+  }
+  return self->keyword_lex_fn(lexer, state);
+}
+
 uint32_t ts_language_symbol_count(const TSLanguage *self) {
   return self->symbol_count + self->alias_count;
 }
@@ -24,6 +64,24 @@ uint32_t ts_language_state_count(const TSLanguage *self) {
   return self->state_count;
 }
 
+TSSymbol ts_language_start_symbol(const TSLanguage *self) {
+  // The start rule is the only non-terminal that leads from the start state
+  // to a state that accepts the end of the input.
+  if (self->state_count <= 1) return 0;
+  for (TSSymbol symbol = self->token_count; symbol < self->symbol_count; symbol++) {
+    TSStateId state = ts_language_next_state(self, 1, symbol);
+    if (state == 0 || state == 1 || state >= self->state_count) continue;
+    TableEntry entry;
+    ts_language_table_entry(self, state, ts_builtin_sym_end, &entry);
+    for (uint32_t i = 0; i < entry.action_count; i++) {
+      if (entry.actions[i].type == TSParseActionTypeAccept) {
+        return ts_language_public_symbol(self, symbol);
+      }
+    }
+  }
+  return 0;
+}
+
 const TSSymbol *ts_language_supertypes(const TSLanguage *self, uint32_t *length) {
   if (self->abi_version >= LANGUAGE_VERSION_WITH_RESERVED_WORDS) {
     *length = self->supertype_count;
@@ -49,6 +107,112 @@ const TSSymbol *ts_language_subtypes(
   return &self->supertype_map_entries[slice.index];
 }
 
+TSFieldId *ts_language_fields_for_symbol(
+  const TSLanguage *self,
+  TSSymbol symbol,
+  uint32_t *length
+) {
+  *length = 0;
+  if (self->field_count == 0 || symbol >= self->symbol_count) return NULL;
+
+  // Every production of a non-terminal is reduced in at least one state, so
+  // scanning the reduce actions of the parse table visits all of the field
+  // maps that can apply to the symbol's nodes, including inherited fields.
+  bool *found = ts_calloc(self->field_count + 1, sizeof(bool));
+  for (TSStateId state = 1; state < self->state_count; state++) {
+    for (TSSymbol token = 0; token < self->token_count; token++) {
+      uint32_t count;
+      const TSParseAction *actions = ts_language_actions(self, state, token, &count);
+      for (uint32_t i = 0; i < count; i++) {
+        const TSParseAction *action = &actions[i];
+        if (
+          action->type != TSParseActionTypeReduce ||
+          ts_language_public_symbol(self, action->reduce.symbol) != symbol
+        ) continue;
+
+        const TSFieldMapEntry *start, *end;
+        ts_language_field_map(self, action->reduce.production_id, &start, &end);
+        for (const TSFieldMapEntry *entry = start; entry < end; entry++) {
+          if (!found[entry->field_id]) {
+            found[entry->field_id] = true;
+            (*length)++;
+          }
+        }
+      }
+    }
+  }
+
+  TSFieldId *result = NULL;
+  if (*length > 0) {
+    result = ts_malloc(*length * sizeof(TSFieldId));
+    uint32_t index = 0;
+    for (TSFieldId id = 1; id <= self->field_count; id++) {
+      if (found[id]) result[index++] = id;
+    }
+  }
+  ts_free(found);
+  return result;
+}
+
+bool ts_language_symbol_is_extra(const TSLanguage *self, TSSymbol symbol) {
+  if (symbol == ts_builtin_sym_end || symbol >= self->symbol_count || self->state_count <= 1) {
+    return false;
+  }
+
+  // Extras are valid in every state, so it is enough to check the start state.
+  // A token is an extra if the parse table shifts it as one.
+  if (symbol < self->token_count) {
+    TableEntry entry;
+    ts_language_table_entry(self, 1, symbol, &entry);
+    for (uint32_t i = 0; i < entry.action_count; i++) {
+      const TSParseAction *action = &entry.actions[i];
+      if (action->type == TSParseActionTypeShift && action->shift.extra) return true;
+    }
+    return false;
+  }
+
+  // A non-terminal extra leaves the parse state unchanged once it has been
+  // reduced, which is also how the parser marks the node as an extra. No other
+  // non-terminal can lead back to the start state, because no state has a
+  // transition into it.
+  return ts_language_next_state(self, 1, symbol) == 1;
+}
+
+const TSLanguage *ts_language_with_external_scanner(
+  const TSLanguage *self,
+  TSExternalScanner scanner,
+  uint32_t external_token_count
+) {
+  if (
+    !self ||
+    ts_language_is_wasm(self) ||
+    self->external_token_count == 0 ||
+    self->external_token_count != external_token_count
+  ) return NULL;
+
+  // A copy of a copy only differs from the original language in its scanner.
+  if (ts_language__is_scanner_copy(self)) {
+    self = ((const ScannerLanguage *)self)->original;
+  }
+
+  // Languages with older ABI versions end before the fields that were added
+  // in ABI 15, so only copy the fields that they have.
+  size_t size = self->abi_version >= LANGUAGE_VERSION_WITH_RESERVED_WORDS
+    ? sizeof(TSLanguage)
+    : offsetof(TSLanguage, name);
+  ScannerLanguage *result = ts_calloc(1, sizeof(ScannerLanguage));
+  memcpy(&result->language, self, size);
+  result->language.keyword_lex_fn = ts_language__scanner_copy_keyword_lex_fn;
+  result->language.external_scanner.create = scanner.create;
+  result->language.external_scanner.destroy = scanner.destroy;
+  result->language.external_scanner.scan = scanner.scan;
+  result->language.external_scanner.serialize = scanner.serialize;
+  result->language.external_scanner.deserialize = scanner.deserialize;
+  result->original = ts_language_copy(self);
+  result->ref_count = 1;
+  return &result->language;
+}
+
 uint32_t ts_language_abi_version(const TSLanguage *self) {
   return self->abi_version;
 }
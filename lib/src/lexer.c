@@ -88,12 +88,20 @@ static void ts_lexer__clear_chunk(Lexer *self) {
 // for the current position.
 static void ts_lexer__get_chunk(Lexer *self) {
   self->chunk_start = self->current_position.bytes;
-  self->chunk = self->input.read(
-    self->input.payload,
-    self->current_position.bytes,
-    self->current_position.extent,
-    &self->chunk_size
-  );
+  if (self->max_input_bytes > 0 && self->chunk_start >= self->max_input_bytes) {
+    self->chunk_size = 0;
+  } else {
+    self->chunk = self->input.read(
+      self->input.payload,
+      self->current_position.bytes,
+      self->current_position.extent,
+      &self->chunk_size
+    );
+    if (self->max_input_bytes > 0 && self->chunk_size > self->max_input_bytes - self->chunk_start) {
+      self->chunk_size = self->max_input_bytes - self->chunk_start;
+    }
+    self->bytes_read += self->chunk_size;
+  }
   if (!self->chunk_size) {
     self->current_included_range_index = self->included_range_count;
     self->chunk = NULL;
@@ -133,6 +141,20 @@ static void ts_lexer__get_lookahead(Lexer *self) {
   if (self->data.lookahead == TS_DECODE_ERROR) {
     self->lookahead_size = 1;
   }
+
+  // If a custom decode function consumed no bytes from a non-empty chunk, it
+  // will never make progress. Record where this happened, and treat it as the
+  // end of the input so that the parse can terminate.
+  if (self->lookahead_size == 0 && !ts_lexer__eof(&self->data)) {
+    if (!self->did_decode_stall) {
+      self->did_decode_stall = true;
+      self->decode_stall_byte = self->current_position.bytes;
+    }
+    self->current_included_range_index = self->included_range_count;
+    ts_lexer__clear_chunk(self);
+    self->lookahead_size = 1;
+    self->data.lookahead = '\0';
+  }
 }
 
 static void ts_lexer_goto(Lexer *self, Length position) {
@@ -198,7 +220,9 @@ static void ts_lexer_goto(Lexer *self, Length position) {
  * @param skip Whether to mark the consumed codepoint as whitespace.
  */
 static void ts_lexer__do_advance(Lexer *self, bool skip) {
+  uint32_t return_size = 0;
   if (self->lookahead_size) {
+    if (self->data.lookahead == '\r') return_size = self->lookahead_size;
     if (self->data.lookahead == '\n') {
       self->current_position.extent.row++;
       self->current_position.extent.column = 0;
@@ -226,6 +250,7 @@ static void ts_lexer__do_advance(Lexer *self, bool skip) {
         current_range->start_byte,
         current_range->start_point,
       };
+      return_size = 0;
     } else {
       current_range = NULL;
       break;
@@ -242,6 +267,17 @@ static void ts_lexer__do_advance(Lexer *self, bool skip) {
       ts_lexer__get_chunk(self);
     }
     ts_lexer__get_lookahead(self);
+
+    // When reporting positions as if line endings were a single `\n`, a `\r`
+    // that precedes a `\n` does not take up a column.
+    if (
+      self->report_lf_positions &&
+      return_size > 0 &&
+      self->data.lookahead == '\n'
+    ) {
+      self->current_position.extent.column -= return_size;
+      if (skip) self->token_start_position = self->current_position;
+    }
   } else {
     ts_lexer__clear_chunk(self);
     self->data.lookahead = '\0';
@@ -295,6 +331,16 @@ static uint32_t ts_lexer__get_column(TSLexer *_self) {
 
   self->did_get_column = true;
 
+  // A character is at most four bytes long, so if the current line is long
+  // enough, the column is known to be beyond the limit without re-reading it.
+  if (
+    self->max_column > 0 &&
+    !self->column_data.valid &&
+    self->current_position.extent.column / 4 >= self->max_column
+  ) {
+    return self->max_column;
+  }
+
   if (!self->column_data.valid) {
     // Record current position
     uint32_t goal_byte = self->current_position.bytes;
@@ -319,6 +365,9 @@ static uint32_t ts_lexer__get_column(TSLexer *_self) {
     }
   }
 
+  if (self->max_column > 0 && self->column_data.value > self->max_column) {
+    return self->max_column;
+  }
   return self->column_data.value;
 }
 
@@ -373,6 +422,12 @@ void ts_lexer_init(Lexer *self) {
     .included_range_count = 0,
     .current_included_range_index = 0,
     .did_get_column = false,
+    .did_decode_stall = false,
+    .decode_stall_byte = 0,
+    .max_input_bytes = 0,
+    .max_column = 0,
+    .bytes_read = 0,
+    .report_lf_positions = false,
     .column_data = {
       .valid = false,
       .value = 0
@@ -387,6 +442,8 @@ void ts_lexer_delete(Lexer *self) {
 
 void ts_lexer_set_input(Lexer *self, TSInput input) {
   self->input = input;
+  self->did_decode_stall = false;
+  self->decode_stall_byte = 0;
   ts_lexer__clear_chunk(self);
   ts_lexer_goto(self, self->current_position);
 }
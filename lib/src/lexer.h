@@ -31,7 +31,13 @@ typedef struct {
   uint32_t chunk_start;
   uint32_t chunk_size;
   uint32_t lookahead_size;
+  uint32_t decode_stall_byte;
+  uint32_t max_input_bytes;
+  uint32_t max_column;
+  uint32_t bytes_read;
+  bool report_lf_positions;
   bool did_get_column;
+  bool did_decode_stall;
   ColumnData column_data;
 
   char debug_buffer[TREE_SITTER_SERIALIZATION_BUFFER_SIZE];
@@ -490,6 +490,23 @@ char *ts_node_string(TSNode self) {
   );
 }
 
+void ts_node_write_string(
+  TSNode self,
+  void *payload,
+  bool (*write)(void *payload, const char *text, uint32_t length)
+) {
+  TSSymbol alias_symbol = ts_node__alias(&self);
+  ts_subtree_write_string(
+    ts_node__subtree(self),
+    alias_symbol,
+    ts_language_symbol_metadata(self.tree->language, alias_symbol).visible,
+    self.tree->language,
+    false,
+    write,
+    payload
+  );
+}
+
 bool ts_node_eq(TSNode self, TSNode other) {
   return self.tree == other.tree && self.id == other.id;
 }
@@ -2,6 +2,7 @@
 #include <limits.h>
 #include <stdbool.h>
 #include <inttypes.h>
+#include <time.h>
 #include "tree_sitter/api.h"
 #include "./alloc.h"
 #include "./array.h"
@@ -86,6 +87,22 @@ typedef struct {
   uint32_t byte_index;
 } TokenCache;
 
+typedef enum {
+  ParsePhaseOther,
+  ParsePhaseLexing,
+  ParsePhaseReduction,
+  ParsePhaseErrorRecovery,
+  ParsePhaseBalancing,
+  ParsePhaseCount,
+} ParsePhase;
+
+typedef struct {
+  ParsePhase current_phase;
+  clock_t phase_start;
+  clock_t totals[ParsePhaseCount];
+  bool enabled;
+} PhaseProfile;
+
 struct TSParser {
   Lexer lexer;
   Stack *stack;
@@ -103,12 +120,23 @@ struct TSParser {
   FILE *dot_graph_file;
   unsigned accept_count;
   unsigned operation_count;
+  unsigned progress_interval;
+  unsigned error_count;
+  unsigned max_errors;
+  void *ambiguity_payload;
+  void (*ambiguity_callback)(void *payload, const TSAmbiguity *ambiguity);
+  Array(TSSymbol) ambiguity_symbols;
+  void *table_usage_payload;
+  void (*table_usage_callback)(void *payload, const TSTableUsage *usage);
   Subtree old_tree;
   TSRangeArray included_range_differences;
   TSParseOptions parse_options;
   TSParseState parse_state;
+  PhaseProfile phase_profile;
   unsigned included_range_difference_index;
   bool has_scanner_error;
+  bool use_external_scanner;
+  bool error_recovery;
   bool canceled_balancing;
   bool has_error;
 };
@@ -173,6 +201,34 @@ static void ts_parser__log(TSParser *self) {
   }
 }
 
+// Switch the parser into the given phase, attributing the time elapsed since
+// the last switch to the previous phase. Returns the previous phase so that
+// the caller can switch back to it.
+static ParsePhase ts_parser__enter_phase(TSParser *self, ParsePhase phase) {
+  PhaseProfile *profile = &self->phase_profile;
+  ParsePhase previous_phase = profile->current_phase;
+  if (profile->enabled) {
+    clock_t now = clock();
+    profile->totals[previous_phase] += now - profile->phase_start;
+    profile->phase_start = now;
+  }
+  profile->current_phase = phase;
+  return previous_phase;
+}
+
+static void ts_parser__start_phase_profile(TSParser *self) {
+  PhaseProfile *profile = &self->phase_profile;
+  for (unsigned i = 0; i < ParsePhaseCount; i++) {
+    profile->totals[i] = 0;
+  }
+  profile->current_phase = ParsePhaseOther;
+  if (profile->enabled) profile->phase_start = clock();
+}
+
+static uint64_t ts_parser__phase_micros(const TSParser *self, ParsePhase phase) {
+  return (uint64_t)self->phase_profile.totals[phase] * 1000000 / CLOCKS_PER_SEC;
+}
+
 static bool ts_parser__breakdown_top_of_stack(
   TSParser *self,
   StackVersion version
@@ -350,7 +406,7 @@ static bool ts_parser__call_keyword_lex_fn(TSParser *self) {
   if (ts_language_is_wasm(self->language)) {
     return ts_wasm_store_call_lex_keyword(self->wasm_store, 0);
   } else {
-    return self->language->keyword_lex_fn(&self->lexer.data, 0);
+    return ts_language_call_keyword_lex_fn(self->language, &self->lexer.data, 0);
   }
 }
 
@@ -533,7 +589,7 @@ static Subtree ts_parser__lex(
     Length current_position = self->lexer.current_position;
     ColumnData column_data = self->lexer.column_data;
 
-    if (lex_mode.external_lex_state != 0) {
+    if (lex_mode.external_lex_state != 0 && self->use_external_scanner) {
       LOG(
         "lex_external state:%d, row:%u, column:%u",
         lex_mode.external_lex_state,
@@ -884,6 +940,58 @@ static bool ts_parser__select_tree(TSParser *self, Subtree left, Subtree right)
   }
 }
 
+// Report a choice between two trees for the same node to the ambiguity callback.
+// The `left` tree is the existing one, and `right_children` are the children of
+// the alternative.
+static void ts_parser__report_ambiguity(
+  TSParser *self,
+  Length position,
+  Subtree left,
+  const Subtree *right_children,
+  uint32_t right_child_count,
+  bool selected_right
+) {
+  if (ts_subtree_error_cost(left) > 0) return;
+  for (uint32_t i = 0; i < right_child_count; i++) {
+    if (ts_subtree_error_cost(right_children[i]) > 0) return;
+  }
+
+  // The root node's children end with the end-of-input token, which is not
+  // part of either alternative.
+  const Subtree *left_children = ts_subtree_children(left);
+  uint32_t left_child_count = ts_subtree_child_count(left);
+  if (left_child_count > 0 && ts_subtree_symbol(left_children[left_child_count - 1]) == ts_builtin_sym_end) {
+    left_child_count--;
+  }
+  if (right_child_count > 0 && ts_subtree_symbol(right_children[right_child_count - 1]) == ts_builtin_sym_end) {
+    right_child_count--;
+  }
+
+  array_clear(&self->ambiguity_symbols);
+  array_reserve(&self->ambiguity_symbols, left_child_count + right_child_count);
+  for (uint32_t i = 0; i < left_child_count; i++) {
+    TSSymbol symbol = ts_subtree_symbol(left_children[i]);
+    array_push(&self->ambiguity_symbols, ts_language_public_symbol(self->language, symbol));
+  }
+  for (uint32_t i = 0; i < right_child_count; i++) {
+    TSSymbol symbol = ts_subtree_symbol(right_children[i]);
+    array_push(&self->ambiguity_symbols, ts_language_public_symbol(self->language, symbol));
+  }
+
+  const TSSymbol *left_symbols = self->ambiguity_symbols.contents;
+  const TSSymbol *right_symbols = left_symbols + left_child_count;
+  TSAmbiguity ambiguity = {
+    .symbol = ts_language_public_symbol(self->language, ts_subtree_symbol(left)),
+    .start_byte = position.bytes + ts_subtree_padding(left).bytes,
+    .end_byte = position.bytes + ts_subtree_total_bytes(left),
+    .chosen_children = selected_right ? right_symbols : left_symbols,
+    .chosen_child_count = selected_right ? right_child_count : left_child_count,
+    .rejected_children = selected_right ? left_symbols : right_symbols,
+    .rejected_child_count = selected_right ? left_child_count : right_child_count,
+  };
+  self->ambiguity_callback(self->ambiguity_payload, &ambiguity);
+}
+
 // Determine if a given tree's children should be replaced by an alternative
 // array of children.
 static bool ts_parser__select_children(
@@ -998,11 +1106,23 @@ static StackVersion ts_parser__reduce(
       SubtreeArray next_slice_children = next_slice.subtrees;
       ts_subtree_array_remove_trailing_extras(&next_slice_children, &self->trailing_extras2);
 
-      if (ts_parser__select_children(
+      bool select_next = ts_parser__select_children(
         self,
         ts_subtree_from_mut(parent),
         &next_slice_children
-      )) {
+      );
+      if (self->ambiguity_callback) {
+        ts_parser__report_ambiguity(
+          self,
+          ts_stack_position(self->stack, slice_version),
+          ts_subtree_from_mut(parent),
+          next_slice_children.contents,
+          next_slice_children.size,
+          select_next
+        );
+      }
+
+      if (select_next) {
         ts_subtree_array_clear(&self->tree_pool, &self->trailing_extras);
         ts_subtree_release(&self->tree_pool, ts_subtree_from_mut(parent));
         array_swap(&self->trailing_extras, &self->trailing_extras2);
@@ -1089,7 +1209,22 @@ static void ts_parser__accept(
     self->accept_count++;
 
     if (self->finished_tree.ptr) {
-      if (ts_parser__select_tree(self, self->finished_tree, root)) {
+      bool select_root = ts_parser__select_tree(self, self->finished_tree, root);
+      if (
+        self->ambiguity_callback &&
+        ts_subtree_symbol(self->finished_tree) == ts_subtree_symbol(root)
+      ) {
+        ts_parser__report_ambiguity(
+          self,
+          length_zero(),
+          self->finished_tree,
+          ts_subtree_children(root),
+          ts_subtree_child_count(root),
+          select_root
+        );
+      }
+
+      if (select_root) {
         ts_subtree_release(&self->tree_pool, self->finished_tree);
         self->finished_tree = root;
       } else {
@@ -1301,8 +1436,10 @@ static void ts_parser__recover(
   //
   // First, try the strategy 1. Upon entering the error state, the parser recorded a summary
   // of the previous parse states and their depths. Look at each state in the summary, to see
-  // if the current lookahead token would be valid in that state.
-  if (summary && !ts_subtree_is_error(lookahead)) {
+  // if the current lookahead token would be valid in that state. When error recovery is
+  // disabled, only strategy 2 is used, so the parser stays in the error state until the end
+  // of the file.
+  if (self->error_recovery && summary && !ts_subtree_is_error(lookahead)) {
     for (unsigned i = 0; i < summary->size; i++) {
       StackSummaryEntry entry = *array_get(summary, i);
 
@@ -1482,8 +1619,9 @@ static void ts_parser__handle_error(
   Length position = ts_stack_position(self->stack, version);
 
   // Push a discontinuity onto the stack. Merge all of the stack versions that
-  // were created in the previous step.
-  bool did_insert_missing_token = false;
+  // were created in the previous step. Missing tokens are only inserted when
+  // error recovery is enabled.
+  bool did_insert_missing_token = !self->error_recovery;
   for (StackVersion v = version; v < version_count;) {
     if (!did_insert_missing_token) {
       TSStateId state = ts_stack_state(self->stack, v);
@@ -1565,13 +1703,29 @@ static void ts_parser__handle_error(
 }
 
 static bool ts_parser__check_progress(TSParser *self, Subtree *lookahead, const uint32_t *position, unsigned operations) {
+  unsigned interval = self->progress_interval
+    ? self->progress_interval
+    : OP_COUNT_PER_PARSER_CALLBACK_CHECK;
   self->operation_count += operations;
-  if (self->operation_count >= OP_COUNT_PER_PARSER_CALLBACK_CHECK) {
+  self->parse_state.step_count += operations;
+  if (self->operation_count >= interval) {
     self->operation_count = 0;
   }
   if (position != NULL) {
     self->parse_state.current_byte_offset = *position;
     self->parse_state.has_error = self->has_error;
+    self->parse_state.error_count = self->error_count;
+    self->parse_state.bytes_read = self->lexer.bytes_read;
+    self->parse_state.current_range_index =
+      self->lexer.current_included_range_index < self->lexer.included_range_count
+        ? self->lexer.current_included_range_index
+        : UINT32_MAX;
+    if (self->max_errors > 0 && self->error_count >= self->max_errors) {
+      if (lookahead && lookahead->ptr) {
+        ts_subtree_release(&self->tree_pool, *lookahead);
+      }
+      return false;
+    }
   }
   if (
     self->operation_count == 0 &&
@@ -1619,7 +1773,9 @@ static bool ts_parser__advance(
     // Otherwise, re-run the lexer.
     if (needs_lex) {
       needs_lex = false;
+      ParsePhase phase = ts_parser__enter_phase(self, ParsePhaseLexing);
       lookahead = ts_parser__lex(self, version, state);
+      ts_parser__enter_phase(self, phase);
       if (self->has_scanner_error) return false;
 
       if (lookahead.ptr) {
@@ -1651,6 +1807,16 @@ static bool ts_parser__advance(
     for (uint32_t i = 0; i < table_entry.action_count; i++) {
       TSParseAction action = table_entry.actions[i];
 
+      if (self->table_usage_callback) {
+        TSTableUsage usage = {.state = state};
+        if (action.type == TSParseActionTypeReduce) {
+          usage.is_reduction = true;
+          usage.symbol = ts_language_public_symbol(self->language, action.reduce.symbol);
+          usage.production_id = action.reduce.production_id;
+        }
+        self->table_usage_callback(self->table_usage_payload, &usage);
+      }
+
       switch (action.type) {
         case TSParseActionTypeShift: {
           if (action.shift.repetition) break;
@@ -1677,11 +1843,13 @@ static bool ts_parser__advance(
           bool is_fragile = table_entry.action_count > 1;
           bool end_of_non_terminal_extra = lookahead.ptr == NULL;
           LOG("reduce sym:%s, child_count:%u", SYM_NAME(action.reduce.symbol), action.reduce.child_count);
+          ParsePhase phase = ts_parser__enter_phase(self, ParsePhaseReduction);
           StackVersion reduction_version = ts_parser__reduce(
             self, version, action.reduce.symbol, action.reduce.child_count,
             action.reduce.dynamic_precedence, action.reduce.production_id,
             is_fragile, end_of_non_terminal_extra
           );
+          ts_parser__enter_phase(self, phase);
           did_reduce = true;
           if (reduction_version != STACK_VERSION_NONE) {
             last_reduction_version = reduction_version;
@@ -1700,7 +1868,9 @@ static bool ts_parser__advance(
             ts_parser__breakdown_lookahead(self, &lookahead, ERROR_STATE, &self->reusable_node);
           }
 
+          ParsePhase phase = ts_parser__enter_phase(self, ParsePhaseErrorRecovery);
           ts_parser__recover(self, version, lookahead);
+          ts_parser__enter_phase(self, phase);
           if (did_reuse) reusable_node_advance(&self->reusable_node);
           return true;
         }
@@ -1873,7 +2043,10 @@ static unsigned ts_parser__condense_stack(TSParser *self) {
           LOG("resume version:%u", i);
           min_error_cost = ts_stack_error_cost(self->stack, i);
           Subtree lookahead = ts_stack_resume(self->stack, i);
+          ParsePhase phase = ts_parser__enter_phase(self, ParsePhaseErrorRecovery);
+          self->error_count++;
           ts_parser__handle_error(self, i, lookahead);
+          ts_parser__enter_phase(self, phase);
           has_unpaused_version = true;
         } else {
           ts_stack_remove_version(self->stack, i);
@@ -1952,7 +2125,7 @@ static bool ts_parser__balance_subtree(TSParser *self) {
   return true;
 }
 
-static bool ts_parser_has_outstanding_parse(TSParser *self) {
+static bool ts_parser_has_outstanding_parse(const TSParser *self) {
   return (
     self->canceled_balancing ||
     self->external_scanner_payload ||
@@ -1976,12 +2149,15 @@ TSParser *ts_parser_new(void) {
   self->language = NULL;
   self->has_scanner_error = false;
   self->has_error = false;
+  self->use_external_scanner = true;
+  self->error_recovery = true;
   self->canceled_balancing = false;
   self->external_scanner_payload = NULL;
   self->operation_count = 0;
   self->old_tree = NULL_SUBTREE;
   self->included_range_differences = (TSRangeArray) array_new();
   self->included_range_difference_index = 0;
+  self->phase_profile = (PhaseProfile) {0};
   ts_parser__set_cached_token(self, 0, NULL_SUBTREE, NULL_SUBTREE);
   return self;
 }
@@ -2009,6 +2185,7 @@ void ts_parser_delete(TSParser *self) {
   array_delete(&self->trailing_extras);
   array_delete(&self->trailing_extras2);
   array_delete(&self->scratch_trees);
+  array_delete(&self->ambiguity_symbols);
   ts_free(self);
 }
 
@@ -2054,7 +2231,7 @@ void ts_parser_print_dot_graphs(TSParser *self, int fd) {
 
   if (fd >= 0) {
     #ifdef _WIN32
-    self->dot_graph_file = _fdopen(fd, "a");
+    self->dot_graph_file = _fdopen(fd, "ab");
     #else
     self->dot_graph_file = fdopen(fd, "a");
     #endif
@@ -2075,6 +2252,80 @@ const TSRange *ts_parser_included_ranges(const TSParser *self, uint32_t *count)
   return ts_lexer_included_ranges(&self->lexer, count);
 }
 
+bool ts_parser_did_decode_stall(const TSParser *self, uint32_t *byte_offset) {
+  if (self->lexer.did_decode_stall && byte_offset) {
+    *byte_offset = self->lexer.decode_stall_byte;
+  }
+  return self->lexer.did_decode_stall;
+}
+
+bool ts_parser_use_external_scanner(const TSParser *self) {
+  return self->use_external_scanner;
+}
+
+void ts_parser_set_use_external_scanner(TSParser *self, bool use_external_scanner) {
+  self->use_external_scanner = use_external_scanner;
+}
+
+void ts_parser_set_max_errors(TSParser *self, uint32_t max_errors) {
+  self->max_errors = max_errors;
+}
+
+void ts_parser_set_progress_interval(TSParser *self, uint32_t interval) {
+  self->progress_interval = interval;
+}
+
+void ts_parser_set_error_recovery(TSParser *self, bool error_recovery) {
+  self->error_recovery = error_recovery;
+}
+
+void ts_parser_set_report_lf_positions(TSParser *self, bool report_lf_positions) {
+  self->lexer.report_lf_positions = report_lf_positions;
+}
+
+void ts_parser_set_max_input_bytes(TSParser *self, uint32_t max_input_bytes) {
+  self->lexer.max_input_bytes = max_input_bytes;
+}
+
+void ts_parser_set_max_column(TSParser *self, uint32_t max_column) {
+  self->lexer.max_column = max_column;
+}
+
+void ts_parser_set_ambiguity_callback(
+  TSParser *self,
+  void *payload,
+  void (*callback)(void *payload, const TSAmbiguity *ambiguity)
+) {
+  self->ambiguity_payload = payload;
+  self->ambiguity_callback = callback;
+}
+
+void ts_parser_set_table_usage_callback(
+  TSParser *self,
+  void *payload,
+  void (*callback)(void *payload, const TSTableUsage *usage)
+) {
+  self->table_usage_payload = payload;
+  self->table_usage_callback = callback;
+}
+
+void ts_parser_set_phase_profiling(TSParser *self, bool enabled) {
+  self->phase_profile.enabled = enabled;
+}
+
+bool ts_parser_phase_timings(const TSParser *self, TSParsePhaseTimings *timings) {
+  if (!self->phase_profile.enabled) return false;
+  if (timings) {
+    *timings = (TSParsePhaseTimings) {
+      .lexing = ts_parser__phase_micros(self, ParsePhaseLexing),
+      .reduction = ts_parser__phase_micros(self, ParsePhaseReduction),
+      .error_recovery = ts_parser__phase_micros(self, ParsePhaseErrorRecovery),
+      .balancing = ts_parser__phase_micros(self, ParsePhaseBalancing),
+    };
+  }
+  return true;
+}
+
 void ts_parser_reset(TSParser *self) {
   ts_parser__external_scanner_destroy(self);
   if (self->wasm_store) {
@@ -2095,6 +2346,8 @@ void ts_parser_reset(TSParser *self) {
     self->finished_tree = NULL_SUBTREE;
   }
   self->accept_count = 0;
+  self->error_count = 0;
+  self->lexer.bytes_read = 0;
   self->has_scanner_error = false;
   self->has_error = false;
   self->canceled_balancing = false;
@@ -2102,6 +2355,16 @@ void ts_parser_reset(TSParser *self) {
   self->parse_state = (TSParseState) {0};
 }
 
+bool ts_parser_is_resumable(const TSParser *self) {
+  return ts_parser_has_outstanding_parse(self);
+}
+
+bool ts_parser_halted_byte_offset(const TSParser *self, uint32_t *byte_offset) {
+  if (!ts_parser_has_outstanding_parse(self)) return false;
+  if (byte_offset) *byte_offset = self->parse_state.current_byte_offset;
+  return true;
+}
+
 TSTree *ts_parser_parse(
   TSParser *self,
   const TSTree *old_tree,
@@ -2123,8 +2386,10 @@ TSTree *ts_parser_parse(
 
   if (ts_parser_has_outstanding_parse(self)) {
     LOG("resume_parsing");
+    if (self->phase_profile.enabled) self->phase_profile.phase_start = clock();
     if (self->canceled_balancing) goto balance;
   } else {
+    ts_parser__start_phase_profile(self);
     ts_parser__external_scanner_create(self);
     if (self->has_scanner_error) goto exit;
 
@@ -2208,7 +2473,10 @@ TSTree *ts_parser_parse(
 
 balance:
   ts_assert(self->finished_tree.ptr);
-  if (!ts_parser__balance_subtree(self)) {
+  ParsePhase phase = ts_parser__enter_phase(self, ParsePhaseBalancing);
+  bool did_balance = ts_parser__balance_subtree(self);
+  ts_parser__enter_phase(self, phase);
+  if (!did_balance) {
     self->canceled_balancing = true;
     return NULL;
   }
@@ -338,6 +338,7 @@ struct TSQueryCursor {
   CaptureListPool capture_list_pool;
   uint32_t depth;
   uint32_t max_start_depth;
+  uint32_t pattern_index;
   TSRange included_range;
   TSRange containing_range;
   uint32_t next_state_id;
@@ -3262,6 +3263,386 @@ TSQuery *ts_query_copy(const TSQuery *self) {
   return copy;
 }
 
+/*
+ * Query serialization - A compiled query is serialized by writing out each of
+ * its arrays, preceded by a header that identifies the format, the layout of
+ * the query's internal structs, and the language that the query was compiled
+ * for. The data is only meant to be loaded by the same version of the library,
+ * built for the same platform.
+ */
+
+#define QUERY_SERIALIZATION_MAGIC 0x51535354 // "TSSQ"
+#define QUERY_SERIALIZATION_VERSION 1
+
+typedef struct {
+  uint32_t magic;
+  uint32_t version;
+  uint32_t step_size;
+  uint32_t pattern_size;
+  uint32_t pattern_entry_size;
+  uint32_t language_hash;
+  uint32_t wildcard_root_pattern_count;
+} QuerySerializationHeader;
+
+typedef Array(char) QueryWriter;
+
+typedef struct {
+  const char *data;
+  uint32_t length;
+  uint32_t offset;
+  bool failed;
+} QueryReader;
+
+// Compute a hash of the node types and field names of a language, so that a
+// serialized query is only loaded with the same language it was compiled for.
+static uint32_t ts_query__language_hash(const TSLanguage *language) {
+  uint32_t hash = 2166136261u;
+  #define HASH_BYTE(byte) hash = (hash ^ (uint8_t)(byte)) * 16777619u
+
+  uint32_t symbol_count = ts_language_symbol_count(language);
+  for (TSSymbol symbol = 0; symbol < symbol_count; symbol++) {
+    const char *name = ts_language_symbol_name(language, symbol);
+    if (name) {
+      for (const char *c = name; *c; c++) HASH_BYTE(*c);
+    }
+    HASH_BYTE(ts_language_symbol_type(language, symbol));
+  }
+
+  uint32_t field_count = ts_language_field_count(language);
+  for (TSFieldId field = 1; field <= field_count; field++) {
+    const char *name = ts_language_field_name_for_id(language, field);
+    if (name) {
+      for (const char *c = name; *c; c++) HASH_BYTE(*c);
+    }
+    HASH_BYTE(0);
+  }
+
+  #undef HASH_BYTE
+  return hash;
+}
+
+static void ts_query__write(QueryWriter *buffer, const void *contents, uint32_t size) {
+  if (size > 0) array_extend(buffer, size, (const char *)contents);
+}
+
+#define ts_query__write_array(buffer, array) \
+  do { \
+    ts_query__write(buffer, &(array)->size, sizeof(uint32_t)); \
+    ts_query__write(buffer, (array)->contents, (array)->size * array_elem_size(array)); \
+  } while (0)
+
+static bool ts_query__read(QueryReader *reader, void *contents, uint32_t size) {
+  if (reader->failed || size > reader->length - reader->offset) {
+    reader->failed = true;
+    return false;
+  }
+  if (size > 0) memcpy(contents, reader->data + reader->offset, size);
+  reader->offset += size;
+  return true;
+}
+
+#define ts_query__read_array(reader, array) \
+  do { \
+    uint32_t _count = 0; \
+    if (!ts_query__read(reader, &_count, sizeof(uint32_t))) break; \
+    if (_count > ((reader)->length - (reader)->offset) / array_elem_size(array)) { \
+      (reader)->failed = true; \
+      break; \
+    } \
+    array_grow_by(array, _count); \
+    ts_query__read(reader, (array)->contents, _count * array_elem_size(array)); \
+  } while (0)
+
+char *ts_query_serialize(const TSQuery *self, uint32_t *length) {
+  QueryWriter buffer = array_new();
+
+  QuerySerializationHeader header = {
+    .magic = QUERY_SERIALIZATION_MAGIC,
+    .version = QUERY_SERIALIZATION_VERSION,
+    .step_size = sizeof(QueryStep),
+    .pattern_size = sizeof(QueryPattern),
+    .pattern_entry_size = sizeof(PatternEntry),
+    .language_hash = ts_query__language_hash(self->language),
+    .wildcard_root_pattern_count = self->wildcard_root_pattern_count,
+  };
+  ts_query__write(&buffer, &header, sizeof(header));
+
+  ts_query__write_array(&buffer, &self->captures.characters);
+  ts_query__write_array(&buffer, &self->captures.slices);
+  ts_query__write_array(&buffer, &self->predicate_values.characters);
+  ts_query__write_array(&buffer, &self->predicate_values.slices);
+  ts_query__write(&buffer, &self->capture_quantifiers.size, sizeof(uint32_t));
+  for (uint32_t i = 0; i < self->capture_quantifiers.size; i++) {
+    ts_query__write_array(&buffer, array_get(&self->capture_quantifiers, i));
+  }
+  ts_query__write_array(&buffer, &self->steps);
+  ts_query__write_array(&buffer, &self->pattern_map);
+  ts_query__write_array(&buffer, &self->predicate_steps);
+  ts_query__write_array(&buffer, &self->patterns);
+  ts_query__write_array(&buffer, &self->step_offsets);
+  ts_query__write_array(&buffer, &self->negated_fields);
+  ts_query__write_array(&buffer, &self->repeat_symbols_with_rootless_patterns);
+
+  *length = buffer.size;
+  return buffer.contents;
+}
+
+// Check that each name in a deserialized symbol table lies within the table's
+// characters, is followed by a null character, and is valid UTF-8.
+static bool ts_query__symbol_table_is_valid(const SymbolTable *table) {
+  for (uint32_t i = 0; i < table->slices.size; i++) {
+    Slice slice = *array_get(&table->slices, i);
+    if (
+      slice.offset >= table->characters.size ||
+      slice.length >= table->characters.size - slice.offset ||
+      *array_get(&table->characters, slice.offset + slice.length) != 0
+    ) return false;
+
+    const uint8_t *name = (const uint8_t *)array_get(&table->characters, slice.offset);
+    uint32_t offset = 0;
+    while (offset < slice.length) {
+      int32_t code_point;
+      offset += ts_decode_utf8(&name[offset], slice.length - offset, &code_point);
+      if (code_point == TS_DECODE_ERROR) return false;
+    }
+  }
+  return true;
+}
+
+// Get one of the steps that a query cursor moves to from the given step
+// without consuming a node, by following its alternative. Returns `NONE` when
+// there is no such step.
+static uint16_t ts_query__step_successor(const TSQuery *self, uint16_t step_index, unsigned i) {
+  const QueryStep *step = array_get(&self->steps, step_index);
+  if (step->alternative_index == NONE) return NONE;
+  if (i == 0) return step->alternative_index;
+  if (i == 1 && step->is_pass_through) return step_index + 1;
+  return NONE;
+}
+
+// Check that following the alternatives of a deserialized query's steps never
+// leads back to the same step, which would make a query cursor loop forever.
+static bool ts_query__steps_are_acyclic(const TSQuery *self) {
+  typedef struct {
+    uint16_t step_index;
+    uint8_t successor_index;
+  } StackEntry;
+
+  enum { UNVISITED, IN_PROGRESS, FINISHED };
+  uint8_t *marks = ts_calloc(self->steps.size, sizeof(uint8_t));
+  Array(StackEntry) stack = array_new();
+  bool result = true;
+
+  for (uint16_t i = 0; i < self->steps.size && result; i++) {
+    if (marks[i] != UNVISITED) continue;
+    marks[i] = IN_PROGRESS;
+    array_push(&stack, ((StackEntry) {.step_index = i, .successor_index = 0}));
+    while (stack.size > 0) {
+      StackEntry *entry = array_back(&stack);
+      if (entry->successor_index == 2) {
+        marks[entry->step_index] = FINISHED;
+        array_pop(&stack);
+        continue;
+      }
+      uint16_t successor = ts_query__step_successor(
+        self,
+        entry->step_index,
+        entry->successor_index++
+      );
+      if (successor == NONE || marks[successor] == FINISHED) continue;
+      if (marks[successor] == IN_PROGRESS) {
+        result = false;
+        break;
+      }
+      marks[successor] = IN_PROGRESS;
+      array_push(&stack, ((StackEntry) {.step_index = successor, .successor_index = 0}));
+    }
+  }
+
+  array_delete(&stack);
+  ts_free(marks);
+  return result;
+}
+
+// Check that every index stored in a deserialized query refers to an existing
+// symbol or field of the language, or to an existing element of one of the
+// query's own arrays, so that executing the query never reads out of bounds.
+static bool ts_query__is_valid(const TSQuery *self, uint32_t wildcard_root_pattern_count) {
+  uint32_t symbol_count = ts_language_symbol_count(self->language);
+  uint32_t field_count = ts_language_field_count(self->language);
+  uint32_t capture_count = self->captures.slices.size;
+  uint32_t string_count = self->predicate_values.slices.size;
+
+  if (
+    !ts_query__symbol_table_is_valid(&self->captures) ||
+    !ts_query__symbol_table_is_valid(&self->predicate_values) ||
+    capture_count > NONE ||
+    self->steps.size > NONE ||
+    self->patterns.size > NONE ||
+    self->capture_quantifiers.size != self->patterns.size ||
+    wildcard_root_pattern_count > self->pattern_map.size
+  ) return false;
+
+  for (uint32_t i = 0; i < self->capture_quantifiers.size; i++) {
+    const CaptureQuantifiers *capture_quantifiers = array_get(&self->capture_quantifiers, i);
+    for (uint32_t j = 0; j < capture_quantifiers->size; j++) {
+      if (*array_get(capture_quantifiers, j) > TSQuantifierOneOrMore) return false;
+    }
+  }
+
+  // Every sequence of steps must end with a step that finishes its pattern.
+  if (self->steps.size == 0) return false;
+  const QueryStep *last_step = array_back(&self->steps);
+  if (last_step->depth != PATTERN_DONE_MARKER || last_step->alternative_index != NONE) {
+    return false;
+  }
+
+  // Each list of negated fields is terminated by a zero.
+  if (self->negated_fields.size > 0 && *array_back(&self->negated_fields) != 0) return false;
+  for (uint32_t i = 0; i < self->negated_fields.size; i++) {
+    if (*array_get(&self->negated_fields, i) > field_count) return false;
+  }
+
+  for (uint32_t i = 0; i < self->steps.size; i++) {
+    const QueryStep *step = array_get(&self->steps, i);
+    if (
+      (step->symbol >= symbol_count && step->symbol != ts_builtin_sym_error) ||
+      step->supertype_symbol >= symbol_count ||
+      step->field > field_count ||
+      (step->alternative_index != NONE && step->alternative_index >= self->steps.size) ||
+      (step->negated_field_list_id != 0 && step->negated_field_list_id >= self->negated_fields.size)
+    ) return false;
+    for (unsigned j = 0; j < MAX_STEP_CAPTURE_COUNT; j++) {
+      if (step->capture_ids[j] != NONE && step->capture_ids[j] >= capture_count) return false;
+    }
+  }
+
+  for (uint32_t i = 0; i < self->pattern_map.size; i++) {
+    const PatternEntry *entry = array_get(&self->pattern_map, i);
+    if (entry->step_index >= self->steps.size || entry->pattern_index >= self->patterns.size) {
+      return false;
+    }
+  }
+
+  for (uint32_t i = 0; i < self->patterns.size; i++) {
+    const QueryPattern *pattern = array_get(&self->patterns, i);
+    if (
+      pattern->steps.length == 0 ||
+      pattern->steps.offset >= self->steps.size ||
+      pattern->steps.length > self->steps.size - pattern->steps.offset ||
+      pattern->predicate_steps.offset > self->predicate_steps.size ||
+      pattern->predicate_steps.length > self->predicate_steps.size - pattern->predicate_steps.offset
+    ) return false;
+  }
+
+  for (uint32_t i = 0; i < self->predicate_steps.size; i++) {
+    const TSQueryPredicateStep *step = array_get(&self->predicate_steps, i);
+    switch (step->type) {
+      case TSQueryPredicateStepTypeDone:
+        break;
+      case TSQueryPredicateStepTypeCapture:
+        if (step->value_id >= capture_count) return false;
+        break;
+      case TSQueryPredicateStepTypeString:
+        if (step->value_id >= string_count) return false;
+        break;
+      default:
+        return false;
+    }
+  }
+
+  for (uint32_t i = 0; i < self->step_offsets.size; i++) {
+    if (array_get(&self->step_offsets, i)->step_index >= self->steps.size) return false;
+  }
+
+  return ts_query__steps_are_acyclic(self);
+}
+
+TSQuery *ts_query_deserialize(
+  const TSLanguage *language,
+  const char *data,
+  uint32_t length,
+  TSQueryError *error_type
+) {
+  if (
+    !language ||
+    language->abi_version > TREE_SITTER_LANGUAGE_VERSION ||
+    language->abi_version < TREE_SITTER_MIN_COMPATIBLE_LANGUAGE_VERSION
+  ) {
+    *error_type = TSQueryErrorLanguage;
+    return NULL;
+  }
+
+  QueryReader reader = {.data = data, .length = length, .offset = 0, .failed = false};
+  QuerySerializationHeader header;
+  if (
+    !ts_query__read(&reader, &header, sizeof(header)) ||
+    header.magic != QUERY_SERIALIZATION_MAGIC ||
+    header.version != QUERY_SERIALIZATION_VERSION ||
+    header.step_size != sizeof(QueryStep) ||
+    header.pattern_size != sizeof(QueryPattern) ||
+    header.pattern_entry_size != sizeof(PatternEntry)
+  ) {
+    *error_type = TSQueryErrorSyntax;
+    return NULL;
+  }
+
+  if (header.language_hash != ts_query__language_hash(language)) {
+    *error_type = TSQueryErrorLanguage;
+    return NULL;
+  }
+
+  TSQuery *self = ts_malloc(sizeof(TSQuery));
+  *self = (TSQuery) {
+    .steps = array_new(),
+    .pattern_map = array_new(),
+    .captures = symbol_table_new(),
+    .capture_quantifiers = array_new(),
+    .predicate_values = symbol_table_new(),
+    .predicate_steps = array_new(),
+    .patterns = array_new(),
+    .step_offsets = array_new(),
+    .string_buffer = array_new(),
+    .negated_fields = array_new(),
+    .repeat_symbols_with_rootless_patterns = array_new(),
+    .wildcard_root_pattern_count = (uint16_t)header.wildcard_root_pattern_count,
+    .language = ts_language_copy(language),
+  };
+
+  ts_query__read_array(&reader, &self->captures.characters);
+  ts_query__read_array(&reader, &self->captures.slices);
+  ts_query__read_array(&reader, &self->predicate_values.characters);
+  ts_query__read_array(&reader, &self->predicate_values.slices);
+  uint32_t pattern_count = 0;
+  if (ts_query__read(&reader, &pattern_count, sizeof(uint32_t))) {
+    for (uint32_t i = 0; i < pattern_count && !reader.failed; i++) {
+      CaptureQuantifiers capture_quantifiers = capture_quantifiers_new();
+      ts_query__read_array(&reader, &capture_quantifiers);
+      array_push(&self->capture_quantifiers, capture_quantifiers);
+    }
+  }
+  ts_query__read_array(&reader, &self->steps);
+  ts_query__read_array(&reader, &self->pattern_map);
+  ts_query__read_array(&reader, &self->predicate_steps);
+  ts_query__read_array(&reader, &self->patterns);
+  ts_query__read_array(&reader, &self->step_offsets);
+  ts_query__read_array(&reader, &self->negated_fields);
+  ts_query__read_array(&reader, &self->repeat_symbols_with_rootless_patterns);
+
+  if (
+    reader.failed ||
+    reader.offset != reader.length ||
+    !ts_query__is_valid(self, header.wildcard_root_pattern_count)
+  ) {
+    *error_type = TSQueryErrorSyntax;
+    ts_query_delete(self);
+    return NULL;
+  }
+
+  *error_type = TSQueryErrorNone;
+  return self;
+}
+
 uint32_t ts_query_pattern_count(const TSQuery *self) {
   return self->patterns.size;
 }
@@ -3348,6 +3729,36 @@ bool ts_query_is_pattern_non_local(
   }
 }
 
+uint32_t ts_query_pattern_depth(
+  const TSQuery *self,
+  uint32_t pattern_index
+) {
+  if (pattern_index >= self->patterns.size) return 0;
+  const QueryPattern *pattern = array_get(&self->patterns, pattern_index);
+  uint32_t depth = 0;
+  for (uint32_t i = 0; i < pattern->steps.length; i++) {
+    const QueryStep *step = array_get(&self->steps, pattern->steps.offset + i);
+    if (step->depth != PATTERN_DONE_MARKER && step->depth + 1u > depth) {
+      depth = step->depth + 1u;
+    }
+  }
+  return depth;
+}
+
+bool ts_query_is_pattern_wildcard_rooted(
+  const TSQuery *self,
+  uint32_t pattern_index
+) {
+  for (unsigned i = 0; i < self->pattern_map.size; i++) {
+    PatternEntry *entry = array_get(&self->pattern_map, i);
+    if (
+      entry->pattern_index == pattern_index &&
+      array_get(&self->steps, entry->step_index)->symbol == WILDCARD_SYMBOL
+    ) return true;
+  }
+  return false;
+}
+
 bool ts_query_is_pattern_guaranteed_at_step(
   const TSQuery *self,
   uint32_t byte_offset
@@ -3442,6 +3853,7 @@ TSQueryCursor *ts_query_cursor_new(void) {
       .end_byte = UINT32_MAX,
     },
     .max_start_depth = UINT32_MAX,
+    .pattern_index = UINT32_MAX,
     .operation_count = 0,
   };
   array_reserve(&self->states, 8);
@@ -3518,12 +3930,23 @@ void ts_query_cursor_exec(
   self->ascending = false;
   self->halted = false;
   self->query = query;
+  self->pattern_index = UINT32_MAX;
   self->did_exceed_match_limit = false;
   self->operation_count = 0;
   self->query_options = NULL;
   self->query_state = (TSQueryCursorState) {0};
 }
 
+void ts_query_cursor_exec_for_pattern(
+  TSQueryCursor *self,
+  const TSQuery *query,
+  uint32_t pattern_index,
+  TSNode node
+) {
+  ts_query_cursor_exec(self, query, node);
+  self->pattern_index = pattern_index;
+}
+
 void ts_query_cursor_exec_with_options(
   TSQueryCursor *self,
   const TSQuery *query,
@@ -3571,6 +3994,15 @@ bool ts_query_cursor_set_point_range(
   return true;
 }
 
+void ts_query_cursor_point_range(
+  const TSQueryCursor *self,
+  TSPoint *start_point,
+  TSPoint *end_point
+) {
+  *start_point = self->included_range.start_point;
+  *end_point = self->included_range.end_point;
+}
+
 bool ts_query_cursor_set_containing_byte_range(
   TSQueryCursor *self,
   uint32_t start_byte,
@@ -4216,7 +4648,8 @@ static inline bool ts_query_cursor__advance(
                 (parent_intersects_range && !parent_is_error)) &&
               (!step->field || field_id == step->field) &&
               (!step->supertype_symbol || supertype_count > 0) &&
-              (start_depth <= self->max_start_depth)
+              (start_depth <= self->max_start_depth) &&
+              (self->pattern_index == UINT32_MAX || pattern->pattern_index == self->pattern_index)
             ) {
               ts_query_cursor__add_state(self, pattern);
             }
@@ -4238,7 +4671,8 @@ static inline bool ts_query_cursor__advance(
                 node_intersects_range :
                 (parent_intersects_range && !parent_is_error)) &&
               (!step->field || field_id == step->field) &&
-              (start_depth <= self->max_start_depth)
+              (start_depth <= self->max_start_depth) &&
+              (self->pattern_index == UINT32_MAX || pattern->pattern_index == self->pattern_index)
             ) {
               ts_query_cursor__add_state(self, pattern);
             }
@@ -159,7 +159,10 @@ static inline bool ts_subtree_can_inline(Length padding, Length size, uint32_t l
     padding.extent.column < TS_MAX_INLINE_TREE_LENGTH &&
     size.bytes < TS_MAX_INLINE_TREE_LENGTH &&
     size.extent.row == 0 &&
-    size.extent.column < TS_MAX_INLINE_TREE_LENGTH &&
+    // Inline subtrees don't store the size's column, which is assumed to be
+    // its byte count. That is not the case when the lexer gives no width to
+    // the `\r` of a CRLF line ending.
+    size.extent.column == size.bytes &&
     lookahead_bytes < 16;
 }
 
@@ -289,6 +292,32 @@ MutableSubtree ts_subtree_make_mut(SubtreePool *pool, Subtree self) {
   return result;
 }
 
+// Copy a subtree along with all of its descendants, so that the result shares
+// no heap-allocated nodes with the original.
+Subtree ts_subtree_deep_copy(Subtree self) {
+  if (self.data.is_inline) return self;
+  MutableSubtree result = ts_subtree_clone(self);
+  MutableSubtreeArray stack = array_new();
+  array_push(&stack, result);
+  while (stack.size > 0) {
+    MutableSubtree tree = array_pop(&stack);
+    Subtree *children = ts_subtree_children(tree);
+    for (uint32_t i = 0; i < tree.ptr->child_count; i++) {
+      Subtree child = children[i];
+      if (child.data.is_inline) continue;
+      MutableSubtree copy = ts_subtree_clone(child);
+
+      // Cloning the parent retained this child, but the copy no longer refers to
+      // it. The original tree still holds a reference, so this can't reach zero.
+      atomic_dec((volatile uint32_t *)&child.ptr->ref_count);
+      children[i] = ts_subtree_from_mut(copy);
+      if (copy.ptr->child_count > 0) array_push(&stack, copy);
+    }
+  }
+  array_delete(&stack);
+  return ts_subtree_from_mut(result);
+}
+
 void ts_subtree_compress(
   MutableSubtree self,
   unsigned count,
@@ -818,6 +847,24 @@ static size_t ts_subtree__write_char_to_string(char *str, size_t n, int32_t chr)
 
 static const char *const ROOT_FIELD = "__ROOT__";
 
+typedef struct {
+  bool (*write)(void *payload, const char *text, uint32_t length);
+  void *payload;
+  bool is_stopped;
+} StringWriter;
+
+static void string_writer__write(StringWriter *self, const char *text) {
+  if (!self->is_stopped && !self->write(self->payload, text, (uint32_t)strlen(text))) {
+    self->is_stopped = true;
+  }
+}
+
+static void string_writer__write_char(StringWriter *self, int32_t chr) {
+  char buffer[16];
+  ts_subtree__write_char_to_string(buffer, sizeof(buffer), chr);
+  string_writer__write(self, buffer);
+}
+
 typedef struct {
   Subtree subtree;
   TSSymbol alias_symbol;
@@ -834,14 +881,11 @@ typedef struct {
   const TSFieldMapEntry *field_map_end;
 } WriteToStringFrame;
 
-static size_t ts_subtree__write_to_string(
-  Subtree self, char *string, size_t limit,
+static void ts_subtree__write_to_string(
+  Subtree self, StringWriter *writer,
   const TSLanguage *language, bool include_all,
   TSSymbol root_alias_symbol, bool root_alias_is_named, const char *root_field_name
 ) {
-  char *cursor = string;
-  char **writer = (limit > 1) ? &cursor : &string;
-
   Array(WriteToStringFrame) stack = array_new();
   array_push(&stack, ((WriteToStringFrame) {
     .subtree = self,
@@ -851,18 +895,19 @@ static size_t ts_subtree__write_to_string(
     .is_root = root_field_name == ROOT_FIELD,
   }));
 
-  while (stack.size) {
+  while (stack.size && !writer->is_stopped) {
     WriteToStringFrame *frame = array_back(&stack);
     Subtree node = frame->subtree;
 
     if (!node.ptr) {
       if (!frame->is_root) {
-        cursor += snprintf(*writer, limit, " ");
+        string_writer__write(writer, " ");
         if (frame->field_name) {
-          cursor += snprintf(*writer, limit, "%s: ", frame->field_name);
+          string_writer__write(writer, frame->field_name);
+          string_writer__write(writer, ": ");
         }
       }
-      cursor += snprintf(*writer, limit, "(NULL)");
+      string_writer__write(writer, "(NULL)");
       (void)array_pop(&stack);
       continue;
     }
@@ -879,38 +924,47 @@ static size_t ts_subtree__write_to_string(
 
       if (is_visible) {
         if (!frame->is_root) {
-          cursor += snprintf(*writer, limit, " ");
+          string_writer__write(writer, " ");
           if (frame->field_name) {
-            cursor += snprintf(*writer, limit, "%s: ", frame->field_name);
+            string_writer__write(writer, frame->field_name);
+            string_writer__write(writer, ": ");
           }
         }
 
         if (ts_subtree_is_error(node) && ts_subtree_child_count(node) == 0 && node.ptr->size.bytes > 0) {
-          cursor += snprintf(*writer, limit, "(UNEXPECTED ");
-          cursor += ts_subtree__write_char_to_string(*writer, limit, node.ptr->lookahead_char);
+          string_writer__write(writer, "(UNEXPECTED ");
+          string_writer__write_char(writer, node.ptr->lookahead_char);
         } else {
           TSSymbol symbol = frame->alias_symbol ? frame->alias_symbol : ts_subtree_symbol(node);
           const char *symbol_name = ts_language_symbol_name(language, symbol);
           if (ts_subtree_missing(node)) {
-            cursor += snprintf(*writer, limit, "(MISSING ");
+            string_writer__write(writer, "(MISSING ");
             if (frame->alias_is_named || ts_subtree_named(node)) {
-              cursor += snprintf(*writer, limit, "%s", symbol_name);
+              string_writer__write(writer, symbol_name);
             } else {
-              cursor += snprintf(*writer, limit, "\"%s\"", symbol_name);
+              string_writer__write(writer, "\"");
+              string_writer__write(writer, symbol_name);
+              string_writer__write(writer, "\"");
             }
           } else {
-            cursor += snprintf(*writer, limit, "(%s", symbol_name);
+            string_writer__write(writer, "(");
+            string_writer__write(writer, symbol_name);
           }
         }
       } else if (frame->is_root) {
         TSSymbol symbol = frame->alias_symbol ? frame->alias_symbol : ts_subtree_symbol(node);
         const char *symbol_name = ts_language_symbol_name(language, symbol);
         if (ts_subtree_child_count(node) > 0) {
-          cursor += snprintf(*writer, limit, "(%s", symbol_name);
+          string_writer__write(writer, "(");
+          string_writer__write(writer, symbol_name);
         } else if (ts_subtree_named(node)) {
-          cursor += snprintf(*writer, limit, "(%s)", symbol_name);
+          string_writer__write(writer, "(");
+          string_writer__write(writer, symbol_name);
+          string_writer__write(writer, ")");
         } else {
-          cursor += snprintf(*writer, limit, "(\"%s\")", symbol_name);
+          string_writer__write(writer, "(\"");
+          string_writer__write(writer, symbol_name);
+          string_writer__write(writer, "\")");
         }
       }
 
@@ -965,12 +1019,18 @@ static size_t ts_subtree__write_to_string(
       continue;
     }
 
-    if (frame->is_visible) cursor += snprintf(*writer, limit, ")");
+    if (frame->is_visible) string_writer__write(writer, ")");
     (void)array_pop(&stack);
   }
 
   array_delete(&stack);
-  return cursor - string;
+}
+
+typedef Array(char) CharArray;
+
+static bool ts_subtree__append_to_string(void *payload, const char *text, uint32_t length) {
+  array_extend((CharArray *)payload, length, text);
+  return true;
 }
 
 char *ts_subtree_string(
@@ -980,19 +1040,30 @@ char *ts_subtree_string(
   const TSLanguage *language,
   bool include_all
 ) {
-  char scratch_string[1];
-  size_t size = ts_subtree__write_to_string(
-    self, scratch_string, 1,
-    language, include_all,
-    alias_symbol, alias_is_named, ROOT_FIELD
-  ) + 1;
-  char *result = ts_malloc(size * sizeof(char));
+  CharArray string = array_new();
+  ts_subtree_write_string(
+    self, alias_symbol, alias_is_named, language, include_all,
+    ts_subtree__append_to_string, &string
+  );
+  array_push(&string, '\0');
+  return string.contents;
+}
+
+void ts_subtree_write_string(
+  Subtree self,
+  TSSymbol alias_symbol,
+  bool alias_is_named,
+  const TSLanguage *language,
+  bool include_all,
+  bool (*write)(void *payload, const char *text, uint32_t length),
+  void *payload
+) {
+  StringWriter writer = {write, payload, false};
   ts_subtree__write_to_string(
-    self, result, size,
+    self, &writer,
     language, include_all,
     alias_symbol, alias_is_named, ROOT_FIELD
   );
-  return result;
 }
 
 void ts_subtree__print_dot_graph(const Subtree *self, uint32_t start_offset,
@@ -216,6 +216,7 @@ Subtree ts_subtree_new_missing_leaf(
   const TSLanguage *language
 );
 MutableSubtree ts_subtree_make_mut(SubtreePool *pool, Subtree self);
+Subtree ts_subtree_deep_copy(Subtree self);
 void ts_subtree_retain(Subtree self);
 void ts_subtree_release(SubtreePool *pool, Subtree self);
 int ts_subtree_compare(Subtree left, Subtree right, SubtreePool *pool);
@@ -224,6 +225,10 @@ void ts_subtree_compress(MutableSubtree self, unsigned count, const TSLanguage *
 void ts_subtree_summarize_children(MutableSubtree self, const TSLanguage *language);
 Subtree ts_subtree_edit(Subtree self, const TSInputEdit *edit, SubtreePool *pool);
 char *ts_subtree_string(Subtree self, TSSymbol alias_symbol, bool alias_is_named, const TSLanguage *language, bool include_all);
+void ts_subtree_write_string(
+  Subtree self, TSSymbol alias_symbol, bool alias_is_named, const TSLanguage *language, bool include_all,
+  bool (*write)(void *payload, const char *text, uint32_t length), void *payload
+);
 void ts_subtree_print_dot_graph(Subtree self, const TSLanguage *language, FILE *f);
 Subtree ts_subtree_last_external_token(Subtree tree);
 const ExternalScannerState *ts_subtree_external_scanner_state(Subtree self);
@@ -24,6 +24,11 @@ TSTree *ts_tree_copy(const TSTree *self) {
   return ts_tree_new(self->root, self->language, self->included_ranges, self->included_range_count);
 }
 
+TSTree *ts_tree_deep_copy(const TSTree *self) {
+  Subtree root = ts_subtree_deep_copy(self->root);
+  return ts_tree_new(root, self->language, self->included_ranges, self->included_range_count);
+}
+
 void ts_tree_delete(TSTree *self) {
   if (!self) return;
 
@@ -69,6 +74,11 @@ TSRange *ts_tree_included_ranges(const TSTree *self, uint32_t *length) {
   return ranges;
 }
 
+const TSRange *ts_tree_included_ranges_view(const TSTree *self, uint32_t *length) {
+  *length = self->included_range_count;
+  return self->included_ranges;
+}
+
 TSRange *ts_tree_get_changed_ranges(const TSTree *old_tree, const TSTree *new_tree, uint32_t *length) {
   TreeCursor cursor1 = {NULL, array_new(), 0};
   TreeCursor cursor2 = {NULL, array_new(), 0};
@@ -111,7 +121,9 @@ int _ts_dup(HANDLE handle) {
 }
 
 void ts_tree_print_dot_graph(const TSTree *self, int fd) {
-  FILE *file = _fdopen(_ts_dup((HANDLE)_get_osfhandle(fd)), "a");
+  // Open in binary mode so that the output matches other platforms, rather
+  // than having its newlines translated.
+  FILE *file = _fdopen(_ts_dup((HANDLE)_get_osfhandle(fd)), "ab");
   ts_subtree_print_dot_graph(self->root, self->language, file);
   fclose(file);
 }